@@ -0,0 +1,420 @@
+//! Dynamic (sparse) segment tree with lazy propagation.
+//!
+//! Nodes are created only when an update reaches them, so the
+//! number of elements `n` can be as large as `10^18`, without
+//! coordinate compression. Each update creates `O(log n)` nodes.
+//!
+//! Uses the same [`LazyMonoid`] as `lazy_segtree`. If you never
+//! need range updates, let `L = ()` and make `combine_lazy` and
+//! `unlazy` do nothing; then only `set` and `prod` are relevant.
+//!
+//! # Example
+//! ```
+//! # extern crate lazy_segtree;
+//! # use dynamic_segtree::DynamicSegtree;
+//! # use lazy_segtree::LazyMonoid;
+//! /// Range add, range sum.
+//! struct AddSum;
+//! impl LazyMonoid for AddSum {
+//!     type V = i64;
+//!     type L = i64;
+//!     const ID: i64 = 0;
+//!     fn op(x: &i64, y: &i64) -> i64 { x + y }
+//!     const NOT_LAZY: i64 = 0;
+//!     fn combine_lazy(cur: &mut i64, up: &i64) { *cur += up; }
+//!     fn unlazy(slot: &mut i64, lazy: i64, nl: usize, nr: usize) {
+//!         *slot += lazy * (nr - nl + 1) as i64;
+//!     }
+//! }
+//!
+//! let mut seg = DynamicSegtree::<AddSum>::new(1_000_000_000, 0);
+//! seg.set(500_000_000, 3);
+//! seg.apply(10..=20, 5);
+//! assert_eq!(seg.prod(0..1_000_000_000), 58);
+//! assert_eq!(seg.prod(15..500_000_000), 30);
+//! assert_eq!(seg.get(500_000_000), 3);
+//! ```
+
+extern crate lazy_segtree;
+
+use std::convert::TryFrom;
+use std::ops::RangeBounds;
+
+use lazy_segtree::{compose, to_half_open, LazyMonoid};
+
+/// Dynamic segment tree over [`LazyMonoid`] `T`.
+///
+/// Internally, the tree covers `0..2^h` for the smallest `2^h >= n`,
+/// where the elements from `n` on are `ID`, and never get lazy values.
+/// Node `0` is the root, and a child index `0` means the child
+/// has not been created yet; all of its elements are the default value.
+///
+/// In the method descriptions, let `n` be the number of elements.
+#[derive(Clone, Debug)]
+pub struct DynamicSegtree<T: LazyMonoid> {
+    n: usize,
+    height: u32,
+    /// `full[h]` is the default value repeated `2^h` times, for `2^h <= n`.
+    full: Vec<T::V>,
+    /// `part[h]` is the default value repeated `n mod 2^h` times,
+    /// i.e. the initial value of the node at height `h` containing `n`.
+    part: Vec<T::V>,
+    val: Vec<T::V>,
+    lazy: Vec<T::L>,
+    ch: Vec<[u32; 2]>,
+}
+
+impl<T: LazyMonoid> DynamicSegtree<T> {
+    /// Returns a dynamic segment tree with `n` elements,
+    /// all initialized to `default`.
+    ///
+    /// 🕒 `O(log n)`.
+    pub fn new(n: usize, default: T::V) -> Self {
+        let height = n.next_power_of_two().trailing_zeros();
+        let mut full = vec![default];
+        for h in 1..=height as usize {
+            if n >> h == 0 {
+                break;
+            }
+            full.push(T::op(&full[h - 1], &full[h - 1]));
+        }
+        let mut part = vec![T::ID];
+        for h in 0..height as usize {
+            part.push(if n >> h & 1 == 1 {
+                T::op(&full[h], &part[h])
+            } else {
+                part[h].clone()
+            });
+        }
+        let mut seg = Self {
+            n,
+            height,
+            full,
+            part,
+            val: vec![],
+            lazy: vec![T::NOT_LAZY],
+            ch: vec![[0; 2]],
+        };
+        seg.val.push(seg.implicit(height, 0).clone());
+        seg
+    }
+
+    /// Returns `n`.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.n
+    }
+
+    /// Returns whether `n == 0`.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    /// Returns the initial value of the node at height `h` covering `nl..`.
+    fn implicit(&self, h: u32, nl: usize) -> &T::V {
+        if nl >= self.n {
+            &self.part[0]
+        } else if self.n - nl >= 1 << h {
+            &self.full[h as usize]
+        } else {
+            &self.part[h as usize]
+        }
+    }
+
+    /// Applies `lazy` to node `x` at height `h` covering `nl..`,
+    /// which must start before `n`.
+    fn all_apply(&mut self, x: usize, h: u32, nl: usize, lazy: &T::L) {
+        let nr = (nl + (1 << h)).min(self.n) - 1;
+        T::unlazy(&mut self.val[x], lazy.clone(), nl, nr);
+        if h > 0 {
+            T::combine_lazy(&mut self.lazy[x], lazy);
+        }
+    }
+
+    /// Returns the `c`-th child of node `x` at height `h`,
+    /// covering `cl..`, creating it if necessary.
+    fn child(&mut self, x: usize, c: usize, h: u32, cl: usize) -> usize {
+        if self.ch[x][c] == 0 {
+            self.ch[x][c] = u32::try_from(self.val.len()).unwrap();
+            let v = self.implicit(h - 1, cl).clone();
+            self.val.push(v);
+            self.lazy.push(T::NOT_LAZY);
+            self.ch.push([0; 2]);
+        }
+        self.ch[x][c] as usize
+    }
+
+    fn push(&mut self, x: usize, h: u32, nl: usize) {
+        if self.lazy[x] == T::NOT_LAZY {
+            return;
+        }
+        let lazy = std::mem::replace(&mut self.lazy[x], T::NOT_LAZY);
+        for c in 0..2 {
+            let cl = nl + (c << (h - 1));
+            if cl < self.n {
+                let child = self.child(x, c, h, cl);
+                self.all_apply(child, h - 1, cl, &lazy);
+            }
+        }
+    }
+
+    fn pull(&mut self, x: usize, h: u32, nl: usize) {
+        let [left, right] = [0, 1].map(|c| match self.ch[x][c] {
+            0 => self.implicit(h - 1, nl + (c << (h - 1))),
+            c => &self.val[c as usize],
+        });
+        let v = T::op(left, right);
+        self.val[x] = v;
+    }
+
+    /// Sets the `i`-th element to `v`.
+    ///
+    /// 🕒 `O(log n)`.
+    ///
+    /// ⚠️ Panics if `i >= n`.
+    pub fn set(&mut self, i: usize, v: T::V) {
+        assert!(i < self.n, "index {} out of {}", i, self.n);
+        let mut path = vec![];
+        let (mut x, mut nl) = (0, 0);
+        for h in (1..=self.height).rev() {
+            self.push(x, h, nl);
+            path.push((x, h, nl));
+            let c = (i - nl) >> (h - 1);
+            nl += c << (h - 1);
+            x = self.child(x, c, h, nl);
+        }
+        self.val[x] = v;
+        for (x, h, nl) in path.into_iter().rev() {
+            self.pull(x, h, nl);
+        }
+    }
+
+    /// Returns the `i`-th element.
+    ///
+    /// 🕒 `O(log n)`.
+    ///
+    /// ⚠️ Panics if `i >= n`.
+    #[must_use]
+    pub fn get(&self, i: usize) -> T::V {
+        assert!(i < self.n, "index {} out of {}", i, self.n);
+        self.prod(i..=i)
+    }
+
+    /// Applies `lazy` to every element in `range`.
+    /// Both `l..r` and `l..=r` are accepted.
+    ///
+    /// 🕒 `O(log n)`.
+    ///
+    /// ⚠️ Panics if the range is out of bounds.
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn apply(&mut self, range: impl RangeBounds<usize>, lazy: T::L) {
+        let (l, r) = to_half_open(range, self.n);
+        if l < r {
+            self.apply_inner(0, self.height, 0, l, r, &lazy);
+        }
+    }
+    fn apply_inner(&mut self, node: usize, h: u32, nl: usize, l: usize, r: usize, lazy: &T::L) {
+        if l <= nl && nl + (1 << h) <= r {
+            self.all_apply(node, h, nl, lazy);
+            return;
+        }
+        self.push(node, h, nl);
+        let half = 1 << (h - 1);
+        for c in 0..2 {
+            let cl = nl + c * half;
+            if l < cl + half && cl < r {
+                let child = self.child(node, c, h, cl);
+                self.apply_inner(child, h - 1, cl, l, r, lazy);
+            }
+        }
+        self.pull(node, h, nl);
+    }
+
+    /// Returns the product of the elements in `range`,
+    /// or `ID` if the range is empty.
+    /// Both `l..r` and `l..=r` are accepted.
+    ///
+    /// 🕒 `O(log n)`.
+    ///
+    /// ⚠️ Panics if the range is out of bounds.
+    pub fn prod(&self, range: impl RangeBounds<usize>) -> T::V {
+        let (l, r) = to_half_open(range, self.n);
+        if l == r {
+            return T::ID;
+        }
+        self.prod_inner(Some(0), self.height, 0, l, r, &T::NOT_LAZY)
+    }
+    /// `pend` is the composition of the lazy values on the ancestors.
+    fn prod_inner(
+        &self,
+        node: Option<usize>,
+        h: u32,
+        nl: usize,
+        l: usize,
+        r: usize,
+        pend: &T::L,
+    ) -> T::V {
+        let nr = nl + (1 << h);
+        if r <= nl || nr <= l {
+            return T::ID;
+        }
+        if l <= nl && nr <= r {
+            let mut v = node
+                .map_or_else(|| self.implicit(h, nl), |x| &self.val[x])
+                .clone();
+            if *pend != T::NOT_LAZY {
+                T::unlazy(&mut v, pend.clone(), nl, nr - 1);
+            }
+            return v;
+        }
+        let (ch, pend) = node.map_or_else(
+            || ([0; 2], pend.clone()),
+            |node| (self.ch[node], compose::<T>(&self.lazy[node], pend)),
+        );
+        let half = 1 << (h - 1);
+        let child = |c: usize| (ch[c] != 0).then_some(ch[c] as usize);
+        let left = self.prod_inner(child(0), h - 1, nl, l, r, &pend);
+        let right = self.prod_inner(child(1), h - 1, nl + half, l, r, &pend);
+        T::op(&left, &right)
+    }
+
+    /// Returns the product of all elements.
+    ///
+    /// 🕒 `O(log n)`.
+    #[must_use]
+    pub fn all_prod(&self) -> T::V {
+        self.prod(..)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::DynamicSegtree;
+    use lazy_segtree::LazyMonoid;
+
+    /// Range add on a list of elements, to catch
+    /// non-commutative mistakes and wrong node ranges.
+    struct AddConcat;
+    impl LazyMonoid for AddConcat {
+        type V = Vec<u64>;
+        type L = u64;
+        const ID: Vec<u64> = vec![];
+        fn op(x: &Vec<u64>, y: &Vec<u64>) -> Vec<u64> {
+            x.iter().chain(y).copied().collect()
+        }
+        const NOT_LAZY: u64 = 0;
+        fn combine_lazy(cur: &mut u64, up: &u64) {
+            *cur += up;
+        }
+        fn unlazy(slot: &mut Vec<u64>, lazy: u64, nl: usize, nr: usize) {
+            assert_eq!(slot.len(), nr - nl + 1);
+            for x in slot {
+                *x += lazy;
+            }
+        }
+    }
+
+    struct AddSum;
+    impl LazyMonoid for AddSum {
+        type V = u64;
+        type L = u64;
+        const ID: u64 = 0;
+        fn op(x: &u64, y: &u64) -> u64 {
+            x + y
+        }
+        const NOT_LAZY: u64 = 0;
+        fn combine_lazy(cur: &mut u64, up: &u64) {
+            *cur += up;
+        }
+        fn unlazy(slot: &mut u64, lazy: u64, nl: usize, nr: usize) {
+            *slot += lazy * (nr - nl + 1) as u64;
+        }
+    }
+
+    enum Query {
+        Set(usize, u64),
+        Apply(usize, usize, u64),
+        Prod(usize, usize),
+    }
+
+    /// Runs `queries` on `n` elements, initially all `default`.
+    fn do_test_dynamic_segtree(n: usize, default: u64, queries: Vec<Query>) {
+        let mut seg = DynamicSegtree::<AddConcat>::new(n, vec![default]);
+        let mut brute = vec![default; n];
+        for (qi, query) in queries.into_iter().enumerate() {
+            match query {
+                Query::Set(i, v) => {
+                    seg.set(i, vec![v]);
+                    brute[i] = v;
+                }
+                Query::Apply(l, r, v) => {
+                    seg.apply(l..r, v);
+                    for x in &mut brute[l..r] {
+                        *x += v;
+                    }
+                }
+                Query::Prod(l, r) => assert_eq!(seg.prod(l..r), brute[l..r], "query {qi}"),
+            }
+            assert_eq!(seg.all_prod(), brute, "query {qi}");
+        }
+        for (i, &x) in brute.iter().enumerate() {
+            assert_eq!(seg.get(i), [x]);
+        }
+    }
+
+    #[test]
+    fn test_dynamic_segtree() {
+        do_test_dynamic_segtree(
+            11,
+            7,
+            vec![
+                Query::Prod(0, 11),
+                Query::Set(3, 1),
+                Query::Prod(2, 5),
+                Query::Apply(2, 9, 10),
+                Query::Prod(0, 4),
+                Query::Prod(8, 11),
+                Query::Apply(0, 11, 1),
+                Query::Set(10, 0),
+                Query::Apply(9, 11, 3),
+                Query::Prod(5, 5),
+                Query::Prod(6, 10),
+                Query::Set(0, 2),
+                Query::Apply(0, 1, 4),
+                Query::Prod(0, 2),
+            ],
+        );
+        do_test_dynamic_segtree(1, 0, vec![Query::Apply(0, 1, 5), Query::Prod(0, 1)]);
+        do_test_dynamic_segtree(
+            16,
+            1,
+            vec![
+                Query::Apply(4, 12, 2),
+                Query::Set(7, 0),
+                Query::Apply(0, 16, 1),
+                Query::Prod(3, 13),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_dynamic_segtree_large() {
+        let n = 1 << 60;
+        let mut seg = DynamicSegtree::<AddSum>::new(n, 1);
+        seg.set(n - 1, 5);
+        seg.apply(n - 3..n - 1, 2);
+        assert_eq!(seg.prod(n - 4..), 1 + 3 + 3 + 5);
+        assert_eq!(seg.get(12345), 1);
+        assert_eq!(seg.all_prod(), (1 << 60) + 8);
+        assert!(seg.val.len() < 300);
+
+        // the padding up to `2^61` must not be part of any product
+        let n = (1 << 60) + 1;
+        let mut seg = DynamicSegtree::<AddSum>::new(n, 1 << 3);
+        assert_eq!(seg.all_prod(), (1 << 63) + (1 << 3));
+        seg.apply(.., 1);
+        seg.set(0, 0);
+        assert_eq!(seg.all_prod(), (1 << 63) + (1 << 60));
+    }
+}
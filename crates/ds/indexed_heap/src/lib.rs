@@ -0,0 +1,250 @@
+//! Indexed 4-ary min-heap with decrease-key.
+//!
+//! # Usage
+//! Each element is an index in `0..n` with a key `K: Ord`.
+//! An index is in the heap at most once, and its key
+//! can be decreased in place, so Dijkstra's algorithm
+//! never sees stale entries.
+//!
+//! # Example
+//! ```
+//! # use indexed_heap::IndexedHeap;
+//! let mut heap = IndexedHeap::new(3);
+//! heap.push(0, 10);
+//! heap.push(1, 20);
+//! assert!(heap.push(1, 5));
+//! assert!(!heap.push(0, 15));
+//! assert_eq!(heap.pop(), Some((1, 5)));
+//! assert_eq!(heap.pop(), Some((0, 10)));
+//! assert_eq!(heap.pop(), None);
+//! ```
+
+const ARITY: usize = 4;
+const NONE: usize = usize::MAX;
+
+/// Indexed heap over the indices `0..n`.
+///
+/// In the method descriptions, let `n` be the number of indices.
+#[derive(Clone, Debug)]
+pub struct IndexedHeap<K> {
+    /// The keys with their indices, stored together
+    /// so that comparisons do not go through `pos`.
+    heap: Vec<(K, usize)>,
+    /// `pos[i]` is the position of `i` in `heap`, or `NONE`.
+    pos: Vec<usize>,
+}
+
+impl<K: Ord> IndexedHeap<K> {
+    /// Creates an empty heap over the indices `0..n`.
+    #[must_use]
+    pub fn new(n: usize) -> Self {
+        Self {
+            heap: vec![],
+            pos: vec![NONE; n],
+        }
+    }
+
+    /// Returns the number of indices in the heap.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    /// Returns whether the heap is empty.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    /// Returns whether `i` is in the heap.
+    ///
+    /// ⚠️ Panics if `i >= n`.
+    #[must_use]
+    pub fn contains(&self, i: usize) -> bool {
+        self.pos[i] != NONE
+    }
+
+    /// Returns the key of `i`, if it is in the heap.
+    ///
+    /// ⚠️ Panics if `i >= n`.
+    #[must_use]
+    pub fn get(&self, i: usize) -> Option<&K> {
+        self.heap.get(self.pos[i]).map(|(k, _)| k)
+    }
+
+    /// Returns the index with the minimum key and its key.
+    #[must_use]
+    pub fn peek(&self) -> Option<(usize, &K)> {
+        self.heap.first().map(|(k, i)| (*i, k))
+    }
+
+    /// Inserts `i` with `key` if it is not in the heap, or
+    /// decreases its key to `key` if that is smaller.
+    /// Returns whether the heap changed.
+    ///
+    /// 🕒 `O(log n)`.
+    ///
+    /// ⚠️ Panics if `i >= n`.
+    pub fn push(&mut self, i: usize, key: K) -> bool {
+        let x = self.pos[i];
+        if x == NONE {
+            self.heap.push((key, i));
+            self.sift_up(self.heap.len() - 1);
+        } else {
+            if self.heap[x].0 <= key {
+                return false;
+            }
+            self.heap[x].0 = key;
+            self.sift_up(x);
+        }
+        true
+    }
+
+    /// Removes and returns the index with the minimum key and its key,
+    /// or `None` if the heap is empty.
+    ///
+    /// 🕒 `O(log n)`.
+    pub fn pop(&mut self) -> Option<(usize, K)> {
+        let last = self.heap.pop()?;
+        let (key, top) = if self.heap.is_empty() {
+            last
+        } else {
+            let top = std::mem::replace(&mut self.heap[0], last);
+            self.sift_down(0);
+            top
+        };
+        self.pos[top] = NONE;
+        Some((top, key))
+    }
+
+    /// Moves the entry at `x` up to its place, updating `pos`.
+    fn sift_up(&mut self, mut x: usize) {
+        while x > 0 {
+            let p = (x - 1) / ARITY;
+            if self.heap[p].0 <= self.heap[x].0 {
+                break;
+            }
+            self.heap.swap(x, p);
+            self.pos[self.heap[x].1] = x;
+            x = p;
+        }
+        self.pos[self.heap[x].1] = x;
+    }
+
+    /// Moves the entry at `x` down to its place, updating `pos`.
+    fn sift_down(&mut self, mut x: usize) {
+        let len = self.heap.len();
+        loop {
+            let first = x * ARITY + 1;
+            if first >= len {
+                break;
+            }
+            let mut c = first;
+            for y in first + 1..len.min(first + ARITY) {
+                if self.heap[y].0 < self.heap[c].0 {
+                    c = y;
+                }
+            }
+            if self.heap[x].0 <= self.heap[c].0 {
+                break;
+            }
+            self.heap.swap(x, c);
+            self.pos[self.heap[x].1] = x;
+            x = c;
+        }
+        self.pos[self.heap[x].1] = x;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    enum Query {
+        Push(usize, usize),
+        Pop,
+    }
+
+    fn do_test_indexed_heap(n: usize, queries: Vec<Query>) {
+        let mut heap = IndexedHeap::new(n);
+        let mut naive: Vec<Option<usize>> = vec![None; n];
+        for (qi, query) in queries.into_iter().enumerate() {
+            match query {
+                Query::Push(i, key) => {
+                    let changed = naive[i].iter().all(|&k| key < k);
+                    assert_eq!(heap.push(i, key), changed, "query {qi}");
+                    if changed {
+                        naive[i] = Some(key);
+                    }
+                }
+                Query::Pop => {
+                    let want = (0..n)
+                        .filter_map(|i| naive[i].map(|k| (k, i)))
+                        .min()
+                        .map(|(k, _)| k);
+                    let got = heap.pop();
+                    assert_eq!(got.map(|(_, k)| k), want, "query {qi}");
+                    if let Some((i, k)) = got {
+                        assert_eq!(naive[i], Some(k), "query {qi}");
+                        naive[i] = None;
+                    }
+                }
+            }
+            assert_eq!(heap.len(), naive.iter().flatten().count(), "query {qi}");
+            for (i, k) in naive.iter().enumerate() {
+                assert_eq!(heap.contains(i), k.is_some(), "query {qi}");
+                assert_eq!(heap.get(i), k.as_ref(), "query {qi}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_indexed_heap() {
+        do_test_indexed_heap(
+            6,
+            vec![
+                Query::Pop,
+                Query::Push(2, 50),
+                Query::Push(4, 30),
+                Query::Push(0, 70),
+                Query::Push(2, 60),
+                Query::Push(2, 20),
+                Query::Push(5, 20),
+                Query::Pop,
+                Query::Push(0, 10),
+                Query::Push(1, 40),
+                Query::Pop,
+                Query::Push(3, 5),
+                Query::Push(4, 30),
+                Query::Pop,
+                Query::Pop,
+                Query::Push(0, 1),
+                Query::Pop,
+                Query::Pop,
+                Query::Pop,
+                Query::Pop,
+            ],
+        );
+        do_test_indexed_heap(
+            1,
+            vec![
+                Query::Push(0, 3),
+                Query::Push(0, 3),
+                Query::Pop,
+                Query::Push(0, 4),
+            ],
+        );
+        // Every index repeatedly decreased, as in Dijkstra.
+        let n = 30;
+        let queries = (0..900)
+            .map(|i| {
+                if i % 4 == 0 {
+                    Query::Pop
+                } else {
+                    Query::Push(i * 7 % n, 1000 - i % 997)
+                }
+            })
+            .collect();
+        do_test_indexed_heap(n, queries);
+    }
+}
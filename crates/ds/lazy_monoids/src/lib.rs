@@ -0,0 +1,395 @@
+//! Ready-made [`LazyMonoid`]s for `lazy_segtree`.
+//!
+//! Each type here is zero-sized, and only serves as the `T`
+//! in `LazySegtree<T>`. The numeric ones are generic over [`Num`],
+//! which is implemented for primitive integers.
+//!
+//! Type | Value | Lazy
+//! -- | -- | --
+//! [`RangeAddSum<T>`] | sum | add `x`
+//! [`RangeAddMin<T>`] | min | add `x`
+//! [`RangeAddMax<T>`] | max | add `x`
+//! [`RangeAssignSum<T>`] | sum | assign `Some(x)`
+//! [`RangeAssignMin<T>`] | min | assign `Some(x)`
+//! [`RangeAssignMax<T>`] | max | assign `Some(x)`
+//! [`RangeAffineSum<MOD>`] | sum of `Modint<MOD>` | `(a, b)`: `x -> ax+b`
+//! [`RangeFlipCount`] | number of `1`s | flip if `true`
+//!
+//! # Example
+//! ```
+//! # extern crate lazy_segtree;
+//! # use lazy_monoids::RangeAddMin;
+//! # use lazy_segtree::LazySegtree;
+//! let mut seg = LazySegtree::<RangeAddMin<i64>>::new(5, |i| i as i64);
+//! seg.apply(0..3, 10);
+//! assert_eq!(seg.prod(..), 3);
+//! assert_eq!(seg.prod(0..3), 10);
+//! ```
+//!
+//! - [LC Range Affine Range Sum](https://judge.yosupo.jp/problem/range_affine_range_sum)
+//! ```ignore
+//! let n = oj.usize();
+//! let q = oj.usize();
+//! let init = oj.vec::<Modint99>(n);
+//! let mut seg = LazySegtree::<RangeAffineSum<998244353>>::new(n, |i| init[i]);
+//! for _ in 0..q {
+//!     if oj.usize() == 0 {
+//!         let (l, r) = (oj.usize(), oj.usize());
+//!         seg.apply(l..r, (oj.read(), oj.read()));
+//!     } else {
+//!         oj.write(seg.prod(oj.usize()..oj.usize())).ln();
+//!     }
+//! }
+//! ```
+
+extern crate lazy_segtree;
+extern crate modint;
+
+use std::convert::TryFrom;
+use std::fmt::Debug;
+use std::marker::PhantomData;
+use std::ops::{Add, Mul};
+
+use lazy_segtree::LazyMonoid;
+use modint::Modint;
+
+/// Numbers usable in the monoids of this crate.
+pub trait Num: Copy + Debug + Ord + Add<Output = Self> + Mul<Output = Self> {
+    const ZERO: Self;
+    const MIN: Self;
+    const MAX: Self;
+    /// Converts the length of a range into `Self`.
+    fn of_len(len: usize) -> Self;
+    /// Returns `self + other`, saturating at `MIN` and `MAX`.
+    #[must_use]
+    fn saturating_add(self, other: Self) -> Self;
+}
+
+macro_rules! impl_num {
+	($($T:ty) *) => { $(
+		impl Num for $T {
+			const ZERO: Self = 0;
+			const MIN: Self = <$T>::MIN;
+			const MAX: Self = <$T>::MAX;
+			fn of_len(len: usize) -> Self { <$T>::try_from(len).unwrap() }
+			fn saturating_add(self, other: Self) -> Self { <$T>::saturating_add(self, other) }
+		}
+	)* };
+}
+impl_num!(u32 u64 u128 usize i32 i64 i128 isize);
+
+/// Range add, range sum.
+#[derive(Debug)]
+pub struct RangeAddSum<T>(PhantomData<T>);
+impl<T: Num> LazyMonoid for RangeAddSum<T> {
+    type V = T;
+    type L = T;
+    const ID: T = T::ZERO;
+    fn op(x: &T, y: &T) -> T {
+        *x + *y
+    }
+    const NOT_LAZY: T = T::ZERO;
+    fn combine_lazy(cur: &mut T, up: &T) {
+        *cur = *cur + *up;
+    }
+    fn unlazy(slot: &mut T, lazy: T, nl: usize, nr: usize) {
+        *slot = *slot + lazy * T::of_len(nr - nl + 1);
+    }
+}
+
+/// Range add, range min.
+///
+/// The additions saturate, so that adding to `ID`, i.e. `T::MAX`,
+/// does not overflow.
+#[derive(Debug)]
+pub struct RangeAddMin<T>(PhantomData<T>);
+impl<T: Num> LazyMonoid for RangeAddMin<T> {
+    type V = T;
+    type L = T;
+    const ID: T = T::MAX;
+    fn op(x: &T, y: &T) -> T {
+        *x.min(y)
+    }
+    const NOT_LAZY: T = T::ZERO;
+    fn combine_lazy(cur: &mut T, up: &T) {
+        *cur = *cur + *up;
+    }
+    fn unlazy(slot: &mut T, lazy: T, _: usize, _: usize) {
+        *slot = slot.saturating_add(lazy);
+    }
+}
+
+/// Range add, range max.
+///
+/// The additions saturate, so that adding to `ID`, i.e. `T::MIN`,
+/// does not overflow.
+#[derive(Debug)]
+pub struct RangeAddMax<T>(PhantomData<T>);
+impl<T: Num> LazyMonoid for RangeAddMax<T> {
+    type V = T;
+    type L = T;
+    const ID: T = T::MIN;
+    fn op(x: &T, y: &T) -> T {
+        *x.max(y)
+    }
+    const NOT_LAZY: T = T::ZERO;
+    fn combine_lazy(cur: &mut T, up: &T) {
+        *cur = *cur + *up;
+    }
+    fn unlazy(slot: &mut T, lazy: T, _: usize, _: usize) {
+        *slot = slot.saturating_add(lazy);
+    }
+}
+
+/// Range assign, range sum.
+#[derive(Debug)]
+pub struct RangeAssignSum<T>(PhantomData<T>);
+impl<T: Num> LazyMonoid for RangeAssignSum<T> {
+    type V = T;
+    type L = Option<T>;
+    const ID: T = T::ZERO;
+    fn op(x: &T, y: &T) -> T {
+        *x + *y
+    }
+    const NOT_LAZY: Option<T> = None;
+    fn combine_lazy(cur: &mut Option<T>, up: &Option<T>) {
+        if up.is_some() {
+            *cur = *up;
+        }
+    }
+    fn unlazy(slot: &mut T, lazy: Option<T>, nl: usize, nr: usize) {
+        if let Some(x) = lazy {
+            *slot = x * T::of_len(nr - nl + 1);
+        }
+    }
+}
+
+/// Range assign, range min.
+#[derive(Debug)]
+pub struct RangeAssignMin<T>(PhantomData<T>);
+impl<T: Num> LazyMonoid for RangeAssignMin<T> {
+    type V = T;
+    type L = Option<T>;
+    const ID: T = T::MAX;
+    fn op(x: &T, y: &T) -> T {
+        *x.min(y)
+    }
+    const NOT_LAZY: Option<T> = None;
+    fn combine_lazy(cur: &mut Option<T>, up: &Option<T>) {
+        if up.is_some() {
+            *cur = *up;
+        }
+    }
+    fn unlazy(slot: &mut T, lazy: Option<T>, _: usize, _: usize) {
+        if let Some(x) = lazy {
+            *slot = x;
+        }
+    }
+}
+
+/// Range assign, range max.
+#[derive(Debug)]
+pub struct RangeAssignMax<T>(PhantomData<T>);
+impl<T: Num> LazyMonoid for RangeAssignMax<T> {
+    type V = T;
+    type L = Option<T>;
+    const ID: T = T::MIN;
+    fn op(x: &T, y: &T) -> T {
+        *x.max(y)
+    }
+    const NOT_LAZY: Option<T> = None;
+    fn combine_lazy(cur: &mut Option<T>, up: &Option<T>) {
+        if up.is_some() {
+            *cur = *up;
+        }
+    }
+    fn unlazy(slot: &mut T, lazy: Option<T>, _: usize, _: usize) {
+        if let Some(x) = lazy {
+            *slot = x;
+        }
+    }
+}
+
+/// Range affine, range sum, modulo `MOD`.
+///
+/// The lazy value `(a, b)` maps each element `x` to `ax+b`.
+#[derive(Debug)]
+pub struct RangeAffineSum<const MOD: u32>;
+impl<const MOD: u32> LazyMonoid for RangeAffineSum<MOD> {
+    type V = Modint<MOD>;
+    type L = (Modint<MOD>, Modint<MOD>);
+    const ID: Modint<MOD> = Modint::new(0);
+    fn op(x: &Modint<MOD>, y: &Modint<MOD>) -> Modint<MOD> {
+        *x + *y
+    }
+    const NOT_LAZY: Self::L = (Modint::new(1), Modint::new(0));
+    fn combine_lazy(cur: &mut Self::L, up: &Self::L) {
+        *cur = (up.0 * cur.0, up.0 * cur.1 + up.1);
+    }
+    fn unlazy(slot: &mut Modint<MOD>, lazy: Self::L, nl: usize, nr: usize) {
+        *slot = lazy.0 * *slot + lazy.1 * Modint::from(nr - nl + 1);
+    }
+}
+
+/// Range flip, range count of `1`s.
+///
+/// Initialize each element to `0` or `1`.
+#[derive(Debug)]
+pub struct RangeFlipCount;
+impl LazyMonoid for RangeFlipCount {
+    type V = usize;
+    type L = bool;
+    const ID: usize = 0;
+    fn op(x: &usize, y: &usize) -> usize {
+        x + y
+    }
+    const NOT_LAZY: bool = false;
+    fn combine_lazy(cur: &mut bool, up: &bool) {
+        *cur ^= up;
+    }
+    fn unlazy(slot: &mut usize, lazy: bool, nl: usize, nr: usize) {
+        if lazy {
+            *slot = nr - nl + 1 - *slot;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use lazy_segtree::LazySegtree;
+
+    type M = modint::Modint99;
+
+    enum Query<L> {
+        Apply(usize, usize, L),
+        Prod(usize, usize),
+    }
+
+    /// Runs `queries` on `LazySegtree<T>` and a brute-force array,
+    /// where `act` applies a lazy value to an element,
+    /// and `fold` folds a nonempty range.
+    fn do_test_monoid<T: LazyMonoid>(
+        initial: Vec<T::V>,
+        queries: Vec<Query<T::L>>,
+        act: impl Fn(&mut T::V, &T::L),
+        fold: impl Fn(&[T::V]) -> T::V,
+    ) where
+        T::V: PartialEq,
+    {
+        let mut seg = LazySegtree::<T>::new(initial.len(), |i| initial[i].clone());
+        let mut brute = initial;
+        for (qi, query) in queries.into_iter().enumerate() {
+            match query {
+                Query::Apply(l, r, lazy) => {
+                    for x in &mut brute[l..r] {
+                        act(x, &lazy);
+                    }
+                    seg.apply(l..r, lazy);
+                }
+                Query::Prod(l, r) => {
+                    assert_eq!(seg.prod(l..r), fold(&brute[l..r]), "query {qi}");
+                }
+            }
+        }
+    }
+
+    /// The same queries for all numeric monoids.
+    fn num_queries<L>(lazy: impl Fn(i64) -> L) -> Vec<Query<L>> {
+        vec![
+            Query::Prod(0, 8),
+            Query::Apply(1, 5, lazy(3)),
+            Query::Prod(0, 3),
+            Query::Prod(4, 8),
+            Query::Apply(3, 8, lazy(-7)),
+            Query::Prod(2, 4),
+            Query::Apply(0, 8, lazy(2)),
+            Query::Prod(0, 8),
+            Query::Apply(5, 6, lazy(-10)),
+            Query::Prod(5, 7),
+            Query::Prod(6, 8),
+            Query::Prod(1, 6),
+        ]
+    }
+
+    #[test]
+    fn test_range_add() {
+        let initial = vec![3, -1, 4, -1, 5, -9, 2, 6];
+        let add = |x: &mut i64, y: &i64| *x += y;
+        let queries = || num_queries(|x| x);
+        do_test_monoid::<RangeAddSum<i64>>(initial.clone(), queries(), add, |a| a.iter().sum());
+        do_test_monoid::<RangeAddMin<i64>>(initial.clone(), queries(), add, |a| {
+            *a.iter().min().unwrap()
+        });
+        do_test_monoid::<RangeAddMax<i64>>(initial, queries(), add, |a| *a.iter().max().unwrap());
+    }
+
+    #[test]
+    fn test_range_add_identity() {
+        let mut min = i64::MAX;
+        RangeAddMin::<i64>::unlazy(&mut min, 5, 0, 3);
+        assert_eq!(min, RangeAddMin::<i64>::ID);
+        let mut max = i64::MIN;
+        RangeAddMax::<i64>::unlazy(&mut max, -5, 0, 3);
+        assert_eq!(max, RangeAddMax::<i64>::ID);
+        let mut max = RangeAddMax::<u32>::ID;
+        RangeAddMax::<u32>::unlazy(&mut max, 5, 0, 3);
+        assert_eq!(max, 5);
+    }
+
+    #[test]
+    fn test_range_assign() {
+        let initial = vec![3, -1, 4, -1, 5, -9, 2, 6];
+        let assign = |x: &mut i64, y: &Option<i64>| *x = y.unwrap();
+        let queries = || num_queries(Some);
+        do_test_monoid::<RangeAssignSum<i64>>(initial.clone(), queries(), assign, |a| {
+            a.iter().sum()
+        });
+        do_test_monoid::<RangeAssignMin<i64>>(initial.clone(), queries(), assign, |a| {
+            *a.iter().min().unwrap()
+        });
+        do_test_monoid::<RangeAssignMax<i64>>(initial, queries(), assign, |a| {
+            *a.iter().max().unwrap()
+        });
+    }
+
+    #[test]
+    fn test_range_affine() {
+        let m = |x: u32| M::from(x);
+        do_test_monoid::<RangeAffineSum<998_244_353>>(
+            vec![m(1), m(2), m(3), m(4), m(5)],
+            vec![
+                Query::Prod(0, 5),
+                Query::Apply(1, 4, (m(2), m(3))),
+                Query::Prod(0, 2),
+                Query::Apply(0, 3, (m(998_244_352), m(10))),
+                Query::Prod(2, 5),
+                Query::Apply(2, 5, (m(0), m(7))),
+                Query::Apply(0, 5, (m(123_456_789), m(987_654_321))),
+                Query::Prod(0, 5),
+                Query::Prod(1, 3),
+            ],
+            |x, (a, b)| *x = *a * *x + *b,
+            |a| a.iter().fold(M::new(0), |acc, x| acc + *x),
+        );
+    }
+
+    #[test]
+    fn test_range_flip() {
+        do_test_monoid::<RangeFlipCount>(
+            vec![1, 0, 0, 1, 1, 0, 1],
+            vec![
+                Query::Prod(0, 7),
+                Query::Apply(1, 5, true),
+                Query::Prod(0, 4),
+                Query::Apply(0, 7, true),
+                Query::Apply(3, 6, false),
+                Query::Prod(2, 7),
+                Query::Apply(2, 3, true),
+                Query::Prod(0, 7),
+            ],
+            |x, flip| *x ^= usize::from(*flip),
+            |a| a.iter().sum(),
+        );
+    }
+}
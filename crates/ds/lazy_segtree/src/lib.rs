@@ -1,8 +1,48 @@
 //! Segment tree with lazy propagation.
 //!
-//! TODO add doc stuff
+//! # Usage
+//! Create a new type (likely zero-sized), and implement [`LazyMonoid`]
+//! for it. `V` and `op` must form a monoid with identity `ID`,
+//! and `L` must act on `V` through `unlazy`:
+//! - `combine_lazy(cur, up)` makes `cur` equivalent to
+//!   "apply `cur`, then apply `up`";
+//! - `unlazy(slot, lazy, nl, nr)` applies `lazy` to `slot`, the product
+//!   of the elements `nl..=nr`. The range is given so that you can
+//!   handle things like range sums.
+//!
+//! The tree is iterative and uses exactly `2n` nodes, so `n`
+//! does not have to be a power of 2.
+//!
+//! # Example
+//! ```
+//! # use lazy_segtree::{LazyMonoid, LazySegtree};
+//! /// Range add, range sum.
+//! struct AddSum;
+//! impl LazyMonoid for AddSum {
+//!     type V = i64;
+//!     type L = i64;
+//!     const ID: i64 = 0;
+//!     fn op(x: &i64, y: &i64) -> i64 { x + y }
+//!     const NOT_LAZY: i64 = 0;
+//!     fn combine_lazy(cur: &mut i64, up: &i64) { *cur += up; }
+//!     fn unlazy(slot: &mut i64, lazy: i64, nl: usize, nr: usize) {
+//!         *slot += lazy * (nr - nl + 1) as i64;
+//!     }
+//! }
+//!
+//! let mut seg = LazySegtree::<AddSum>::new(5, |i| i as i64);
+//! assert_eq!(seg.query(1, 3), 6); // inclusive
+//! seg.apply(2..5, 10); // half-open
+//! assert_eq!(seg.prod(1..=3), 26);
+//! seg.set(0, 7);
+//! assert_eq!(seg.get(4), 14);
+//! assert_eq!(seg.all_prod(), 47);
+//! assert_eq!(seg.max_right(0, |s| *s <= 20), 3);
+//! assert_eq!(format!("{:?}", seg), "[7, 1, 12, 13, 14]");
+//! ```
 
 use std::fmt::Debug;
+use std::ops::{Bound, RangeBounds};
 
 pub trait LazyMonoid {
     /// Type of the value.
@@ -24,7 +64,13 @@ pub trait LazyMonoid {
     fn unlazy(slot: &mut Self::V, lazy: Self::L, nl: usize, nr: usize);
 }
 
-/// TODO: add custom Debug, showing only the actual values
+/// Segment tree over [`LazyMonoid`] `T`.
+///
+/// Node `i` has children `2i` and `2i+1`, and the `i`-th element
+/// is at node `n+i`. A node's value already includes its own lazy
+/// value, which is yet to be pushed to its children.
+///
+/// In the method descriptions, let `n` be the number of elements.
 #[derive(Clone)]
 pub struct LazySegtree<T: LazyMonoid> {
     n: usize,
@@ -34,78 +80,593 @@ pub struct LazySegtree<T: LazyMonoid> {
 
 impl<T: LazyMonoid> Debug for LazySegtree<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("LazySegtree")
-            .field("n", &self.n)
-            .field("arr", &self.arr)
-            .field("lazy", &self.lazy)
+        f.debug_list()
+            .entries((0..self.n).map(|i| self.get(i)))
             .finish()
     }
 }
 
+/// Converts `range` into a half-open range `l..r` within `0..n`.
+///
+/// ⚠️ Panics if the range is out of bounds.
+#[must_use]
+pub fn to_half_open(range: impl RangeBounds<usize>, n: usize) -> (usize, usize) {
+    let l = match range.start_bound() {
+        Bound::Included(&l) => l,
+        Bound::Excluded(&l) => l + 1,
+        Bound::Unbounded => 0,
+    };
+    let r = match range.end_bound() {
+        Bound::Included(&r) => r + 1,
+        Bound::Excluded(&r) => r,
+        Bound::Unbounded => n,
+    };
+    assert!(l <= r && r <= n, "bad range {}..{} on {}", l, r, n);
+    (l, r)
+}
+
+/// Returns the lazy value equivalent to "apply `old`, then apply `new`".
+#[must_use]
+pub fn compose<T: LazyMonoid>(old: &T::L, new: &T::L) -> T::L {
+    let mut cur = old.clone();
+    if *new != T::NOT_LAZY {
+        T::combine_lazy(&mut cur, new);
+    }
+    cur
+}
+
 impl<T: LazyMonoid> LazySegtree<T> {
+    /// Returns a segment tree with `size` elements,
+    /// where the `i`-th element is `init(i)`.
+    ///
+    /// 🕒 `O(n)`.
     pub fn new(size: usize, init: impl Fn(usize) -> T::V) -> Self {
-        // TODO don't use power of 2
-        let mut n = 1usize;
-        while n < size {
-            n <<= 1;
-        }
+        let n = size;
         let mut arr = vec![T::ID; n * 2];
-        let lazy = vec![T::NOT_LAZY; n * 2];
-        for (i, slot) in arr[n..n + size].iter_mut().enumerate() {
+        for (i, slot) in arr[n..].iter_mut().enumerate() {
             *slot = init(i);
         }
         for i in (1..n).rev() {
             arr[i] = T::op(&arr[i * 2], &arr[i * 2 + 1]);
         }
-        Self { n, arr, lazy }
+        Self {
+            n,
+            arr,
+            lazy: vec![T::NOT_LAZY; n],
+        }
+    }
+
+    /// Returns `n`.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.n
+    }
+
+    /// Returns whether `n == 0`.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    /// Returns the range `nl..=nr` of node `x` at height `h`.
+    const fn node_range(&self, x: usize, h: u32) -> (usize, usize) {
+        let nl = (x << h) - self.n;
+        (nl, nl + (1 << h) - 1)
+    }
+
+    /// Applies `lazy` to node `x` at height `h`.
+    fn all_apply(&mut self, x: usize, h: u32, lazy: &T::L) {
+        let (nl, nr) = self.node_range(x, h);
+        T::unlazy(&mut self.arr[x], lazy.clone(), nl, nr);
+        if x < self.n {
+            T::combine_lazy(&mut self.lazy[x], lazy);
+        }
+    }
+
+    /// Pushes the lazy values on the ancestors of the leaf `y`,
+    /// from top to bottom.
+    fn push_above(&mut self, y: usize) {
+        for h in (1..=y.ilog2()).rev() {
+            let x = y >> h;
+            if self.lazy[x] == T::NOT_LAZY {
+                continue;
+            }
+            let lazy = std::mem::replace(&mut self.lazy[x], T::NOT_LAZY);
+            self.all_apply(x * 2, h - 1, &lazy);
+            self.all_apply(x * 2 + 1, h - 1, &lazy);
+        }
+    }
+
+    /// Recomputes the ancestors of the leaf `y`, from bottom to top.
+    fn pull_above(&mut self, y: usize) {
+        for h in 1..=y.ilog2() {
+            let x = y >> h;
+            self.arr[x] = T::op(&self.arr[x * 2], &self.arr[x * 2 + 1]);
+            if self.lazy[x] != T::NOT_LAZY {
+                let (nl, nr) = self.node_range(x, h);
+                T::unlazy(&mut self.arr[x], self.lazy[x].clone(), nl, nr);
+            }
+        }
+    }
+
+    /// Returns the lazy value pending on the children of node `x`,
+    /// whose ancestors hold the lazy value `pend`.
+    fn below(&self, x: usize, pend: &T::L) -> T::L {
+        if x < self.n {
+            compose::<T>(&self.lazy[x], pend)
+        } else {
+            pend.clone()
+        }
+    }
+
+    /// Returns the actual value of node `x` at height `h`,
+    /// whose ancestors hold the lazy value `pend`.
+    fn eval(&self, x: usize, h: u32, pend: &T::L) -> T::V {
+        let mut v = self.arr[x].clone();
+        if *pend != T::NOT_LAZY {
+            let (nl, nr) = self.node_range(x, h);
+            T::unlazy(&mut v, pend.clone(), nl, nr);
+        }
+        v
+    }
+
+    /// Returns the canonical nodes of `l..r` at height `h`,
+    /// on the left and on the right side, if any.
+    fn boundary(&self, l: usize, r: usize, h: u32) -> (Option<usize>, Option<usize>) {
+        // the boundaries after `h` steps of the bottom-up loop
+        let (lo, hi) = (((l + self.n - 1) >> h) + 1, (r + self.n) >> h);
+        if lo >= hi {
+            return (None, None);
+        }
+        (
+            Some(lo).filter(|_| lo & 1 == 1),
+            Some(hi - 1).filter(|_| hi & 1 == 1),
+        )
+    }
+
+    /// Calls `f(x, h, pend)` on the nodes `x = pick(h)` for the heights
+    /// `h <= top`, from bottom to top, where the ancestors of `x` are
+    /// `base >> k` for `k > h`, and `pend` is their lazy value.
+    /// Stops and returns `false` as soon as `f` does.
+    fn visit_up(
+        &self,
+        base: usize,
+        top: u32,
+        pend: &T::L,
+        pick: &impl Fn(u32) -> Option<usize>,
+        f: &mut impl FnMut(usize, u32, &T::L) -> bool,
+    ) -> bool {
+        // the recursion only computes `pend` top-down, with depth `top`
+        if top > 0 && !self.visit_up(base, top - 1, &self.below(base >> top, pend), pick, f) {
+            return false;
+        }
+        if let Some(x) = pick(top) {
+            return f(x, top, pend);
+        }
+        true
+    }
+
+    /// Same as `visit_up`, but from top to bottom.
+    fn visit_down(
+        &self,
+        base: usize,
+        top: u32,
+        pick: &impl Fn(u32) -> Option<usize>,
+        f: &mut impl FnMut(usize, u32, &T::L) -> bool,
+    ) -> bool {
+        let mut pend = T::NOT_LAZY;
+        for h in (0..=top).rev() {
+            pend = self.below(base >> (h + 1), &pend);
+            if let Some(x) = pick(h) {
+                if !f(x, h, &pend) {
+                    return false;
+                }
+            }
+        }
+        true
     }
 
-    fn propagate(&mut self, i: usize, nl: usize, nr: usize) {
-        if self.lazy[i] == T::NOT_LAZY {
+    /// Calls `f(x, h, pend)` on the canonical nodes `x` at height `h`
+    /// covering `l..r`, from left to right, or from right to left
+    /// if `rev`, where `pend` is the lazy value of the ancestors of `x`.
+    /// Stops and returns `false` as soon as `f` does.
+    ///
+    /// 🕒 `O(log n)`, without allocation.
+    fn for_each_node(
+        &self,
+        l: usize,
+        r: usize,
+        rev: bool,
+        f: &mut impl FnMut(usize, u32, &T::L) -> bool,
+    ) -> bool {
+        if l == r {
+            return true;
+        }
+        // the ancestors of the canonical nodes are those of
+        // the nodes just outside the range
+        let (lb, rb) = (l + self.n - 1, r + self.n);
+        let top = rb.ilog2();
+        let left = |h| self.boundary(l, r, h).0;
+        let right = |h| self.boundary(l, r, h).1;
+        if rev {
+            self.visit_up(rb, top, &T::NOT_LAZY, &right, f) && self.visit_down(lb, top, &left, f)
+        } else {
+            self.visit_up(lb, top, &T::NOT_LAZY, &left, f) && self.visit_down(rb, top, &right, f)
+        }
+    }
+
+    /// Sets the `i`-th element to `v`.
+    ///
+    /// 🕒 `O(log n)`.
+    ///
+    /// ⚠️ Panics if `i >= n`.
+    pub fn set(&mut self, i: usize, v: T::V) {
+        assert!(i < self.n, "index {} out of {}", i, self.n);
+        let y = i + self.n;
+        self.push_above(y);
+        self.arr[y] = v;
+        self.pull_above(y);
+    }
+
+    /// Returns the `i`-th element.
+    ///
+    /// 🕒 `O(log n)`.
+    ///
+    /// ⚠️ Panics if `i >= n`.
+    #[must_use]
+    pub fn get(&self, i: usize) -> T::V {
+        assert!(i < self.n, "index {} out of {}", i, self.n);
+        let y = i + self.n;
+        let mut pend = T::NOT_LAZY;
+        for h in (1..=y.ilog2()).rev() {
+            pend = self.below(y >> h, &pend);
+        }
+        self.eval(y, 0, &pend)
+    }
+
+    /// Applies `lazy` to every element in `range`.
+    /// Both `l..r` and `l..=r` are accepted.
+    ///
+    /// 🕒 `O(log n)`.
+    ///
+    /// ⚠️ Panics if the range is out of bounds.
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn apply(&mut self, range: impl RangeBounds<usize>, lazy: T::L) {
+        let (l, r) = to_half_open(range, self.n);
+        if l == r {
             return;
         }
-        let lazy = std::mem::replace(&mut self.lazy[i], T::NOT_LAZY);
-        if i < self.n {
-            T::combine_lazy(&mut self.lazy[i * 2], &lazy);
-            T::combine_lazy(&mut self.lazy[i * 2 + 1], &lazy);
+        let (yl, yr) = (l + self.n, r - 1 + self.n);
+        self.push_above(yl);
+        self.push_above(yr);
+        let (mut l, mut r, mut h) = (yl, yr + 1, 0);
+        while l < r {
+            if l & 1 == 1 {
+                self.all_apply(l, h, &lazy);
+                l += 1;
+            }
+            if r & 1 == 1 {
+                r -= 1;
+                self.all_apply(r, h, &lazy);
+            }
+            l >>= 1;
+            r >>= 1;
+            h += 1;
         }
-        T::unlazy(&mut self.arr[i], lazy, nl, nr);
+        self.pull_above(yl);
+        self.pull_above(yr);
     }
 
+    /// Applies `lazy` to every element in `l..=r`.
+    ///
+    /// 🕒 `O(log n)`.
+    ///
+    /// ⚠️ Panics if `l > r + 1` or `r >= n`.
     pub fn update(&mut self, l: usize, r: usize, lazy: T::L) {
-        self.update_inner(l, r, &lazy, 1, 0, self.n - 1);
+        self.apply(l..=r, lazy);
     }
-    fn update_inner(&mut self, l: usize, r: usize, lazy: &T::L, i: usize, nl: usize, nr: usize) {
-        self.propagate(i, nl, nr);
-        if r < nl || nr < l {
-            return;
+
+    /// Returns the product of the elements in `range`,
+    /// or `ID` if the range is empty.
+    /// Both `l..r` and `l..=r` are accepted.
+    ///
+    /// 🕒 `O(log n)`.
+    ///
+    /// ⚠️ Panics if the range is out of bounds.
+    pub fn prod(&self, range: impl RangeBounds<usize>) -> T::V {
+        let (l, r) = to_half_open(range, self.n);
+        let mut acc = T::ID;
+        self.for_each_node(l, r, false, &mut |x, h, pend| {
+            acc = T::op(&acc, &self.eval(x, h, pend));
+            true
+        });
+        acc
+    }
+
+    /// Returns the product of the elements in `l..=r`.
+    ///
+    /// 🕒 `O(log n)`.
+    ///
+    /// ⚠️ Panics if `l > r + 1` or `r >= n`.
+    #[must_use]
+    pub fn query(&self, l: usize, r: usize) -> T::V {
+        self.prod(l..=r)
+    }
+
+    /// Returns the product of all elements.
+    ///
+    /// 🕒 `O(log n)`.
+    #[must_use]
+    pub fn all_prod(&self) -> T::V {
+        self.prod(..)
+    }
+
+    /// Returns the largest `r` such that `pred(prod(l..r))` holds,
+    /// assuming `pred` is monotone, i.e. once it fails for `l..r`,
+    /// it also fails for `l..r'` where `r' > r`.
+    ///
+    /// 🕒 `O(log n)` calls to `op` and `pred`.
+    ///
+    /// ⚠️ Panics if `l > n` or `pred(ID)` is false.
+    pub fn max_right(&self, l: usize, pred: impl Fn(&T::V) -> bool) -> usize {
+        assert!(l <= self.n, "index {} out of {}", l, self.n);
+        assert!(pred(&T::ID), "pred(ID) must be true");
+        let mut acc = T::ID;
+        let mut found = self.n;
+        self.for_each_node(l, self.n, false, &mut |mut x, mut h, pend| {
+            let nxt = T::op(&acc, &self.eval(x, h, pend));
+            if pred(&nxt) {
+                acc = nxt;
+                return true;
+            }
+            let mut pend = pend.clone();
+            while x < self.n {
+                pend = self.below(x, &pend);
+                x *= 2;
+                h -= 1;
+                let nxt = T::op(&acc, &self.eval(x, h, &pend));
+                if pred(&nxt) {
+                    acc = nxt;
+                    x += 1;
+                }
+            }
+            found = x - self.n;
+            false
+        });
+        found
+    }
+
+    /// Returns the smallest `l` such that `pred(prod(l..r))` holds,
+    /// assuming `pred` is monotone, i.e. once it fails for `l..r`,
+    /// it also fails for `l'..r` where `l' < l`.
+    ///
+    /// 🕒 `O(log n)` calls to `op` and `pred`.
+    ///
+    /// ⚠️ Panics if `r > n` or `pred(ID)` is false.
+    pub fn min_left(&self, r: usize, pred: impl Fn(&T::V) -> bool) -> usize {
+        assert!(r <= self.n, "index {} out of {}", r, self.n);
+        assert!(pred(&T::ID), "pred(ID) must be true");
+        let mut acc = T::ID;
+        let mut found = 0;
+        self.for_each_node(0, r, true, &mut |mut x, mut h, pend| {
+            let nxt = T::op(&self.eval(x, h, pend), &acc);
+            if pred(&nxt) {
+                acc = nxt;
+                return true;
+            }
+            let mut pend = pend.clone();
+            while x < self.n {
+                pend = self.below(x, &pend);
+                x = x * 2 + 1;
+                h -= 1;
+                let nxt = T::op(&self.eval(x, h, &pend), &acc);
+                if pred(&nxt) {
+                    acc = nxt;
+                    x -= 1;
+                }
+            }
+            found = x + 1 - self.n;
+            false
+        });
+        found
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{LazyMonoid, LazySegtree};
+
+    /// Range add on a list of elements, to catch
+    /// non-commutative mistakes and wrong node ranges.
+    struct AddConcat;
+    impl LazyMonoid for AddConcat {
+        type V = Vec<u64>;
+        type L = u64;
+        const ID: Vec<u64> = vec![];
+        fn op(x: &Vec<u64>, y: &Vec<u64>) -> Vec<u64> {
+            x.iter().chain(y).copied().collect()
         }
-        if l <= nl && nr <= r {
-            T::combine_lazy(&mut self.lazy[i], lazy);
-            self.propagate(i, nl, nr);
-            return;
+        const NOT_LAZY: u64 = 0;
+        fn combine_lazy(cur: &mut u64, up: &u64) {
+            *cur += up;
+        }
+        fn unlazy(slot: &mut Vec<u64>, lazy: u64, nl: usize, nr: usize) {
+            assert_eq!(slot.len(), nr - nl + 1);
+            for x in slot {
+                *x += lazy;
+            }
+        }
+    }
+
+    /// Range affine on a list of elements, to catch
+    /// lazy values composed in the wrong order.
+    struct AffineConcat;
+    impl LazyMonoid for AffineConcat {
+        type V = Vec<u64>;
+        type L = (u64, u64);
+        const ID: Vec<u64> = vec![];
+        fn op(x: &Vec<u64>, y: &Vec<u64>) -> Vec<u64> {
+            x.iter().chain(y).copied().collect()
+        }
+        const NOT_LAZY: (u64, u64) = (1, 0);
+        fn combine_lazy(cur: &mut (u64, u64), up: &(u64, u64)) {
+            *cur = (up.0.wrapping_mul(cur.0), affine(*up, cur.1));
         }
-        let m = (nl + nr) / 2;
-        self.update_inner(l, r, lazy, i * 2, nl, m);
-        self.update_inner(l, r, lazy, i * 2 + 1, m + 1, nr);
-        self.arr[i] = T::op(&self.arr[i * 2], &self.arr[i * 2 + 1]);
+        fn unlazy(slot: &mut Vec<u64>, lazy: (u64, u64), _: usize, _: usize) {
+            for x in slot {
+                *x = affine(lazy, *x);
+            }
+        }
+    }
+
+    fn affine((a, b): (u64, u64), x: u64) -> u64 {
+        a.wrapping_mul(x).wrapping_add(b)
     }
 
-    pub fn query(&mut self, l: usize, r: usize) -> T::V {
-        self.query_inner(l, r, 1, 0, self.n - 1)
+    enum Query<L> {
+        Set(usize, u64),
+        Apply(usize, usize, L),
+        Prod(usize, usize),
+        /// `max_right(l, |s| sum(s) <= bound)`.
+        MaxRight(usize, u64),
+        /// `min_left(r, |s| sum(s) <= bound)`.
+        MinLeft(usize, u64),
     }
-    fn query_inner(&mut self, l: usize, r: usize, i: usize, nl: usize, nr: usize) -> T::V {
-        self.propagate(i, nl, nr);
-        if r < nl || nr < l {
-            return T::ID;
+
+    /// Runs `queries` against a brute force, where `act`
+    /// applies a lazy value to a single element.
+    fn do_test_lazy_segtree<T>(
+        initial: Vec<u64>,
+        queries: Vec<Query<T::L>>,
+        act: fn(&T::L, u64) -> u64,
+    ) where
+        T: LazyMonoid<V = Vec<u64>>,
+    {
+        let n = initial.len();
+        let mut seg = LazySegtree::<T>::new(n, |i| vec![initial[i]]);
+        let mut brute = initial;
+        for (qi, query) in queries.into_iter().enumerate() {
+            let sum = |l: usize, r: usize| brute[l..r].iter().sum::<u64>();
+            let pred = |bound: u64| move |s: &Vec<u64>| s.iter().sum::<u64>() <= bound;
+            match query {
+                Query::Set(i, v) => {
+                    seg.set(i, vec![v]);
+                    brute[i] = v;
+                }
+                Query::Apply(l, r, lazy) => {
+                    for x in &mut brute[l..r] {
+                        *x = act(&lazy, *x);
+                    }
+                    seg.apply(l..r, lazy);
+                }
+                Query::Prod(l, r) => {
+                    assert_eq!(seg.prod(l..r), brute[l..r], "query {qi}");
+                    if l < r {
+                        assert_eq!(seg.query(l, r - 1), brute[l..r], "query {qi}");
+                    }
+                }
+                Query::MaxRight(l, bound) => {
+                    let ans = (l..=n).take_while(|&r| sum(l, r) <= bound).last();
+                    assert_eq!(Some(seg.max_right(l, pred(bound))), ans, "query {qi}");
+                }
+                Query::MinLeft(r, bound) => {
+                    let ans = (0..=r).rev().take_while(|&l| sum(l, r) <= bound).last();
+                    assert_eq!(Some(seg.min_left(r, pred(bound))), ans, "query {qi}");
+                }
+            }
+            assert_eq!(seg.all_prod(), brute, "query {qi}");
         }
-        if l <= nl && nr <= r {
-            return self.arr[i].clone();
+        for (i, &x) in brute.iter().enumerate() {
+            assert_eq!(seg.get(i), [x]);
+        }
+    }
+
+    #[test]
+    fn test_lazy_segtree_add() {
+        do_test_lazy_segtree::<AddConcat>(
+            vec![3, 1, 4, 1, 5, 9, 2, 6, 5, 3, 5],
+            vec![
+                Query::Prod(0, 11),
+                Query::Apply(2, 7, 10),
+                Query::Prod(1, 4),
+                Query::Prod(6, 6),
+                Query::Apply(0, 11, 1),
+                Query::Set(4, 0),
+                Query::Prod(3, 9),
+                Query::Apply(4, 5, 2),
+                Query::MaxRight(0, 30),
+                Query::MaxRight(4, 20),
+                Query::MaxRight(11, 0),
+                Query::MinLeft(11, 25),
+                Query::MinLeft(7, 14),
+                Query::MinLeft(0, 0),
+                Query::Apply(9, 11, 100),
+                Query::MaxRight(8, 1000),
+                Query::MinLeft(11, 100),
+                Query::Prod(10, 11),
+            ],
+            |lazy, x| x + lazy,
+        );
+        do_test_lazy_segtree::<AddConcat>(
+            vec![],
+            vec![
+                Query::Apply(0, 0, 1),
+                Query::Prod(0, 0),
+                Query::MaxRight(0, 0),
+                Query::MinLeft(0, 0),
+            ],
+            |lazy, x| x + lazy,
+        );
+        do_test_lazy_segtree::<AddConcat>(
+            vec![5],
+            vec![
+                Query::Apply(0, 1, 2),
+                Query::MaxRight(0, 6),
+                Query::MaxRight(0, 7),
+                Query::Set(0, 1),
+                Query::MinLeft(1, 1),
+            ],
+            |lazy, x| x + lazy,
+        );
+    }
+
+    #[test]
+    fn test_lazy_segtree_affine() {
+        do_test_lazy_segtree::<AffineConcat>(
+            vec![1, 2, 3, 4, 5, 6, 7],
+            vec![
+                Query::Apply(0, 5, (2, 1)),
+                Query::Apply(3, 7, (1, 3)),
+                Query::Prod(2, 6),
+                Query::Apply(1, 4, (3, 0)),
+                Query::Apply(0, 7, (1, 1)),
+                Query::Set(2, 0),
+                Query::Apply(2, 3, (2, 2)),
+                Query::Prod(0, 7),
+                Query::MaxRight(1, 40),
+                Query::MinLeft(7, 30),
+            ],
+            |&lazy, x| affine(lazy, x),
+        );
+    }
+
+    #[test]
+    fn test_lazy_segtree_affine_ranges() {
+        // every range of every small size, on top of each other,
+        // so that pending lazy values meet in every pattern
+        for n in 0..10 {
+            let mut queries = vec![];
+            for l in 0..=n {
+                for r in l..=n {
+                    queries.push(Query::Apply(l, r, (l as u64 % 2 + 1, r as u64)));
+                    for l in 0..=n {
+                        for r in l..=n {
+                            queries.push(Query::Prod(l, r));
+                        }
+                    }
+                }
+            }
+            do_test_lazy_segtree::<AffineConcat>(vec![0; n], queries, |&lazy, x| affine(lazy, x));
         }
-        let m = (nl + nr) / 2;
-        T::op(
-            &self.query_inner(l, r, i * 2, nl, m),
-            &self.query_inner(l, r, i * 2 + 1, m + 1, nr),
-        )
     }
 }
@@ -0,0 +1,367 @@
+//! Persistent segment tree.
+//!
+//! Every update returns a new [`Version`] and leaves the old ones
+//! intact, creating only `O(log n)` new nodes.
+//! Uses the `V`/`ID`/`op` part of [`LazyMonoid`] from `lazy_segtree`;
+//! the lazy part is ignored.
+//!
+//! # Example
+//! The classic use is the k-th smallest element in a range.
+//! Insert the elements one by one into a tree of counts,
+//! so that version `i` contains the first `i` elements.
+//! Then the counts of `a[l..r]` are version `r` minus version `l`.
+//! ```
+//! # extern crate lazy_segtree;
+//! # use persistent_segtree::PersistentSegtree;
+//! # use lazy_segtree::LazyMonoid;
+//! struct Count;
+//! impl LazyMonoid for Count {
+//!     type V = usize;
+//!     type L = ();
+//!     const ID: usize = 0;
+//!     fn op(x: &usize, y: &usize) -> usize { x + y }
+//!     const NOT_LAZY: () = ();
+//!     fn combine_lazy(_: &mut (), _: &()) {}
+//!     fn unlazy(_: &mut usize, _: (), _: usize, _: usize) {}
+//! }
+//!
+//! let a = [3, 1, 4, 1, 5, 9, 2, 6];
+//! let mut seg = PersistentSegtree::<Count>::new(10);
+//! let mut versions = vec![seg.empty()];
+//! for x in a {
+//!     let last = *versions.last().unwrap();
+//!     let cnt = *seg.get(last, x);
+//!     versions.push(seg.set(last, x, cnt + 1));
+//! }
+//!
+//! // 2nd smallest (0-indexed) in a[2..7] = [4, 1, 5, 9, 2]
+//! let mut k = 2;
+//! let kth = seg.descend_pair(versions[2], versions[7], |x, y| {
+//!     let cnt = y - x;
+//!     if cnt <= k { k -= cnt; true } else { false }
+//! });
+//! assert_eq!(kth, 4);
+//! assert_eq!(seg.prod(versions[4], 1..=3), 3); // count of 1, 2, 3 in a[..4]
+//! ```
+
+extern crate lazy_segtree;
+
+use std::convert::TryFrom;
+use std::ops::RangeBounds;
+
+use lazy_segtree::{to_half_open, LazyMonoid};
+
+/// A handle to a version of [`PersistentSegtree`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Version(u32);
+
+/// Persistent segment tree over [`LazyMonoid`] `T`.
+///
+/// Internally, the tree covers `0..2^h` for the smallest `2^h >= n`.
+/// Node `0` represents a subtree where every element is `ID`,
+/// and its children are itself.
+///
+/// In the method descriptions, let `n` be the number of elements.
+#[derive(Clone, Debug)]
+pub struct PersistentSegtree<T: LazyMonoid> {
+    n: usize,
+    height: u32,
+    val: Vec<T::V>,
+    ch: Vec<[u32; 2]>,
+}
+
+impl<T: LazyMonoid> PersistentSegtree<T> {
+    /// Returns a persistent segment tree with `n` elements,
+    /// containing only [`empty`](Self::empty).
+    ///
+    /// 🕒 `O(1)`.
+    #[must_use]
+    pub fn new(n: usize) -> Self {
+        Self {
+            n,
+            height: n.next_power_of_two().trailing_zeros(),
+            val: vec![T::ID],
+            ch: vec![[0; 2]],
+        }
+    }
+
+    /// Returns `n`.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.n
+    }
+
+    /// Returns whether `n == 0`.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    /// Returns the version where every element is `ID`.
+    #[must_use]
+    pub const fn empty(&self) -> Version {
+        Version(0)
+    }
+
+    fn new_node(&mut self, v: T::V, ch: [u32; 2]) -> u32 {
+        self.val.push(v);
+        self.ch.push(ch);
+        u32::try_from(self.val.len() - 1).unwrap()
+    }
+
+    /// Returns a new version whose elements are `values`.
+    ///
+    /// 🕒 `O(n)`.
+    ///
+    /// ⚠️ Panics if `values.len() != n`.
+    pub fn build(&mut self, values: Vec<T::V>) -> Version {
+        assert_eq!(values.len(), self.n, "wrong number of elements");
+        let mut level = values
+            .into_iter()
+            .map(|v| self.new_node(v, [0; 2]))
+            .collect::<Vec<_>>();
+        for _ in 0..self.height {
+            level = level
+                .chunks(2)
+                .map(|c| {
+                    let ch = [c[0], c.get(1).copied().unwrap_or(0)];
+                    let v = T::op(&self.val[ch[0] as usize], &self.val[ch[1] as usize]);
+                    self.new_node(v, ch)
+                })
+                .collect();
+        }
+        Version(level.first().copied().unwrap_or(0))
+    }
+
+    /// Returns a new version where the `i`-th element
+    /// of `ver` is set to `v`.
+    ///
+    /// 🕒 `O(log n)`, creating `O(log n)` nodes.
+    ///
+    /// ⚠️ Panics if `i >= n`.
+    pub fn set(&mut self, ver: Version, i: usize, v: T::V) -> Version {
+        assert!(i < self.n, "index {} out of {}", i, self.n);
+        let mut path = vec![];
+        let mut x = ver.0;
+        for h in (0..self.height).rev() {
+            let c = (i >> h) & 1;
+            path.push((x, c));
+            x = self.ch[x as usize][c];
+        }
+        let mut x = self.new_node(v, [0; 2]);
+        for (old, c) in path.into_iter().rev() {
+            let mut ch = self.ch[old as usize];
+            ch[c] = x;
+            let v = T::op(&self.val[ch[0] as usize], &self.val[ch[1] as usize]);
+            x = self.new_node(v, ch);
+        }
+        Version(x)
+    }
+
+    /// Returns the `i`-th element of `ver`.
+    ///
+    /// 🕒 `O(log n)`.
+    ///
+    /// ⚠️ Panics if `i >= n`.
+    #[must_use]
+    pub fn get(&self, ver: Version, i: usize) -> &T::V {
+        assert!(i < self.n, "index {} out of {}", i, self.n);
+        let mut x = ver.0;
+        for h in (0..self.height).rev() {
+            x = self.ch[x as usize][(i >> h) & 1];
+        }
+        &self.val[x as usize]
+    }
+
+    /// Returns the product of the elements of `ver` in `range`,
+    /// or `ID` if the range is empty.
+    /// Both `l..r` and `l..=r` are accepted.
+    ///
+    /// 🕒 `O(log n)`.
+    ///
+    /// ⚠️ Panics if the range is out of bounds.
+    pub fn prod(&self, ver: Version, range: impl RangeBounds<usize>) -> T::V {
+        let (l, r) = to_half_open(range, self.n);
+        if l == r {
+            return T::ID;
+        }
+        self.prod_inner(ver.0, self.height, 0, l, r)
+    }
+    fn prod_inner(&self, node: u32, h: u32, nl: usize, l: usize, r: usize) -> T::V {
+        let nr = nl + (1 << h);
+        if node == 0 || r <= nl || nr <= l {
+            return T::ID;
+        }
+        if l <= nl && nr <= r {
+            return self.val[node as usize].clone();
+        }
+        let [left, right] = self.ch[node as usize];
+        let half = 1 << (h - 1);
+        T::op(
+            &self.prod_inner(left, h - 1, nl, l, r),
+            &self.prod_inner(right, h - 1, nl + half, l, r),
+        )
+    }
+
+    /// Descends from the roots of `older` and `newer` simultaneously,
+    /// and returns the index of the leaf where it ends.
+    /// At each node, `go_right` is called with the values of the
+    /// left children of `older` and `newer`; if it returns `true`,
+    /// the descent continues to the right children.
+    ///
+    /// This is useful when `newer` "minus" `older` makes sense,
+    /// such as finding the k-th smallest element in a range.
+    ///
+    /// 🕒 `O(log n)` calls to `go_right`.
+    ///
+    /// ⚠️ The returned index may be `>= n` if `go_right`
+    /// keeps going right.
+    pub fn descend_pair(
+        &self,
+        older: Version,
+        newer: Version,
+        mut go_right: impl FnMut(&T::V, &T::V) -> bool,
+    ) -> usize {
+        let (mut p, mut q, mut idx) = (older.0 as usize, newer.0 as usize, 0);
+        for _ in 0..self.height {
+            let (pl, ql) = (self.ch[p][0] as usize, self.ch[q][0] as usize);
+            let side = usize::from(go_right(&self.val[pl], &self.val[ql]));
+            p = self.ch[p][side] as usize;
+            q = self.ch[q][side] as usize;
+            idx = idx * 2 + side;
+        }
+        idx
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::PersistentSegtree;
+    use lazy_segtree::LazyMonoid;
+
+    /// Concatenation, to catch non-commutative mistakes.
+    struct Concat;
+    impl LazyMonoid for Concat {
+        type V = Vec<usize>;
+        type L = ();
+        const ID: Vec<usize> = vec![];
+        fn op(x: &Vec<usize>, y: &Vec<usize>) -> Vec<usize> {
+            x.iter().chain(y).copied().collect()
+        }
+        const NOT_LAZY: () = ();
+        fn combine_lazy(_cur: &mut (), _up: &()) {}
+        fn unlazy(_slot: &mut Vec<usize>, _lazy: (), _nl: usize, _nr: usize) {}
+    }
+
+    struct Count;
+    impl LazyMonoid for Count {
+        type V = usize;
+        type L = ();
+        const ID: usize = 0;
+        fn op(x: &usize, y: &usize) -> usize {
+            x + y
+        }
+        const NOT_LAZY: () = ();
+        fn combine_lazy(_cur: &mut (), _up: &()) {}
+        fn unlazy(_slot: &mut usize, _lazy: (), _nl: usize, _nr: usize) {}
+    }
+
+    enum Query {
+        /// Sets an element of the `k`-th version, as a new version.
+        Set(usize, usize, usize),
+        Get(usize, usize),
+        Prod(usize, usize, usize),
+    }
+
+    /// Runs `queries`, where version `0` is all empty,
+    /// and version `1` is `initial`.
+    fn do_test_persistent_segtree(initial: Vec<usize>, queries: Vec<Query>) {
+        let n = initial.len();
+        let mut seg = PersistentSegtree::<Concat>::new(n);
+        let initial = initial.into_iter().map(|x| vec![x]).collect::<Vec<_>>();
+        let mut versions = vec![
+            (seg.empty(), vec![vec![]; n]),
+            (seg.build(initial.clone()), initial),
+        ];
+        for (qi, query) in queries.into_iter().enumerate() {
+            match query {
+                Query::Set(k, i, v) => {
+                    let (ver, mut brute) = versions[k].clone();
+                    brute[i] = vec![v];
+                    versions.push((seg.set(ver, i, vec![v]), brute));
+                }
+                Query::Get(k, i) => {
+                    let (ver, brute) = &versions[k];
+                    assert_eq!(*seg.get(*ver, i), brute[i], "query {qi}");
+                }
+                Query::Prod(k, l, r) => {
+                    let (ver, brute) = &versions[k];
+                    assert_eq!(seg.prod(*ver, l..r), brute[l..r].concat(), "query {qi}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_persistent_segtree() {
+        do_test_persistent_segtree(
+            vec![3, 1, 4, 1, 5, 9, 2],
+            vec![
+                Query::Prod(0, 0, 7),
+                Query::Prod(1, 0, 7),
+                Query::Prod(1, 2, 5),
+                Query::Set(1, 3, 8),
+                Query::Set(2, 0, 6),
+                Query::Set(1, 6, 0),
+                Query::Set(0, 4, 7),
+                Query::Prod(1, 0, 7),
+                Query::Prod(2, 2, 6),
+                Query::Prod(3, 0, 4),
+                Query::Prod(4, 5, 7),
+                Query::Prod(5, 3, 6),
+                Query::Get(3, 3),
+                Query::Get(4, 3),
+                Query::Get(5, 4),
+                Query::Set(5, 4, 2),
+                Query::Prod(6, 0, 7),
+                Query::Prod(5, 4, 5),
+            ],
+        );
+        do_test_persistent_segtree(
+            vec![5],
+            vec![Query::Set(1, 0, 2), Query::Get(1, 0), Query::Prod(2, 0, 1)],
+        );
+    }
+
+    #[test]
+    fn test_persistent_segtree_kth() {
+        let a = [3, 1, 4, 1, 5, 9, 2, 6, 5, 3, 5, 8, 9, 7, 9, 3, 2, 3, 8, 4];
+        let (n, m) = (a.len(), 10);
+        let mut seg = PersistentSegtree::<Count>::new(m);
+        let mut versions = vec![seg.empty()];
+        for x in &a {
+            let last = *versions.last().unwrap();
+            let cnt = *seg.get(last, *x);
+            versions.push(seg.set(last, *x, cnt + 1));
+        }
+        for l in 0..n {
+            for r in l + 1..=n {
+                let mut sorted = a[l..r].to_vec();
+                sorted.sort_unstable();
+                for (k, x) in sorted.into_iter().enumerate() {
+                    let mut k = k;
+                    let kth = seg.descend_pair(versions[l], versions[r], |x, y| {
+                        let cnt = y - x;
+                        if cnt <= k {
+                            k -= cnt;
+                            true
+                        } else {
+                            false
+                        }
+                    });
+                    assert_eq!(kth, x);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,221 @@
+//! Radix heap, a monotone min-heap over unsigned integer keys.
+//!
+//! # Usage
+//! Keys must never be smaller than the last popped key.
+//! This is exactly the case in Dijkstra's algorithm with
+//! nonnegative integer costs, where it is usually faster
+//! than a binary heap.
+//!
+//! Each element is moved between buckets at most `O(log C)` times,
+//! where `C` is the largest key, so a push followed by a pop takes
+//! amortized `O(log C)` time.
+//!
+//! # Example
+//! ```
+//! # use radix_heap::RadixHeap;
+//! let mut heap = RadixHeap::new();
+//! heap.push(5u32, 'a');
+//! heap.push(3, 'b');
+//! assert_eq!(heap.pop(), Some((3, 'b')));
+//! heap.push(4, 'c');
+//! assert_eq!(heap.pop(), Some((4, 'c')));
+//! assert_eq!(heap.pop(), Some((5, 'a')));
+//! assert_eq!(heap.pop(), None);
+//! ```
+
+/// Unsigned integer usable as a [`RadixHeap`] key.
+pub trait RadixKey: Copy + Ord {
+    /// Converts the key into `u64`, preserving order.
+    fn to_u64(self) -> u64;
+}
+
+macro_rules! impl_radix_key {
+    ($($t:ty)*) => {$(
+        impl RadixKey for $t {
+            #[allow(clippy::cast_lossless)]
+            fn to_u64(self) -> u64 {
+                self as u64
+            }
+        }
+    )*};
+}
+impl_radix_key!(u8 u16 u32 u64 usize);
+
+/// Radix heap with keys `K` and values `V`.
+#[derive(Clone, Debug)]
+pub struct RadixHeap<K, V> {
+    /// `buckets[0]` holds the keys equal to `last`, and `buckets[i]`
+    /// the keys whose highest bit differing from `last` is `i-1`.
+    buckets: Vec<Vec<(K, V)>>,
+    last: u64,
+    len: usize,
+}
+
+impl<K: RadixKey, V> Default for RadixHeap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: RadixKey, V> RadixHeap<K, V> {
+    /// Creates an empty heap.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            buckets: (0..65).map(|_| vec![]).collect(),
+            last: 0,
+            len: 0,
+        }
+    }
+
+    /// Returns the number of elements.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns whether the heap is empty.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn bucket(&self, key: K) -> usize {
+        (64 - (key.to_u64() ^ self.last).leading_zeros()) as usize
+    }
+
+    /// Inserts `value` with `key`.
+    ///
+    /// 🕒 `O(1)`.
+    ///
+    /// ⚠️ Panics if `key` is smaller than the last popped key.
+    pub fn push(&mut self, key: K, value: V) {
+        assert!(
+            key.to_u64() >= self.last,
+            "key smaller than the last popped"
+        );
+        let b = self.bucket(key);
+        self.buckets[b].push((key, value));
+        self.len += 1;
+    }
+
+    /// Removes and returns an element with the minimum key,
+    /// or `None` if the heap is empty.
+    ///
+    /// 🕒 Amortized `O(log C)`.
+    pub fn pop(&mut self) -> Option<(K, V)> {
+        if self.len == 0 {
+            return None;
+        }
+        if self.buckets[0].is_empty() {
+            let i = self.buckets.iter().position(|b| !b.is_empty())?;
+            let bucket = std::mem::take(&mut self.buckets[i]);
+            self.last = bucket.iter().map(|(k, _)| k.to_u64()).min()?;
+            for (key, value) in bucket {
+                let b = self.bucket(key);
+                self.buckets[b].push((key, value));
+            }
+        }
+        self.len -= 1;
+        self.buckets[0].pop()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::cmp::Reverse;
+    use std::collections::BinaryHeap;
+
+    enum Query {
+        /// Pushes the last popped key plus the given amount.
+        Push(u64),
+        Pop,
+    }
+
+    fn do_test_radix_heap(queries: Vec<Query>) {
+        let mut heap = RadixHeap::new();
+        let mut naive = BinaryHeap::new();
+        let mut last = 0;
+        for (qi, query) in queries.into_iter().enumerate() {
+            match query {
+                Query::Push(d) => {
+                    heap.push(last + d, qi);
+                    naive.push(Reverse((last + d, qi)));
+                }
+                Query::Pop => {
+                    let got = heap.pop();
+                    let want = naive.pop().map(|Reverse(x)| x);
+                    assert_eq!(got.map(|x| x.0), want.map(|x| x.0), "query {qi}");
+                    last = got.map_or(last, |x| x.0);
+                }
+            }
+            assert_eq!(heap.len(), naive.len(), "query {qi}");
+        }
+    }
+
+    #[test]
+    fn test_radix_heap() {
+        do_test_radix_heap(vec![
+            Query::Pop,
+            Query::Push(5),
+            Query::Push(3),
+            Query::Push(3),
+            Query::Push(8),
+            Query::Pop,
+            Query::Push(0),
+            Query::Push(1),
+            Query::Pop,
+            Query::Pop,
+            Query::Pop,
+            Query::Push(1 << 40),
+            Query::Push(7),
+            Query::Push(1 << 20),
+            Query::Pop,
+            Query::Push(0),
+            Query::Pop,
+            Query::Pop,
+            Query::Push((1 << 40) - 1),
+            Query::Pop,
+            Query::Pop,
+            Query::Pop,
+            Query::Pop,
+        ]);
+        // Keys spread over every bucket, popped in one go.
+        let mut queries = (0..64)
+            .map(|b| Query::Push((1 << b) - 1))
+            .collect::<Vec<_>>();
+        queries.extend((0..65).map(|_| Query::Pop));
+        do_test_radix_heap(queries);
+        // Interleaved pushes and pops, as in Dijkstra.
+        let queries = (0..300u64)
+            .map(|i| {
+                if i % 3 == 0 {
+                    Query::Pop
+                } else {
+                    Query::Push(i * i % 97)
+                }
+            })
+            .collect();
+        do_test_radix_heap(queries);
+    }
+
+    #[test]
+    fn test_max_key() {
+        let mut heap = RadixHeap::new();
+        heap.push(u64::MAX, 1);
+        heap.push(0, 0);
+        assert_eq!(heap.pop(), Some((0, 0)));
+        assert_eq!(heap.pop(), Some((u64::MAX, 1)));
+        assert!(heap.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "key smaller than the last popped")]
+    fn test_decreasing_key() {
+        let mut heap = RadixHeap::new();
+        heap.push(5u8, ());
+        let _ = heap.pop();
+        heap.push(4, ());
+    }
+}
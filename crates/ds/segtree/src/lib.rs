@@ -0,0 +1,388 @@
+//! Segment tree over a monoid, without lazy propagation.
+//!
+//! This is the usual bottom-up (iterative) segment tree.
+//! If you only need point updates and range queries, prefer this
+//! over `lazy_segtree`, which pays for lazy values all the time.
+//!
+//! # Usage
+//! A segment tree is defined over a type `V` and an operation `op`
+//! that together form a monoid:
+//! - **Associativity**: `a op (b op c) = (a op b) op c`
+//! - **Identity**: `ID op a = a op ID = a`
+//!
+//! Create a new type (likely zero-sized), and implement [`Monoid`]
+//! for it. This is the same as the `V`/`ID`/`op` part of
+//! `lazy_segtree::LazyMonoid`.
+//!
+//! # Example
+//! ```
+//! # use segtree::{Monoid, Segtree};
+//! struct Sum;
+//! impl Monoid for Sum {
+//!     type V = i64;
+//!     const ID: i64 = 0;
+//!     fn op(x: &i64, y: &i64) -> i64 { x + y }
+//! }
+//!
+//! let mut seg = Segtree::<Sum>::from(vec![3, 1, 4, 1, 5]);
+//! assert_eq!(seg.prod(1..4), 6);
+//! assert_eq!(seg.prod(1..=4), 11);
+//! seg.set(2, 10);
+//! assert_eq!(seg.all_prod(), 20);
+//! // the largest r such that sum of [0, r) is <= 14
+//! assert_eq!(seg.max_right(0, |s| *s <= 14), 3);
+//! ```
+//!
+//! - [LC Point Set Range Composite](https://judge.yosupo.jp/problem/point_set_range_composite)
+//! ```ignore
+//! struct Affine;
+//! impl Monoid for Affine {
+//!     type V = (Modint99, Modint99);
+//!     const ID: Self::V = (Modint99::new(1), Modint99::new(0));
+//!     fn op(x: &Self::V, y: &Self::V) -> Self::V {
+//!         (x.0 * y.0, x.1 * y.0 + y.1)
+//!     }
+//! }
+//!
+//! let n = oj.usize();
+//! let q = oj.usize();
+//! let init = (0..n).map(|_| (oj.read(), oj.read())).collect::<Vec<_>>();
+//! let mut seg = Segtree::<Affine>::from(init);
+//! for _ in 0..q {
+//!     if oj.usize() == 0 {
+//!         seg.set(oj.usize(), (oj.read(), oj.read()));
+//!     } else {
+//!         let (a, b) = seg.prod(oj.usize()..oj.usize());
+//!         oj.write(a * oj.read::<Modint99>() + b).ln();
+//!     }
+//! }
+//! ```
+
+extern crate lazy_segtree;
+
+use lazy_segtree::to_half_open;
+use std::fmt::Debug;
+use std::ops::RangeBounds;
+
+pub trait Monoid {
+    /// Type of the value.
+    type V: Clone + Debug;
+
+    /// Identity value.
+    const ID: Self::V;
+    /// Operator `x op y`.
+    fn op(x: &Self::V, y: &Self::V) -> Self::V;
+}
+
+/// Segment tree over [`Monoid`] `T`.
+///
+/// In the method descriptions, let `n` be the number of elements.
+#[derive(Clone)]
+pub struct Segtree<T: Monoid> {
+    n: usize,
+    size: usize,
+    arr: Vec<T::V>,
+}
+
+impl<T: Monoid> Debug for Segtree<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list()
+            .entries(&self.arr[self.size..self.size + self.n])
+            .finish()
+    }
+}
+
+impl<T: Monoid> From<Vec<T::V>> for Segtree<T> {
+    fn from(value: Vec<T::V>) -> Self {
+        let n = value.len();
+        let size = n.next_power_of_two();
+        let mut arr = vec![T::ID; size * 2];
+        for (slot, v) in arr[size..].iter_mut().zip(value) {
+            *slot = v;
+        }
+        for i in (1..size).rev() {
+            arr[i] = T::op(&arr[i * 2], &arr[i * 2 + 1]);
+        }
+        Self { n, size, arr }
+    }
+}
+
+impl<T: Monoid> Segtree<T> {
+    /// Returns a segment tree with `n` elements,
+    /// where the `i`-th element is `init(i)`.
+    ///
+    /// 🕒 `O(n)`.
+    pub fn new(n: usize, init: impl Fn(usize) -> T::V) -> Self {
+        Self::from((0..n).map(init).collect::<Vec<_>>())
+    }
+
+    /// Returns `n`.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.n
+    }
+
+    /// Returns whether `n == 0`.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    /// Sets the `i`-th element to `v`.
+    ///
+    /// 🕒 `O(log n)`.
+    ///
+    /// ⚠️ Panics if `i >= n`.
+    pub fn set(&mut self, i: usize, v: T::V) {
+        assert!(i < self.n, "index {} out of {}", i, self.n);
+        let mut i = i + self.size;
+        self.arr[i] = v;
+        while i > 1 {
+            i >>= 1;
+            self.arr[i] = T::op(&self.arr[i * 2], &self.arr[i * 2 + 1]);
+        }
+    }
+
+    /// Returns the `i`-th element.
+    ///
+    /// 🕒 `O(1)`.
+    ///
+    /// ⚠️ Panics if `i >= n`.
+    #[must_use]
+    pub fn get(&self, i: usize) -> &T::V {
+        assert!(i < self.n, "index {} out of {}", i, self.n);
+        &self.arr[i + self.size]
+    }
+
+    /// Returns the product of the elements in `range`,
+    /// or `ID` if the range is empty.
+    /// Both `l..r` and `l..=r` are accepted.
+    ///
+    /// 🕒 `O(log n)`.
+    ///
+    /// ⚠️ Panics if the range is out of bounds.
+    pub fn prod(&self, range: impl RangeBounds<usize>) -> T::V {
+        let (l, r) = to_half_open(range, self.n);
+        let (mut l, mut r) = (l + self.size, r + self.size);
+        let mut left = T::ID;
+        let mut right = T::ID;
+        while l < r {
+            if l & 1 == 1 {
+                left = T::op(&left, &self.arr[l]);
+                l += 1;
+            }
+            if r & 1 == 1 {
+                r -= 1;
+                right = T::op(&self.arr[r], &right);
+            }
+            l >>= 1;
+            r >>= 1;
+        }
+        T::op(&left, &right)
+    }
+
+    /// Returns the product of all elements.
+    ///
+    /// 🕒 `O(1)`.
+    #[must_use]
+    pub fn all_prod(&self) -> T::V {
+        self.arr[1].clone()
+    }
+
+    /// Returns the largest `r` such that `pred(prod(l..r))` holds,
+    /// assuming `pred` is monotone, i.e. once it fails for `l..r`,
+    /// it also fails for `l..r'` where `r' > r`.
+    ///
+    /// 🕒 `O(log n)` calls to `op` and `pred`.
+    ///
+    /// ⚠️ Panics if `l > n` or `pred(ID)` is false.
+    pub fn max_right(&self, l: usize, pred: impl Fn(&T::V) -> bool) -> usize {
+        assert!(l <= self.n, "index {} out of {}", l, self.n);
+        assert!(pred(&T::ID), "pred(ID) must be true");
+        if l == self.n {
+            return self.n;
+        }
+        let mut l = l + self.size;
+        let mut acc = T::ID;
+        loop {
+            l >>= l.trailing_zeros();
+            let nxt = T::op(&acc, &self.arr[l]);
+            if !pred(&nxt) {
+                while l < self.size {
+                    l *= 2;
+                    let nxt = T::op(&acc, &self.arr[l]);
+                    if pred(&nxt) {
+                        acc = nxt;
+                        l += 1;
+                    }
+                }
+                return l - self.size;
+            }
+            acc = nxt;
+            l += 1;
+            if l.is_power_of_two() {
+                return self.n;
+            }
+        }
+    }
+
+    /// Returns the smallest `l` such that `pred(prod(l..r))` holds,
+    /// assuming `pred` is monotone, i.e. once it fails for `l..r`,
+    /// it also fails for `l'..r` where `l' < l`.
+    ///
+    /// 🕒 `O(log n)` calls to `op` and `pred`.
+    ///
+    /// ⚠️ Panics if `r > n` or `pred(ID)` is false.
+    pub fn min_left(&self, r: usize, pred: impl Fn(&T::V) -> bool) -> usize {
+        assert!(r <= self.n, "index {} out of {}", r, self.n);
+        assert!(pred(&T::ID), "pred(ID) must be true");
+        if r == 0 {
+            return 0;
+        }
+        let mut r = r + self.size;
+        let mut acc = T::ID;
+        loop {
+            r -= 1;
+            while r > 1 && r & 1 == 1 {
+                r >>= 1;
+            }
+            let nxt = T::op(&self.arr[r], &acc);
+            if !pred(&nxt) {
+                while r < self.size {
+                    r = r * 2 + 1;
+                    let nxt = T::op(&self.arr[r], &acc);
+                    if pred(&nxt) {
+                        acc = nxt;
+                        r -= 1;
+                    }
+                }
+                return r + 1 - self.size;
+            }
+            acc = nxt;
+            if r.is_power_of_two() {
+                return 0;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Monoid, Segtree};
+
+    struct Sum;
+    impl Monoid for Sum {
+        type V = u64;
+        const ID: u64 = 0;
+        fn op(x: &u64, y: &u64) -> u64 {
+            x + y
+        }
+    }
+
+    /// Concatenation, to catch non-commutative mistakes.
+    struct Concat;
+    impl Monoid for Concat {
+        type V = Vec<usize>;
+        const ID: Vec<usize> = vec![];
+        fn op(x: &Vec<usize>, y: &Vec<usize>) -> Vec<usize> {
+            x.iter().chain(y).copied().collect()
+        }
+    }
+
+    enum Query {
+        Set(usize, u64),
+        Prod(usize, usize),
+        /// `max_right(l, |s| s <= bound)`.
+        MaxRight(usize, u64),
+        /// `min_left(r, |s| s <= bound)`.
+        MinLeft(usize, u64),
+    }
+
+    fn do_test_segtree(initial: Vec<u64>, queries: Vec<Query>) {
+        let mut seg = Segtree::<Sum>::from(initial.clone());
+        let mut brute = initial;
+        let n = brute.len();
+        for (qi, query) in queries.into_iter().enumerate() {
+            let sum = |l: usize, r: usize| brute[l..r].iter().sum::<u64>();
+            match query {
+                Query::Set(i, v) => {
+                    seg.set(i, v);
+                    brute[i] = v;
+                    assert_eq!(*seg.get(i), v, "query {qi}");
+                }
+                Query::Prod(l, r) => {
+                    assert_eq!(seg.prod(l..r), sum(l, r), "query {qi}");
+                    if l < r {
+                        let last = r - 1;
+                        assert_eq!(seg.prod(l..=last), sum(l, r), "query {qi}");
+                    }
+                }
+                Query::MaxRight(l, bound) => {
+                    let ans = (l..=n).take_while(|&r| sum(l, r) <= bound).last();
+                    assert_eq!(Some(seg.max_right(l, |s| *s <= bound)), ans, "query {qi}");
+                }
+                Query::MinLeft(r, bound) => {
+                    let ans = (0..=r).rev().take_while(|&l| sum(l, r) <= bound).last();
+                    assert_eq!(Some(seg.min_left(r, |s| *s <= bound)), ans, "query {qi}");
+                }
+            }
+            assert_eq!(seg.all_prod(), brute.iter().sum::<u64>(), "query {qi}");
+        }
+    }
+
+    #[test]
+    fn test_segtree_sum() {
+        do_test_segtree(
+            vec![3, 1, 4, 1, 5, 9, 2, 6, 5, 3],
+            vec![
+                Query::Prod(0, 10),
+                Query::Prod(2, 7),
+                Query::Prod(4, 4),
+                Query::Set(3, 12),
+                Query::Prod(2, 6),
+                Query::MaxRight(0, 8),
+                Query::MaxRight(3, 11),
+                Query::MaxRight(5, 100),
+                Query::MaxRight(10, 0),
+                Query::MinLeft(10, 8),
+                Query::MinLeft(4, 3),
+                Query::MinLeft(0, 0),
+                Query::Set(9, 0),
+                Query::MinLeft(10, 5),
+                Query::Set(0, 7),
+                Query::MaxRight(0, 6),
+                Query::Prod(0, 1),
+            ],
+        );
+        do_test_segtree(
+            vec![],
+            vec![
+                Query::Prod(0, 0),
+                Query::MaxRight(0, 0),
+                Query::MinLeft(0, 0),
+            ],
+        );
+        do_test_segtree(
+            vec![5],
+            vec![
+                Query::MaxRight(0, 4),
+                Query::MaxRight(0, 5),
+                Query::MinLeft(1, 4),
+                Query::Set(0, 2),
+                Query::MinLeft(1, 2),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_segtree_order() {
+        for n in 0..20 {
+            let seg = Segtree::<Concat>::new(n, |i| vec![i]);
+            for l in 0..=n {
+                for r in l..=n {
+                    assert_eq!(seg.prod(l..r), (l..r).collect::<Vec<_>>());
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,545 @@
+//! Segment tree beats (Ji Driver segment tree).
+//!
+//! Some lazy operations, like range chmin, cannot always be applied
+//! to a node using only the node's value. Segment tree beats allows
+//! [`BeatsMonoid::unlazy`] to **fail**; then the tree pushes the
+//! lazy value to the children and recomputes the node from them.
+//! With a careful choice of the value type, the total number of
+//! failures is bounded (amortized `O(log^2 n)` per operation).
+//!
+//! [`ChminChmaxAddSum`] is the usual instance supporting
+//! range chmin, range chmax, range add, and range sum.
+//!
+//! # Example
+//! - [LC Range Chmin Chmax Add Range Sum](https://judge.yosupo.jp/problem/range_chmin_chmax_add_range_sum)
+//! ```ignore
+//! let n = oj.usize();
+//! let q = oj.usize();
+//! let a = oj.vec::<i64>(n);
+//! let mut seg = SegtreeBeats::<ChminChmaxAddSum>::new(n, |i| BeatsNode::new(a[i]));
+//! for _ in 0..q {
+//!     let qty = oj.usize();
+//!     let (l, r) = (oj.usize(), oj.usize());
+//!     match qty {
+//!         0 => seg.apply(l..r, Clamp::chmin(oj.i64())),
+//!         1 => seg.apply(l..r, Clamp::chmax(oj.i64())),
+//!         2 => seg.apply(l..r, Clamp::add(oj.i64())),
+//!         _ => { oj.write(seg.prod(l..r).sum).ln(); }
+//!     }
+//! }
+//! ```
+
+extern crate lazy_segtree;
+
+use lazy_segtree::to_half_open;
+use std::convert::TryFrom;
+use std::fmt::Debug;
+use std::ops::RangeBounds;
+
+/// [`LazyMonoid`](lazy_segtree::LazyMonoid) whose `unlazy` may fail.
+pub trait BeatsMonoid {
+    /// Type of the value.
+    type V: Clone + Debug;
+    /// Type of the lazy value.
+    type L: Clone + Debug + PartialEq;
+
+    /// Identity value.
+    const ID: Self::V;
+    /// Operator `x op y`.
+    fn op(x: &Self::V, y: &Self::V) -> Self::V;
+
+    /// Identity lazy value.
+    const NOT_LAZY: Self::L;
+    /// Lazy operator combining `up` into `cur`.
+    fn combine_lazy(cur: &mut Self::L, up: &Self::L);
+    /// Unlazy operator combining `lazy` into `slot`,
+    /// on a node that represents the range `nl..=nr`.
+    ///
+    /// Returns `false` if it cannot be done from `slot` alone;
+    /// then `slot` will be recomputed from the children.
+    /// It must not fail if `nl == nr`.
+    fn unlazy(slot: &mut Self::V, lazy: Self::L, nl: usize, nr: usize) -> bool;
+}
+
+/// Segment tree beats over [`BeatsMonoid`] `T`.
+///
+/// In the method descriptions, let `n` be the number of elements.
+pub struct SegtreeBeats<T: BeatsMonoid> {
+    n: usize,
+    log: u32,
+    arr: Vec<T::V>,
+    lazy: Vec<T::L>,
+}
+
+impl<T: BeatsMonoid> Clone for SegtreeBeats<T> {
+    fn clone(&self) -> Self {
+        Self {
+            n: self.n,
+            log: self.log,
+            arr: self.arr.clone(),
+            lazy: self.lazy.clone(),
+        }
+    }
+}
+
+impl<T: BeatsMonoid> Debug for SegtreeBeats<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut me = self.clone();
+        let leaves = (0..self.n).map(|i| me.get(i)).collect::<Vec<_>>();
+        f.debug_list().entries(leaves).finish()
+    }
+}
+
+impl<T: BeatsMonoid> SegtreeBeats<T> {
+    /// Returns a segment tree with `n` elements,
+    /// where the `i`-th element is `init(i)`.
+    ///
+    /// 🕒 `O(n)`.
+    pub fn new(n: usize, init: impl Fn(usize) -> T::V) -> Self {
+        let size = n.next_power_of_two();
+        let mut arr = vec![T::ID; size * 2];
+        for (i, slot) in arr[size..size + n].iter_mut().enumerate() {
+            *slot = init(i);
+        }
+        for i in (1..size).rev() {
+            arr[i] = T::op(&arr[i * 2], &arr[i * 2 + 1]);
+        }
+        Self {
+            n,
+            log: size.trailing_zeros(),
+            arr,
+            lazy: vec![T::NOT_LAZY; size],
+        }
+    }
+
+    /// Returns `n`.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.n
+    }
+
+    /// Returns whether `n == 0`.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    fn pull(&mut self, x: usize) {
+        self.arr[x] = T::op(&self.arr[x * 2], &self.arr[x * 2 + 1]);
+    }
+
+    fn all_apply(&mut self, x: usize, lazy: &T::L) {
+        let h = self.log - x.ilog2();
+        let nl = (x << h) - self.lazy.len();
+        let ok = T::unlazy(&mut self.arr[x], lazy.clone(), nl, nl + (1 << h) - 1);
+        if h == 0 {
+            assert!(ok, "unlazy failed on a leaf");
+            return;
+        }
+        T::combine_lazy(&mut self.lazy[x], lazy);
+        if !ok {
+            self.push(x);
+            self.pull(x);
+        }
+    }
+
+    fn push(&mut self, x: usize) {
+        if self.lazy[x] == T::NOT_LAZY {
+            return;
+        }
+        let lazy = std::mem::replace(&mut self.lazy[x], T::NOT_LAZY);
+        self.all_apply(x * 2, &lazy);
+        self.all_apply(x * 2 + 1, &lazy);
+    }
+
+    /// Pushes the ancestors of the boundaries of `l..r` (shifted to leaves).
+    fn push_boundary(&mut self, l: usize, r: usize) {
+        for h in (1..=self.log).rev() {
+            if (l >> h) << h != l {
+                self.push(l >> h);
+            }
+            if (r >> h) << h != r {
+                self.push((r - 1) >> h);
+            }
+        }
+    }
+
+    /// Returns the `i`-th element.
+    ///
+    /// 🕒 `O(log n)`.
+    ///
+    /// ⚠️ Panics if `i >= n`.
+    pub fn get(&mut self, i: usize) -> T::V {
+        assert!(i < self.n, "index {} out of {}", i, self.n);
+        let y = i + self.lazy.len();
+        for h in (1..=self.log).rev() {
+            self.push(y >> h);
+        }
+        self.arr[y].clone()
+    }
+
+    /// Applies `lazy` to every element in `range`.
+    /// Both `l..r` and `l..=r` are accepted.
+    ///
+    /// 🕒 Amortized `O(log^2 n)` for [`ChminChmaxAddSum`].
+    ///
+    /// ⚠️ Panics if the range is out of bounds.
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn apply(&mut self, range: impl RangeBounds<usize>, lazy: T::L) {
+        let (l, r) = to_half_open(range, self.n);
+        if l == r {
+            return;
+        }
+        let (l, r) = (l + self.lazy.len(), r + self.lazy.len());
+        self.push_boundary(l, r);
+        let (mut lx, mut rx) = (l, r);
+        while lx < rx {
+            if lx & 1 == 1 {
+                self.all_apply(lx, &lazy);
+                lx += 1;
+            }
+            if rx & 1 == 1 {
+                rx -= 1;
+                self.all_apply(rx, &lazy);
+            }
+            lx >>= 1;
+            rx >>= 1;
+        }
+        for h in 1..=self.log {
+            if (l >> h) << h != l {
+                self.pull(l >> h);
+            }
+            if (r >> h) << h != r {
+                self.pull((r - 1) >> h);
+            }
+        }
+    }
+
+    /// Returns the product of the elements in `range`,
+    /// or `ID` if the range is empty.
+    /// Both `l..r` and `l..=r` are accepted.
+    ///
+    /// 🕒 `O(log n)`.
+    ///
+    /// ⚠️ Panics if the range is out of bounds.
+    pub fn prod(&mut self, range: impl RangeBounds<usize>) -> T::V {
+        let (l, r) = to_half_open(range, self.n);
+        if l == r {
+            return T::ID;
+        }
+        let (mut l, mut r) = (l + self.lazy.len(), r + self.lazy.len());
+        self.push_boundary(l, r);
+        let mut left = T::ID;
+        let mut right = T::ID;
+        while l < r {
+            if l & 1 == 1 {
+                left = T::op(&left, &self.arr[l]);
+                l += 1;
+            }
+            if r & 1 == 1 {
+                r -= 1;
+                right = T::op(&self.arr[r], &right);
+            }
+            l >>= 1;
+            r >>= 1;
+        }
+        T::op(&left, &right)
+    }
+
+    /// Returns the product of all elements.
+    ///
+    /// 🕒 `O(1)`.
+    #[must_use]
+    pub fn all_prod(&self) -> T::V {
+        self.arr[1].clone()
+    }
+}
+
+/// Value of [`ChminChmaxAddSum`]: the sum, maximum, and minimum
+/// of a range of `i64`s, along with what segment tree beats needs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BeatsNode {
+    pub sum: i64,
+    pub max: i64,
+    pub min: i64,
+    pub len: usize,
+    /// Second maximum, or `i64::MIN` if none.
+    max2: i64,
+    max_cnt: i64,
+    /// Second minimum, or `i64::MAX` if none.
+    min2: i64,
+    min_cnt: i64,
+}
+
+impl BeatsNode {
+    /// Returns the node for a single element `x`.
+    #[must_use]
+    pub const fn new(x: i64) -> Self {
+        Self {
+            sum: x,
+            max: x,
+            min: x,
+            len: 1,
+            max2: i64::MIN,
+            max_cnt: 1,
+            min2: i64::MAX,
+            min_cnt: 1,
+        }
+    }
+}
+
+/// Lazy value of [`ChminChmaxAddSum`]:
+/// `x -> min(max(x + add, lo), hi)`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Clamp {
+    pub add: i64,
+    pub lo: i64,
+    pub hi: i64,
+}
+
+impl Clamp {
+    /// `x -> x + a`.
+    #[must_use]
+    pub const fn add(a: i64) -> Self {
+        Self {
+            add: a,
+            lo: i64::MIN,
+            hi: i64::MAX,
+        }
+    }
+
+    /// `x -> min(x, hi)`.
+    #[must_use]
+    pub const fn chmin(hi: i64) -> Self {
+        Self {
+            add: 0,
+            lo: i64::MIN,
+            hi,
+        }
+    }
+
+    /// `x -> max(x, lo)`.
+    #[must_use]
+    pub const fn chmax(lo: i64) -> Self {
+        Self {
+            add: 0,
+            lo,
+            hi: i64::MAX,
+        }
+    }
+}
+
+/// Adds `a` to `x`, unless `x` is `i64::MIN` or `i64::MAX`
+/// which represent infinities.
+const fn shift(x: i64, a: i64) -> i64 {
+    if x == i64::MIN || x == i64::MAX {
+        x
+    } else {
+        x + a
+    }
+}
+
+/// Range chmin, range chmax, range add, range sum (and min/max).
+///
+/// Initialize each element with [`BeatsNode::new`],
+/// and apply [`Clamp`]s.
+#[derive(Debug)]
+pub struct ChminChmaxAddSum;
+impl BeatsMonoid for ChminChmaxAddSum {
+    type V = BeatsNode;
+    type L = Clamp;
+    const ID: BeatsNode = BeatsNode {
+        sum: 0,
+        max: i64::MIN,
+        min: i64::MAX,
+        len: 0,
+        max2: i64::MIN,
+        max_cnt: 0,
+        min2: i64::MAX,
+        min_cnt: 0,
+    };
+    fn op(x: &BeatsNode, y: &BeatsNode) -> BeatsNode {
+        let (max, max2, max_cnt) = match x.max.cmp(&y.max) {
+            std::cmp::Ordering::Equal => (x.max, x.max2.max(y.max2), x.max_cnt + y.max_cnt),
+            std::cmp::Ordering::Greater => (x.max, x.max2.max(y.max), x.max_cnt),
+            std::cmp::Ordering::Less => (y.max, y.max2.max(x.max), y.max_cnt),
+        };
+        let (min, min2, min_cnt) = match x.min.cmp(&y.min) {
+            std::cmp::Ordering::Equal => (x.min, x.min2.min(y.min2), x.min_cnt + y.min_cnt),
+            std::cmp::Ordering::Less => (x.min, x.min2.min(y.min), x.min_cnt),
+            std::cmp::Ordering::Greater => (y.min, y.min2.min(x.min), y.min_cnt),
+        };
+        BeatsNode {
+            sum: x.sum + y.sum,
+            max,
+            min,
+            len: x.len + y.len,
+            max2,
+            max_cnt,
+            min2,
+            min_cnt,
+        }
+    }
+
+    const NOT_LAZY: Clamp = Clamp::add(0);
+    fn combine_lazy(cur: &mut Clamp, up: &Clamp) {
+        let clamp = |x: i64| x.max(up.lo).min(up.hi);
+        *cur = Clamp {
+            add: cur.add + up.add,
+            lo: clamp(shift(cur.lo, up.add)),
+            hi: clamp(shift(cur.hi, up.add)),
+        };
+    }
+    fn unlazy(slot: &mut BeatsNode, lazy: Clamp, _: usize, _: usize) -> bool {
+        if slot.len == 0 {
+            return true;
+        }
+        let node = slot;
+        // add
+        node.sum += lazy.add * i64::try_from(node.len).unwrap();
+        for x in [&mut node.max, &mut node.max2, &mut node.min, &mut node.min2] {
+            *x = shift(*x, lazy.add);
+        }
+        // chmax
+        if lazy.lo > node.min {
+            if lazy.lo >= node.min2 {
+                return false;
+            }
+            node.sum += (lazy.lo - node.min) * node.min_cnt;
+            if node.max == node.min {
+                node.max = lazy.lo;
+            } else if node.max2 == node.min {
+                node.max2 = lazy.lo;
+            }
+            node.min = lazy.lo;
+        }
+        // chmin
+        if lazy.hi < node.max {
+            if lazy.hi <= node.max2 {
+                return false;
+            }
+            node.sum -= (node.max - lazy.hi) * node.max_cnt;
+            if node.min == node.max {
+                node.min = lazy.hi;
+            } else if node.min2 == node.max {
+                node.min2 = lazy.hi;
+            }
+            node.max = lazy.hi;
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{BeatsNode, ChminChmaxAddSum, Clamp, SegtreeBeats};
+
+    enum Query {
+        Chmin(usize, usize, i64),
+        Chmax(usize, usize, i64),
+        Add(usize, usize, i64),
+        Prod(usize, usize),
+    }
+
+    fn check(seg: &mut SegtreeBeats<ChminChmaxAddSum>, brute: &[i64], l: usize, r: usize) {
+        let node = seg.prod(l..r);
+        let part = &brute[l..r];
+        assert_eq!(node.sum, part.iter().sum::<i64>());
+        assert_eq!(node.len, r - l);
+        if l < r {
+            assert_eq!(node.max, *part.iter().max().unwrap());
+            assert_eq!(node.min, *part.iter().min().unwrap());
+        }
+    }
+
+    fn do_test_beats(initial: Vec<i64>, queries: Vec<Query>) {
+        let mut brute = initial;
+        let n = brute.len();
+        let mut seg = SegtreeBeats::<ChminChmaxAddSum>::new(n, |i| BeatsNode::new(brute[i]));
+        for (qi, query) in queries.into_iter().enumerate() {
+            let (l, r, lazy, f): (_, _, _, Box<dyn Fn(i64) -> i64>) = match query {
+                Query::Chmin(l, r, x) => (l, r, Clamp::chmin(x), Box::new(move |v: i64| v.min(x))),
+                Query::Chmax(l, r, x) => (l, r, Clamp::chmax(x), Box::new(move |v: i64| v.max(x))),
+                Query::Add(l, r, x) => (l, r, Clamp::add(x), Box::new(move |v| v + x)),
+                Query::Prod(l, r) => {
+                    check(&mut seg, &brute, l, r);
+                    continue;
+                }
+            };
+            seg.apply(l..r, lazy);
+            for v in &mut brute[l..r] {
+                *v = f(*v);
+            }
+            assert_eq!(seg.all_prod().sum, brute.iter().sum::<i64>(), "query {qi}");
+        }
+        for (i, v) in brute.iter().enumerate() {
+            assert_eq!(seg.get(i).sum, *v);
+        }
+    }
+
+    #[test]
+    fn test_chmin_chmax_add_sum() {
+        do_test_beats(
+            vec![5, -3, 8, 0, 2, 9, -7, 4, 4, 1],
+            vec![
+                Query::Prod(0, 10),
+                Query::Chmin(0, 10, 4),
+                Query::Prod(0, 10),
+                Query::Prod(2, 6),
+                Query::Chmax(3, 8, 1),
+                Query::Prod(0, 5),
+                Query::Add(1, 9, -2),
+                Query::Prod(4, 9),
+                Query::Chmin(0, 6, 0),
+                Query::Chmax(0, 10, -4),
+                Query::Prod(0, 10),
+                Query::Add(0, 3, 10),
+                Query::Chmin(2, 10, -1),
+                Query::Prod(2, 4),
+                Query::Prod(7, 7),
+                Query::Chmax(5, 6, 20),
+                Query::Prod(0, 10),
+            ],
+        );
+        do_test_beats(
+            vec![3],
+            vec![
+                Query::Chmin(0, 1, 1),
+                Query::Chmax(0, 1, 2),
+                Query::Add(0, 1, -5),
+                Query::Prod(0, 1),
+            ],
+        );
+    }
+
+    /// Applies every range in turn, with the operation and value
+    /// depending on the range, and checks every range after each one.
+    #[test]
+    fn test_chmin_chmax_add_sum_ranges() {
+        for n in 1..8 {
+            let mut brute = (0..n)
+                .map(|i| [4, -2, 7, 0, -5, 3, 1][i])
+                .collect::<Vec<_>>();
+            let mut seg = SegtreeBeats::<ChminChmaxAddSum>::new(n, |i| BeatsNode::new(brute[i]));
+            for l in 0..n {
+                for r in l + 1..=n {
+                    let x = [3, -1, 0, 2, -4][(l * 7 + r) % 5];
+                    let (lazy, f): (_, Box<dyn Fn(i64) -> i64>) = match (l + r) % 3 {
+                        0 => (Clamp::chmin(x), Box::new(move |v: i64| v.min(x))),
+                        1 => (Clamp::chmax(x), Box::new(move |v: i64| v.max(x))),
+                        _ => (Clamp::add(x), Box::new(move |v| v + x)),
+                    };
+                    seg.apply(l..r, lazy);
+                    for v in &mut brute[l..r] {
+                        *v = f(*v);
+                    }
+                    for ql in 0..=n {
+                        for qr in ql..=n {
+                            check(&mut seg, &brute, ql, qr);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,276 @@
+//! Sparse table and disjoint sparse table, for static range queries.
+//!
+//! # Usage
+//! Both tables are defined over a type `V` and an operation `op`
+//! that form a semigroup:
+//! - **Associativity**: `a op (b op c) = (a op b) op c`
+//!
+//! Create a new type (likely zero-sized), and implement [`Semigroup`]
+//! for it. Ready-made ones are [`Min`], [`Max`], and [`GcdOp`].
+//!
+//! - [`SparseTable`] additionally requires **idempotence**
+//!   (`a op a = a`), marked by [`Idempotent`].
+//!   Each query calls `op` once.
+//! - [`DisjointSparseTable`] works for any semigroup,
+//!   e.g. composition of functions.
+//!   Each query calls `op` at most once.
+//!
+//! Both take `O(n log n)` time and space to build, and
+//! take inclusive ranges `l..=r` like `LazySegtree::query`.
+//!
+//! # Example
+//! ```
+//! # use sparse_table::{Min, SparseTable};
+//! let table = SparseTable::<Min<i32>>::from(vec![3, 1, 4, 1, 5]);
+//! assert_eq!(table.query(0, 0), 3);
+//! assert_eq!(table.query(2, 4), 1);
+//! ```
+//!
+//! - [LC Static RMQ](https://judge.yosupo.jp/problem/staticrmq)
+//! ```ignore
+//! let n = oj.usize();
+//! let q = oj.usize();
+//! let table = SparseTable::<Min<u32>>::from(oj.vec::<u32>(n));
+//! for _ in 0..q {
+//!     let (l, r) = (oj.usize(), oj.usize());
+//!     oj.write(table.query(l, r - 1)).ln();
+//! }
+//! ```
+
+extern crate gcd;
+
+use std::fmt::Debug;
+use std::marker::PhantomData;
+
+pub trait Semigroup {
+    /// Type of the value.
+    type V: Clone + Debug;
+
+    /// Operator `x op y`.
+    fn op(x: &Self::V, y: &Self::V) -> Self::V;
+}
+
+/// Marker for a [`Semigroup`] with `x op x = x`.
+pub trait Idempotent: Semigroup {}
+
+/// Minimum.
+#[derive(Clone, Debug)]
+pub struct Min<T>(PhantomData<T>);
+impl<T: Clone + Debug + Ord> Semigroup for Min<T> {
+    type V = T;
+    fn op(x: &T, y: &T) -> T {
+        x.min(y).clone()
+    }
+}
+impl<T: Clone + Debug + Ord> Idempotent for Min<T> {}
+
+/// Maximum.
+#[derive(Clone, Debug)]
+pub struct Max<T>(PhantomData<T>);
+impl<T: Clone + Debug + Ord> Semigroup for Max<T> {
+    type V = T;
+    fn op(x: &T, y: &T) -> T {
+        x.max(y).clone()
+    }
+}
+impl<T: Clone + Debug + Ord> Idempotent for Max<T> {}
+
+/// Greatest common divisor, using [`gcd::Gcd`].
+#[derive(Clone, Debug)]
+pub struct GcdOp<T>(PhantomData<T>);
+impl<T: Copy + Debug + gcd::Gcd> Semigroup for GcdOp<T> {
+    type V = T;
+    fn op(x: &T, y: &T) -> T {
+        x.gcd(*y)
+    }
+}
+impl<T: Copy + Debug + gcd::Gcd> Idempotent for GcdOp<T> {}
+
+/// Sparse table over [`Idempotent`] `T`.
+///
+/// In the method descriptions, let `n` be the number of elements.
+#[derive(Clone, Debug)]
+pub struct SparseTable<T: Idempotent> {
+    /// `table[k][i]` is the product of `i..i+2^k`.
+    table: Vec<Vec<T::V>>,
+}
+
+impl<T: Idempotent> From<Vec<T::V>> for SparseTable<T> {
+    /// 🕒 `O(n log n)`.
+    fn from(arr: Vec<T::V>) -> Self {
+        let mut table = vec![arr];
+        let mut k = 1;
+        while 1 << k <= table[0].len() {
+            let prev = &table[k - 1];
+            let row = (0..=table[0].len() - (1 << k))
+                .map(|i| T::op(&prev[i], &prev[i + (1 << (k - 1))]))
+                .collect();
+            table.push(row);
+            k += 1;
+        }
+        Self { table }
+    }
+}
+
+impl<T: Idempotent> SparseTable<T> {
+    /// Returns `n`.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.table[0].len()
+    }
+
+    /// Returns whether `n == 0`.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.table[0].is_empty()
+    }
+
+    /// Returns the product of the elements in `l..=r`.
+    ///
+    /// 🕒 `O(1)`.
+    ///
+    /// ⚠️ Panics if `l > r` or `r >= n`.
+    #[must_use]
+    pub fn query(&self, l: usize, r: usize) -> T::V {
+        assert!(
+            l <= r && r < self.len(),
+            "bad range {}..={} on {}",
+            l,
+            r,
+            self.len()
+        );
+        let k = (r - l + 1).ilog2() as usize;
+        T::op(&self.table[k][l], &self.table[k][r + 1 - (1 << k)])
+    }
+}
+
+/// Disjoint sparse table over [`Semigroup`] `T`.
+///
+/// In the method descriptions, let `n` be the number of elements.
+#[derive(Clone, Debug)]
+pub struct DisjointSparseTable<T: Semigroup> {
+    arr: Vec<T::V>,
+    /// For each block `b..b+2^(k+1)` with middle `m = b+2^k`,
+    /// `table[k][i]` is the product of `i..m` if `i < m`,
+    /// and of `m..=i` otherwise.
+    table: Vec<Vec<T::V>>,
+}
+
+impl<T: Semigroup> From<Vec<T::V>> for DisjointSparseTable<T> {
+    /// 🕒 `O(n log n)`.
+    fn from(arr: Vec<T::V>) -> Self {
+        let n = arr.len();
+        let mut table = vec![];
+        let mut k = 0;
+        while 1 << k < n {
+            let half = 1 << k;
+            let mut row = arr.clone();
+            for mid in (half..n).step_by(half * 2) {
+                for i in (mid - half..mid - 1).rev() {
+                    row[i] = T::op(&arr[i], &row[i + 1]);
+                }
+                for i in mid + 1..n.min(mid + half) {
+                    row[i] = T::op(&row[i - 1], &arr[i]);
+                }
+            }
+            table.push(row);
+            k += 1;
+        }
+        Self { arr, table }
+    }
+}
+
+impl<T: Semigroup> DisjointSparseTable<T> {
+    /// Returns `n`.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.arr.len()
+    }
+
+    /// Returns whether `n == 0`.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.arr.is_empty()
+    }
+
+    /// Returns the product of the elements in `l..=r`.
+    ///
+    /// 🕒 `O(1)`.
+    ///
+    /// ⚠️ Panics if `l > r` or `r >= n`.
+    #[must_use]
+    pub fn query(&self, l: usize, r: usize) -> T::V {
+        assert!(
+            l <= r && r < self.len(),
+            "bad range {}..={} on {}",
+            l,
+            r,
+            self.len()
+        );
+        if l == r {
+            return self.arr[l].clone();
+        }
+        let row = &self.table[(l ^ r).ilog2() as usize];
+        T::op(&row[l], &row[r])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    struct Concat;
+    impl Semigroup for Concat {
+        type V = Vec<usize>;
+        fn op(x: &Vec<usize>, y: &Vec<usize>) -> Vec<usize> {
+            [x.as_slice(), y].concat()
+        }
+    }
+
+    #[test]
+    fn test_sparse_table() {
+        for n in 1..70 {
+            let arr = (0..n as u64).map(|i| i * i * 7 % 60).collect::<Vec<_>>();
+            let min = SparseTable::<Min<u64>>::from(arr.clone());
+            let max = SparseTable::<Max<u64>>::from(arr.clone());
+            let gcd = SparseTable::<GcdOp<u64>>::from(arr.clone());
+            assert_eq!(min.len(), n);
+            for l in 0..n {
+                for r in l..n {
+                    let part = &arr[l..=r];
+                    assert_eq!(min.query(l, r), *part.iter().min().unwrap());
+                    assert_eq!(max.query(l, r), *part.iter().max().unwrap());
+                    let g = part.iter().fold(0, |acc, &x| gcd::Gcd::gcd(acc, x));
+                    assert_eq!(gcd.query(l, r), g);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_disjoint_sparse_table() {
+        for n in 1..70 {
+            let table =
+                DisjointSparseTable::<Concat>::from((0..n).map(|i| vec![i]).collect::<Vec<_>>());
+            assert_eq!(table.len(), n);
+            for l in 0..n {
+                for r in l..n {
+                    assert_eq!(table.query(l, r), (l..=r).collect::<Vec<_>>());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_empty() {
+        assert!(SparseTable::<Min<i32>>::from(vec![]).is_empty());
+        assert!(DisjointSparseTable::<Concat>::from(vec![]).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "bad range")]
+    fn test_bad_range() {
+        let table = DisjointSparseTable::<Max<i32>>::from(vec![1, 2, 3]);
+        let _ = table.query(2, 1);
+    }
+}
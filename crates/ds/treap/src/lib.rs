@@ -0,0 +1,936 @@
+//! [Treaps](https://en.wikipedia.org/wiki/Treap), with split and merge.
+//!
+//! - [`ImplicitTreap`] is a sequence indexed by position. It supports
+//!   insertion and removal at any position, range reversal, and
+//!   range products and actions over a [`LazyMonoid`].
+//! - [`TreapSet`] is an ordered set that also knows the rank of each
+//!   element, which `BTreeSet` does not.
+//!
+//! All operations take expected `O(log n)` time.
+//! Nodes live in an arena, and removed nodes are reused.
+//!
+//! # Usage
+//! [`ImplicitTreap`] uses [`LazyMonoid`] as in `lazy_segtree`,
+//! except that `unlazy` is always called with `nl = 0` and
+//! `nr = len - 1`, where `len` is the number of elements in `slot`.
+//! For [`ImplicitTreap::reverse`], the lazy action should not depend
+//! on the order of the elements, which is the case for most actions.
+//!
+//! # Example
+//! ```
+//! # extern crate lazy_segtree;
+//! # use lazy_segtree::LazyMonoid;
+//! # use treap::{ImplicitTreap, TreapSet};
+//! /// Range add, range concat.
+//! struct AddConcat;
+//! impl LazyMonoid for AddConcat {
+//!     type V = Vec<i64>;
+//!     type L = i64;
+//!     const ID: Vec<i64> = vec![];
+//!     fn op(x: &Vec<i64>, y: &Vec<i64>) -> Vec<i64> { [x.as_slice(), y].concat() }
+//!     const NOT_LAZY: i64 = 0;
+//!     fn combine_lazy(cur: &mut i64, up: &i64) { *cur += up; }
+//!     fn unlazy(slot: &mut Vec<i64>, lazy: i64, _: usize, _: usize) {
+//!         slot.iter_mut().for_each(|x| *x += lazy);
+//!     }
+//! }
+//!
+//! let mut seq = ImplicitTreap::<AddConcat>::from(vec![vec![1], vec![2], vec![3]]);
+//! seq.insert(1, vec![5]);
+//! seq.reverse(0..3);
+//! seq.apply(1.., 10);
+//! assert_eq!(seq.prod(..), vec![2, 15, 11, 13]);
+//!
+//! let mut set = TreapSet::new();
+//! set.insert(30);
+//! set.insert(10);
+//! set.insert(20);
+//! assert_eq!(set.kth(1), Some(&20));
+//! assert_eq!(set.rank(&25), 2);
+//! assert_eq!(set.range_count(15..=30), 2);
+//! ```
+//!
+//! - [LC Dynamic Sequence Range Affine Range Sum](https://judge.yosupo.jp/problem/dynamic_sequence_range_affine_range_sum)
+//! ```ignore
+//! let n = oj.usize();
+//! let q = oj.usize();
+//! let mut seq = ImplicitTreap::<RangeAffineSum<998244353>>::from(oj.vec::<Modint99>(n));
+//! for _ in 0..q {
+//!     match oj.usize() {
+//!         0 => { let i = oj.usize(); seq.insert(i, oj.read()); }
+//!         1 => { seq.remove(oj.usize()); }
+//!         2 => { seq.reverse(oj.usize()..oj.usize()); }
+//!         3 => {
+//!             let (l, r) = (oj.usize(), oj.usize());
+//!             seq.apply(l..r, (oj.read(), oj.read()));
+//!         }
+//!         _ => { oj.write(seq.prod(oj.usize()..oj.usize())).ln(); }
+//!     }
+//! }
+//! ```
+
+extern crate lazy_segtree;
+
+use lazy_segtree::to_half_open;
+use std::convert::TryFrom;
+use std::fmt::Debug;
+use std::iter::FromIterator;
+use std::ops::{Bound, RangeBounds};
+
+pub use lazy_segtree::LazyMonoid;
+
+/// Xorshift, for the priorities.
+const fn next_priority(seed: &mut u32) -> u32 {
+    *seed ^= *seed << 13;
+    *seed ^= *seed >> 17;
+    *seed ^= *seed << 5;
+    *seed
+}
+
+/// The null node index.
+const NIL: u32 = u32::MAX;
+
+struct Node<T: LazyMonoid> {
+    val: T::V,
+    /// Product of the subtree.
+    prod: T::V,
+    /// Product of the subtree in reverse order.
+    rprod: T::V,
+    lazy: T::L,
+    rev: bool,
+    size: usize,
+    pri: u32,
+    ch: [u32; 2],
+}
+
+impl<T: LazyMonoid> Clone for Node<T> {
+    fn clone(&self) -> Self {
+        Self {
+            val: self.val.clone(),
+            prod: self.prod.clone(),
+            rprod: self.rprod.clone(),
+            lazy: self.lazy.clone(),
+            rev: self.rev,
+            size: self.size,
+            pri: self.pri,
+            ch: self.ch,
+        }
+    }
+}
+
+impl<T: LazyMonoid> Node<T> {
+    fn new(val: T::V, pri: u32) -> Self {
+        Self {
+            prod: val.clone(),
+            rprod: val.clone(),
+            val,
+            lazy: T::NOT_LAZY,
+            rev: false,
+            size: 1,
+            pri,
+            ch: [NIL, NIL],
+        }
+    }
+}
+
+/// Sequence over [`LazyMonoid`] `T`.
+///
+/// A node's values already include its own lazy value, which is yet to be pushed to its children.
+///
+/// In the method descriptions, let `n` be the number of elements.
+pub struct ImplicitTreap<T: LazyMonoid> {
+    root: u32,
+    nodes: Vec<Node<T>>,
+    free: Vec<u32>,
+    seed: u32,
+}
+
+impl<T: LazyMonoid> Clone for ImplicitTreap<T> {
+    fn clone(&self) -> Self {
+        Self {
+            root: self.root,
+            nodes: self.nodes.clone(),
+            free: self.free.clone(),
+            seed: self.seed,
+        }
+    }
+}
+
+impl<T: LazyMonoid> Default for ImplicitTreap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: LazyMonoid> From<Vec<T::V>> for ImplicitTreap<T> {
+    /// 🕒 `O(n log n)`.
+    fn from(arr: Vec<T::V>) -> Self {
+        let mut treap = Self::new();
+        treap.nodes.reserve(arr.len());
+        for v in arr {
+            let x = treap.new_node(v);
+            treap.root = treap.merge(treap.root, x);
+        }
+        treap
+    }
+}
+
+impl<T: LazyMonoid> Debug for ImplicitTreap<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.to_vec()).finish()
+    }
+}
+
+impl<T: LazyMonoid> ImplicitTreap<T> {
+    /// Returns an empty sequence.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            root: NIL,
+            nodes: vec![],
+            free: vec![],
+            seed: 2_463_534_242,
+        }
+    }
+
+    /// Returns `n`.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.size(self.root)
+    }
+
+    /// Returns whether `n == 0`.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.root == NIL
+    }
+
+    fn size(&self, x: u32) -> usize {
+        if x == NIL {
+            0
+        } else {
+            self.nodes[x as usize].size
+        }
+    }
+
+    fn new_node(&mut self, v: T::V) -> u32 {
+        let node = Node::new(v, next_priority(&mut self.seed));
+        if let Some(x) = self.free.pop() {
+            self.nodes[x as usize] = node;
+            return x;
+        }
+        self.nodes.push(node);
+        u32::try_from(self.nodes.len() - 1).unwrap()
+    }
+
+    fn pull(&mut self, x: u32) {
+        let cur = &self.nodes[x as usize];
+        let [lc, rc] = cur.ch;
+        let (mut size, mut prod, mut rprod) = (1, cur.val.clone(), cur.val.clone());
+        if lc != NIL {
+            let left = &self.nodes[lc as usize];
+            size += left.size;
+            prod = T::op(&left.prod, &prod);
+            rprod = T::op(&rprod, &left.rprod);
+        }
+        if rc != NIL {
+            let right = &self.nodes[rc as usize];
+            size += right.size;
+            prod = T::op(&prod, &right.prod);
+            rprod = T::op(&right.rprod, &rprod);
+        }
+        let cur = &mut self.nodes[x as usize];
+        cur.size = size;
+        cur.prod = prod;
+        cur.rprod = rprod;
+    }
+
+    fn all_apply(&mut self, x: u32, lazy: &T::L) {
+        if x == NIL {
+            return;
+        }
+        let node = &mut self.nodes[x as usize];
+        let last = node.size - 1;
+        T::unlazy(&mut node.val, lazy.clone(), 0, 0);
+        T::unlazy(&mut node.prod, lazy.clone(), 0, last);
+        T::unlazy(&mut node.rprod, lazy.clone(), 0, last);
+        T::combine_lazy(&mut node.lazy, lazy);
+    }
+
+    fn toggle(&mut self, x: u32) {
+        if x == NIL {
+            return;
+        }
+        let node = &mut self.nodes[x as usize];
+        node.ch.swap(0, 1);
+        std::mem::swap(&mut node.prod, &mut node.rprod);
+        node.rev ^= true;
+    }
+
+    fn push(&mut self, x: u32) {
+        let node = &mut self.nodes[x as usize];
+        let [lc, rc] = node.ch;
+        let rev = std::mem::replace(&mut node.rev, false);
+        if node.lazy != T::NOT_LAZY {
+            let lazy = std::mem::replace(&mut node.lazy, T::NOT_LAZY);
+            self.all_apply(lc, &lazy);
+            self.all_apply(rc, &lazy);
+        }
+        if rev {
+            self.toggle(lc);
+            self.toggle(rc);
+        }
+    }
+
+    /// Splits `x` into the first `k` elements and the rest.
+    fn split(&mut self, x: u32, k: usize) -> (u32, u32) {
+        if x == NIL {
+            return (NIL, NIL);
+        }
+        self.push(x);
+        let [lc, rc] = self.nodes[x as usize].ch;
+        let left = self.size(lc);
+        if k <= left {
+            let (lo, hi) = self.split(lc, k);
+            self.nodes[x as usize].ch[0] = hi;
+            self.pull(x);
+            (lo, x)
+        } else {
+            let (lo, hi) = self.split(rc, k - left - 1);
+            self.nodes[x as usize].ch[1] = lo;
+            self.pull(x);
+            (x, hi)
+        }
+    }
+
+    fn merge(&mut self, a: u32, b: u32) -> u32 {
+        if a == NIL {
+            return b;
+        }
+        if b == NIL {
+            return a;
+        }
+        if self.nodes[a as usize].pri > self.nodes[b as usize].pri {
+            self.push(a);
+            let r = self.nodes[a as usize].ch[1];
+            self.nodes[a as usize].ch[1] = self.merge(r, b);
+            self.pull(a);
+            a
+        } else {
+            self.push(b);
+            let l = self.nodes[b as usize].ch[0];
+            self.nodes[b as usize].ch[0] = self.merge(a, l);
+            self.pull(b);
+            b
+        }
+    }
+
+    /// Runs `f` on the root of the elements `l..r`.
+    fn with_range<U>(&mut self, l: usize, r: usize, f: impl FnOnce(&mut Self, u32) -> U) -> U {
+        let (front, back) = self.split(self.root, r);
+        let (front, mid) = self.split(front, l);
+        let ret = f(self, mid);
+        let front = self.merge(front, mid);
+        self.root = self.merge(front, back);
+        ret
+    }
+
+    /// Inserts `v` so that it becomes the `i`-th element.
+    ///
+    /// 🕒 `O(log n)`.
+    ///
+    /// ⚠️ Panics if `i > n`.
+    pub fn insert(&mut self, i: usize, v: T::V) {
+        assert!(i <= self.len(), "index {} out of {}", i, self.len());
+        let node = self.new_node(v);
+        let (front, back) = self.split(self.root, i);
+        let front = self.merge(front, node);
+        self.root = self.merge(front, back);
+    }
+
+    /// Removes and returns the `i`-th element.
+    ///
+    /// 🕒 `O(log n)`.
+    ///
+    /// ⚠️ Panics if `i >= n`.
+    pub fn remove(&mut self, i: usize) -> T::V {
+        assert!(i < self.len(), "index {} out of {}", i, self.len());
+        let (front, back) = self.split(self.root, i + 1);
+        let (front, mid) = self.split(front, i);
+        self.root = self.merge(front, back);
+        self.free.push(mid);
+        std::mem::replace(&mut self.nodes[mid as usize].val, T::ID)
+    }
+
+    /// Returns the `i`-th element.
+    ///
+    /// 🕒 `O(log n)`.
+    ///
+    /// ⚠️ Panics if `i >= n`.
+    pub fn get(&mut self, mut i: usize) -> T::V {
+        assert!(i < self.len(), "index {} out of {}", i, self.len());
+        let mut x = self.root;
+        loop {
+            self.push(x);
+            let node = &self.nodes[x as usize];
+            let left = self.size(node.ch[0]);
+            match i.cmp(&left) {
+                std::cmp::Ordering::Less => x = node.ch[0],
+                std::cmp::Ordering::Equal => return node.val.clone(),
+                std::cmp::Ordering::Greater => {
+                    i -= left + 1;
+                    x = node.ch[1];
+                }
+            }
+        }
+    }
+
+    /// Sets the `i`-th element to `v`.
+    ///
+    /// 🕒 `O(log n)`.
+    ///
+    /// ⚠️ Panics if `i >= n`.
+    pub fn set(&mut self, i: usize, v: T::V) {
+        assert!(i < self.len(), "index {} out of {}", i, self.len());
+        self.with_range(i, i + 1, |me, x| {
+            me.nodes[x as usize].val = v;
+            me.pull(x);
+        });
+    }
+
+    /// Returns the product of the elements in `range`,
+    /// or `ID` if the range is empty.
+    /// Both `l..r` and `l..=r` are accepted.
+    ///
+    /// 🕒 `O(log n)`.
+    ///
+    /// ⚠️ Panics if the range is out of bounds.
+    pub fn prod(&mut self, range: impl RangeBounds<usize>) -> T::V {
+        let (l, r) = to_half_open(range, self.len());
+        self.with_range(l, r, |me, x| me.prod_of(x))
+    }
+
+    /// Applies `lazy` to every element in `range`.
+    /// Both `l..r` and `l..=r` are accepted.
+    ///
+    /// 🕒 `O(log n)`.
+    ///
+    /// ⚠️ Panics if the range is out of bounds.
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn apply(&mut self, range: impl RangeBounds<usize>, lazy: T::L) {
+        let (l, r) = to_half_open(range, self.len());
+        self.with_range(l, r, |me, x| me.all_apply(x, &lazy));
+    }
+
+    /// Reverses the elements in `range`.
+    /// Both `l..r` and `l..=r` are accepted.
+    ///
+    /// 🕒 `O(log n)`.
+    ///
+    /// ⚠️ Panics if the range is out of bounds.
+    pub fn reverse(&mut self, range: impl RangeBounds<usize>) {
+        let (l, r) = to_half_open(range, self.len());
+        self.with_range(l, r, Self::toggle);
+    }
+
+    /// Returns the product of all elements.
+    ///
+    /// 🕒 `O(1)`.
+    #[must_use]
+    pub fn all_prod(&self) -> T::V {
+        self.prod_of(self.root)
+    }
+
+    fn prod_of(&self, x: u32) -> T::V {
+        if x == NIL {
+            T::ID
+        } else {
+            self.nodes[x as usize].prod.clone()
+        }
+    }
+
+    /// Returns all elements in order.
+    ///
+    /// 🕒 `O(n)`.
+    #[must_use]
+    pub fn to_vec(&self) -> Vec<T::V> {
+        let mut out = Vec::with_capacity(self.len());
+        self.collect(self.root, &T::NOT_LAZY, false, &mut out);
+        out
+    }
+
+    /// Pushes the elements of `x` to `out`, where `lazy` and `rev`
+    /// are pending from the ancestors.
+    fn collect(&self, x: u32, lazy: &T::L, rev: bool, out: &mut Vec<T::V>) {
+        if x == NIL {
+            return;
+        }
+        let node = &self.nodes[x as usize];
+        let mut val = node.val.clone();
+        let mut down = node.lazy.clone();
+        if *lazy != T::NOT_LAZY {
+            T::unlazy(&mut val, lazy.clone(), 0, 0);
+            T::combine_lazy(&mut down, lazy);
+        }
+        let [lc, rc] = node.ch;
+        let (lc, rc) = if rev { (rc, lc) } else { (lc, rc) };
+        let rev = rev ^ node.rev;
+        self.collect(lc, &down, rev, out);
+        out.push(val);
+        self.collect(rc, &down, rev, out);
+    }
+}
+
+#[derive(Clone, Debug)]
+struct SetNode<K> {
+    key: K,
+    size: usize,
+    pri: u32,
+    ch: [u32; 2],
+}
+
+/// Ordered set of `K` with rank queries.
+///
+/// In the method descriptions, let `n` be the number of elements.
+#[derive(Clone)]
+pub struct TreapSet<K: Ord> {
+    root: u32,
+    nodes: Vec<SetNode<K>>,
+    free: Vec<u32>,
+    seed: u32,
+}
+
+impl<K: Ord> Default for TreapSet<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord + Debug> Debug for TreapSet<K> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut keys = vec![];
+        let mut stack = vec![];
+        let mut x = self.root;
+        while x != NIL || !stack.is_empty() {
+            while x != NIL {
+                stack.push(x);
+                x = self.nodes[x as usize].ch[0];
+            }
+            let y = stack.pop().unwrap();
+            keys.push(&self.nodes[y as usize].key);
+            x = self.nodes[y as usize].ch[1];
+        }
+        f.debug_set().entries(keys).finish()
+    }
+}
+
+impl<K: Ord> FromIterator<K> for TreapSet<K> {
+    fn from_iter<I: IntoIterator<Item = K>>(iter: I) -> Self {
+        let mut set = Self::new();
+        for k in iter {
+            set.insert(k);
+        }
+        set
+    }
+}
+
+impl<K: Ord> TreapSet<K> {
+    /// Returns an empty set.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            root: NIL,
+            nodes: vec![],
+            free: vec![],
+            seed: 2_463_534_242,
+        }
+    }
+
+    fn size(&self, x: u32) -> usize {
+        if x == NIL {
+            0
+        } else {
+            self.nodes[x as usize].size
+        }
+    }
+
+    /// Returns `n`.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.size(self.root)
+    }
+
+    /// Returns whether `n == 0`.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.root == NIL
+    }
+
+    fn pull(&mut self, x: u32) {
+        let [l, r] = self.nodes[x as usize].ch;
+        self.nodes[x as usize].size = self.size(l) + 1 + self.size(r);
+    }
+
+    /// Splits `x` into the keys `< key` (or `<= key` if `inclusive`)
+    /// and the rest.
+    fn split(&mut self, x: u32, key: &K, inclusive: bool) -> (u32, u32) {
+        if x == NIL {
+            return (NIL, NIL);
+        }
+        let [lc, rc] = self.nodes[x as usize].ch;
+        let cur = &self.nodes[x as usize].key;
+        if cur < key || (inclusive && cur == key) {
+            let (lo, hi) = self.split(rc, key, inclusive);
+            self.nodes[x as usize].ch[1] = lo;
+            self.pull(x);
+            (x, hi)
+        } else {
+            let (lo, hi) = self.split(lc, key, inclusive);
+            self.nodes[x as usize].ch[0] = hi;
+            self.pull(x);
+            (lo, x)
+        }
+    }
+
+    fn merge(&mut self, a: u32, b: u32) -> u32 {
+        if a == NIL {
+            return b;
+        }
+        if b == NIL {
+            return a;
+        }
+        if self.nodes[a as usize].pri > self.nodes[b as usize].pri {
+            let r = self.nodes[a as usize].ch[1];
+            self.nodes[a as usize].ch[1] = self.merge(r, b);
+            self.pull(a);
+            a
+        } else {
+            let l = self.nodes[b as usize].ch[0];
+            self.nodes[b as usize].ch[0] = self.merge(a, l);
+            self.pull(b);
+            b
+        }
+    }
+
+    /// Returns whether `key` is in the set.
+    ///
+    /// 🕒 `O(log n)`.
+    #[must_use]
+    pub fn contains(&self, key: &K) -> bool {
+        let mut x = self.root;
+        while x != NIL {
+            let node = &self.nodes[x as usize];
+            match key.cmp(&node.key) {
+                std::cmp::Ordering::Less => x = node.ch[0],
+                std::cmp::Ordering::Equal => return true,
+                std::cmp::Ordering::Greater => x = node.ch[1],
+            }
+        }
+        false
+    }
+
+    /// Inserts `key`, and returns whether it was newly inserted.
+    ///
+    /// 🕒 `O(log n)`.
+    pub fn insert(&mut self, key: K) -> bool {
+        if self.contains(&key) {
+            return false;
+        }
+        let (a, b) = self.split(self.root, &key, false);
+        let node = SetNode {
+            key,
+            size: 1,
+            pri: next_priority(&mut self.seed),
+            ch: [NIL, NIL],
+        };
+        let x = if let Some(x) = self.free.pop() {
+            self.nodes[x as usize] = node;
+            x
+        } else {
+            self.nodes.push(node);
+            u32::try_from(self.nodes.len() - 1).unwrap()
+        };
+        let ax = self.merge(a, x);
+        self.root = self.merge(ax, b);
+        true
+    }
+
+    /// Removes `key`, and returns whether it was in the set.
+    ///
+    /// 🕒 `O(log n)`.
+    pub fn remove(&mut self, key: &K) -> bool {
+        if !self.contains(key) {
+            return false;
+        }
+        let (a, bc) = self.split(self.root, key, false);
+        let (b, c) = self.split(bc, key, true);
+        self.free.push(b);
+        self.root = self.merge(a, c);
+        true
+    }
+
+    /// Returns the `k`-th smallest key (0-indexed),
+    /// or `None` if `k >= n`.
+    ///
+    /// 🕒 `O(log n)`.
+    #[must_use]
+    pub fn kth(&self, mut k: usize) -> Option<&K> {
+        let mut x = self.root;
+        while x != NIL {
+            let node = &self.nodes[x as usize];
+            let left = self.size(node.ch[0]);
+            match k.cmp(&left) {
+                std::cmp::Ordering::Less => x = node.ch[0],
+                std::cmp::Ordering::Equal => return Some(&node.key),
+                std::cmp::Ordering::Greater => {
+                    k -= left + 1;
+                    x = node.ch[1];
+                }
+            }
+        }
+        None
+    }
+
+    /// Returns the number of keys `< key` (or `<= key` if `inclusive`).
+    fn count_below(&self, key: &K, inclusive: bool) -> usize {
+        let mut x = self.root;
+        let mut cnt = 0;
+        while x != NIL {
+            let node = &self.nodes[x as usize];
+            if node.key < *key || (inclusive && node.key == *key) {
+                cnt += self.size(node.ch[0]) + 1;
+                x = node.ch[1];
+            } else {
+                x = node.ch[0];
+            }
+        }
+        cnt
+    }
+
+    /// Returns the number of keys less than `key`.
+    ///
+    /// 🕒 `O(log n)`.
+    #[must_use]
+    pub fn rank(&self, key: &K) -> usize {
+        self.count_below(key, false)
+    }
+
+    /// Returns the number of keys in `range`.
+    ///
+    /// 🕒 `O(log n)`.
+    #[must_use]
+    pub fn range_count(&self, range: impl RangeBounds<K>) -> usize {
+        let l = match range.start_bound() {
+            Bound::Included(k) => self.count_below(k, false),
+            Bound::Excluded(k) => self.count_below(k, true),
+            Bound::Unbounded => 0,
+        };
+        let r = match range.end_bound() {
+            Bound::Included(k) => self.count_below(k, true),
+            Bound::Excluded(k) => self.count_below(k, false),
+            Bound::Unbounded => self.len(),
+        };
+        r.saturating_sub(l)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ImplicitTreap, LazyMonoid, TreapSet};
+    use std::collections::BTreeSet;
+
+    /// Range add on a list of elements, to catch
+    /// non-commutative mistakes and wrong lengths.
+    struct AddConcat;
+    impl LazyMonoid for AddConcat {
+        type V = Vec<u64>;
+        type L = u64;
+        const ID: Vec<u64> = vec![];
+        fn op(x: &Vec<u64>, y: &Vec<u64>) -> Vec<u64> {
+            x.iter().chain(y).copied().collect()
+        }
+        const NOT_LAZY: u64 = 0;
+        fn combine_lazy(cur: &mut u64, up: &u64) {
+            *cur += up;
+        }
+        fn unlazy(slot: &mut Vec<u64>, lazy: u64, nl: usize, nr: usize) {
+            assert_eq!(slot.len(), nr - nl + 1);
+            for x in slot {
+                *x += lazy;
+            }
+        }
+    }
+
+    enum SeqQuery {
+        Insert(usize, u64),
+        Remove(usize),
+        Set(usize, u64),
+        Apply(usize, usize, u64),
+        Reverse(usize, usize),
+        Prod(usize, usize),
+    }
+
+    fn do_test_implicit_treap(queries: Vec<SeqQuery>) {
+        let mut brute = vec![];
+        let mut seq = ImplicitTreap::<AddConcat>::new();
+        for (qi, query) in queries.into_iter().enumerate() {
+            match query {
+                SeqQuery::Insert(i, v) => {
+                    seq.insert(i, vec![v]);
+                    brute.insert(i, v);
+                }
+                SeqQuery::Remove(i) => {
+                    assert_eq!(seq.remove(i), vec![brute.remove(i)], "query {qi}");
+                }
+                SeqQuery::Set(i, v) => {
+                    seq.set(i, vec![v]);
+                    brute[i] = v;
+                    assert_eq!(seq.get(i), vec![v], "query {qi}");
+                }
+                SeqQuery::Apply(l, r, v) => {
+                    seq.apply(l..r, v);
+                    for x in &mut brute[l..r] {
+                        *x += v;
+                    }
+                }
+                SeqQuery::Reverse(l, r) => {
+                    seq.reverse(l..r);
+                    brute[l..r].reverse();
+                }
+                SeqQuery::Prod(l, r) => assert_eq!(seq.prod(l..r), brute[l..r], "query {qi}"),
+            }
+            assert_eq!(seq.len(), brute.len(), "query {qi}");
+            assert_eq!(seq.all_prod(), brute, "query {qi}");
+        }
+        let expected = brute.iter().map(|&x| vec![x]).collect::<Vec<_>>();
+        assert_eq!(seq.to_vec(), expected);
+        assert_eq!(format!("{seq:?}"), format!("{expected:?}"));
+    }
+
+    #[test]
+    fn test_implicit_treap() {
+        do_test_implicit_treap(vec![
+            SeqQuery::Prod(0, 0),
+            SeqQuery::Insert(0, 3),
+            SeqQuery::Insert(1, 1),
+            SeqQuery::Insert(0, 4),
+            SeqQuery::Insert(2, 1),
+            SeqQuery::Insert(4, 5),
+            SeqQuery::Insert(2, 9),
+            SeqQuery::Prod(1, 4),
+            SeqQuery::Reverse(0, 6),
+            SeqQuery::Prod(0, 3),
+            SeqQuery::Apply(1, 5, 10),
+            SeqQuery::Reverse(2, 6),
+            SeqQuery::Prod(0, 6),
+            SeqQuery::Remove(3),
+            SeqQuery::Set(0, 7),
+            SeqQuery::Apply(0, 2, 1),
+            SeqQuery::Reverse(0, 5),
+            SeqQuery::Insert(5, 2),
+            SeqQuery::Reverse(1, 4),
+            SeqQuery::Remove(0),
+            SeqQuery::Prod(2, 5),
+            SeqQuery::Set(4, 0),
+            SeqQuery::Reverse(3, 3),
+            SeqQuery::Prod(0, 5),
+        ]);
+        do_test_implicit_treap(vec![
+            SeqQuery::Insert(0, 1),
+            SeqQuery::Reverse(0, 1),
+            SeqQuery::Apply(0, 1, 2),
+            SeqQuery::Remove(0),
+            SeqQuery::Insert(0, 5),
+        ]);
+    }
+
+    /// Reverses and then adds to every range in turn,
+    /// so that lazy values are pushed through reversed nodes.
+    #[test]
+    fn test_implicit_treap_ranges() {
+        for n in 1..10 {
+            let mut queries = (0..n)
+                .map(|i| SeqQuery::Insert(i, i as u64))
+                .collect::<Vec<_>>();
+            for l in 0..n {
+                for r in l + 1..=n {
+                    queries.push(SeqQuery::Reverse(l, r));
+                    queries.push(SeqQuery::Apply(l, r, (l * n + r) as u64));
+                }
+            }
+            do_test_implicit_treap(queries);
+        }
+    }
+
+    enum SetQuery {
+        Insert(usize),
+        Remove(usize),
+        /// Checks the queries around `x` and `y`.
+        Check(usize, usize),
+    }
+
+    fn do_test_treap_set(queries: Vec<SetQuery>) {
+        let mut brute = BTreeSet::new();
+        let mut set = TreapSet::new();
+        for (qi, query) in queries.into_iter().enumerate() {
+            match query {
+                SetQuery::Insert(x) => assert_eq!(set.insert(x), brute.insert(x), "query {qi}"),
+                SetQuery::Remove(x) => assert_eq!(set.remove(&x), brute.remove(&x), "query {qi}"),
+                SetQuery::Check(x, y) => {
+                    assert_eq!(set.contains(&x), brute.contains(&x), "query {qi}");
+                    assert_eq!(set.kth(x), brute.iter().nth(x), "query {qi}");
+                    assert_eq!(set.rank(&x), brute.range(..x).count(), "query {qi}");
+                    assert_eq!(
+                        set.range_count(x..y),
+                        brute.range(x..y).count(),
+                        "query {qi}"
+                    );
+                    assert_eq!(
+                        set.range_count(x..=y),
+                        brute.range(x..=y).count(),
+                        "query {qi}"
+                    );
+                    assert_eq!(set.range_count(y + 1..=x), 0, "query {qi}");
+                }
+            }
+            assert_eq!(set.len(), brute.len(), "query {qi}");
+        }
+        assert_eq!(format!("{set:?}"), format!("{brute:?}"));
+    }
+
+    #[test]
+    fn test_treap_set() {
+        do_test_treap_set(vec![
+            SetQuery::Check(0, 5),
+            SetQuery::Insert(5),
+            SetQuery::Insert(1),
+            SetQuery::Insert(9),
+            SetQuery::Insert(5),
+            SetQuery::Insert(3),
+            SetQuery::Check(0, 1),
+            SetQuery::Check(1, 5),
+            SetQuery::Check(3, 3),
+            SetQuery::Check(4, 9),
+            SetQuery::Remove(4),
+            SetQuery::Remove(5),
+            SetQuery::Remove(5),
+            SetQuery::Check(2, 8),
+            SetQuery::Insert(0),
+            SetQuery::Insert(7),
+            SetQuery::Check(0, 9),
+            SetQuery::Check(4, 10),
+            SetQuery::Remove(0),
+            SetQuery::Remove(9),
+            SetQuery::Check(1, 7),
+        ]);
+        // Inserting and removing in order, to exercise
+        // rotations on a sorted sequence.
+        let mut queries = (0..50)
+            .map(|x| SetQuery::Insert(x * 3 % 50))
+            .collect::<Vec<_>>();
+        queries.extend((0..50).map(|x| SetQuery::Check(x, x + 10)));
+        queries.extend((0..50).step_by(2).map(SetQuery::Remove));
+        queries.extend((0..50).map(|x| SetQuery::Check(x, x + 10)));
+        do_test_treap_set(queries);
+    }
+}
@@ -0,0 +1,339 @@
+//! [Wavelet matrix](https://doi.org/10.1016/j.is.2014.06.002)
+//! for static range k-th smallest and range frequency queries.
+//!
+//! The values are coordinate-compressed first, so any `T: Ord + Copy`
+//! such as `u64` or `i64` works. Let `m` be the number of distinct
+//! values; each query takes `O(log m)` time.
+//!
+//! All index ranges are inclusive `l..=r`, as in `Fenwick::sum`.
+//!
+//! # Example
+//! ```
+//! # use wavelet_matrix::WaveletMatrix;
+//! let wm = WaveletMatrix::from(vec![5i64, -3, 8, 5, 0, 2]);
+//! assert_eq!(wm.kth_smallest(1, 4, 0), -3);
+//! assert_eq!(wm.kth_smallest(1, 4, 2), 5);
+//! assert_eq!(wm.rank(0, 5, 5), 3); // -3, 0, 2
+//! assert_eq!(wm.range_freq(0, 5, 0, 6), 4); // 5, 5, 0, 2
+//! assert_eq!(wm.prev_value(0, 3, 5), Some(-3));
+//! assert_eq!(wm.next_value(0, 3, 6), Some(8));
+//! assert_eq!(wm.next_value(0, 3, 9), None);
+//! ```
+//!
+//! - [LC Range Kth Smallest](https://judge.yosupo.jp/problem/range_kth_smallest)
+//! ```ignore
+//! let n = oj.usize();
+//! let q = oj.usize();
+//! let wm = WaveletMatrix::from(oj.vec::<u64>(n));
+//! for _ in 0..q {
+//!     let (l, r, k) = (oj.usize(), oj.usize(), oj.usize());
+//!     oj.write(wm.kth_smallest(l, r - 1, k)).ln();
+//! }
+//! ```
+
+/// Bit vector with `O(1)` rank.
+#[derive(Clone, Debug)]
+struct BitVector {
+    words: Vec<u64>,
+    /// `ones[i]` is the number of `1`s in `words[..i]`.
+    ones: Vec<usize>,
+}
+
+impl BitVector {
+    fn new(bits: impl Iterator<Item = bool>, n: usize) -> Self {
+        let mut words = vec![0u64; n / 64 + 1];
+        for (i, bit) in bits.enumerate() {
+            words[i / 64] |= u64::from(bit) << (i % 64);
+        }
+        let mut ones = vec![0; words.len() + 1];
+        for i in 0..words.len() {
+            ones[i + 1] = ones[i] + words[i].count_ones() as usize;
+        }
+        Self { words, ones }
+    }
+
+    /// Returns the number of `0`s in `..i`.
+    fn rank0(&self, i: usize) -> usize {
+        let mask = (1u64 << (i % 64)) - 1;
+        i - self.ones[i / 64] - (self.words[i / 64] & mask).count_ones() as usize
+    }
+}
+
+/// Wavelet matrix over `T`.
+///
+/// In the method descriptions, let `n` be the number of elements.
+#[derive(Clone, Debug)]
+pub struct WaveletMatrix<T> {
+    n: usize,
+    /// Sorted distinct values.
+    vals: Vec<T>,
+    /// From the highest bit to the lowest.
+    levels: Vec<BitVector>,
+    /// Number of `0`s in each level.
+    zeros: Vec<usize>,
+}
+
+impl<T: Ord + Copy> From<Vec<T>> for WaveletMatrix<T> {
+    /// 🕒 `O(n log n)`.
+    fn from(arr: Vec<T>) -> Self {
+        let n = arr.len();
+        let mut vals = arr.clone();
+        vals.sort_unstable();
+        vals.dedup();
+        let height = (usize::BITS - vals.len().leading_zeros()) as usize;
+        let mut cur = arr
+            .iter()
+            .map(|x| vals.binary_search(x).unwrap())
+            .collect::<Vec<_>>();
+        let mut levels = Vec::with_capacity(height);
+        let mut zeros = Vec::with_capacity(height);
+        for h in (0..height).rev() {
+            levels.push(BitVector::new(cur.iter().map(|&c| c >> h & 1 == 1), n));
+            let (mut lo, hi): (Vec<_>, Vec<_>) = cur.into_iter().partition(|&c| c >> h & 1 == 0);
+            zeros.push(lo.len());
+            lo.extend(hi);
+            cur = lo;
+        }
+        Self {
+            n,
+            vals,
+            levels,
+            zeros,
+        }
+    }
+}
+
+impl<T: Ord + Copy> WaveletMatrix<T> {
+    /// Returns `n`.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.n
+    }
+
+    /// Returns whether `n == 0`.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    fn check_range(&self, l: usize, r: usize) {
+        assert!(
+            l <= r && r < self.n,
+            "bad range {}..={} on {}",
+            l,
+            r,
+            self.n
+        );
+    }
+
+    /// Returns the `k`-th smallest (0-indexed) value in `l..=r`.
+    ///
+    /// 🕒 `O(log m)`.
+    ///
+    /// ⚠️ Panics if `l > r`, `r >= n`, or `k > r-l`.
+    #[must_use]
+    pub fn kth_smallest(&self, l: usize, r: usize, mut k: usize) -> T {
+        self.check_range(l, r);
+        assert!(k <= r - l, "k = {} too large for {}..={}", k, l, r);
+        let (mut l, mut r) = (l, r + 1);
+        let mut c = 0;
+        for (level, &zeros) in self.levels.iter().zip(&self.zeros) {
+            let (zl, zr) = (level.rank0(l), level.rank0(r));
+            c <<= 1;
+            if k < zr - zl {
+                (l, r) = (zl, zr);
+            } else {
+                k -= zr - zl;
+                c |= 1;
+                (l, r) = (zeros + l - zl, zeros + r - zr);
+            }
+        }
+        self.vals[c]
+    }
+
+    /// Returns the number of values in `l..r` (half-open)
+    /// whose compressed value is less than `c`.
+    fn count_less(&self, mut l: usize, mut r: usize, c: usize) -> usize {
+        if c >= self.vals.len() {
+            return r - l;
+        }
+        let height = self.levels.len();
+        let mut cnt = 0;
+        for (h, (level, &zeros)) in self.levels.iter().zip(&self.zeros).enumerate() {
+            let (zl, zr) = (level.rank0(l), level.rank0(r));
+            if c >> (height - 1 - h) & 1 == 1 {
+                cnt += zr - zl;
+                (l, r) = (zeros + l - zl, zeros + r - zr);
+            } else {
+                (l, r) = (zl, zr);
+            }
+        }
+        cnt
+    }
+
+    /// Returns the number of values less than `x` in `l..=r`.
+    ///
+    /// 🕒 `O(log m)`.
+    ///
+    /// ⚠️ Panics if `l > r` or `r >= n`.
+    #[must_use]
+    pub fn rank(&self, l: usize, r: usize, x: T) -> usize {
+        self.check_range(l, r);
+        self.count_less(l, r + 1, self.vals.partition_point(|v| *v < x))
+    }
+
+    /// Returns the number of values `v` in `l..=r`
+    /// with `lo <= v < hi`.
+    ///
+    /// 🕒 `O(log m)`.
+    ///
+    /// ⚠️ Panics if `l > r` or `r >= n`.
+    #[must_use]
+    pub fn range_freq(&self, l: usize, r: usize, lo: T, hi: T) -> usize {
+        if lo >= hi {
+            self.check_range(l, r);
+            return 0;
+        }
+        self.rank(l, r, hi) - self.rank(l, r, lo)
+    }
+
+    /// Returns the largest value less than `x` in `l..=r`,
+    /// or `None` if there is no such value.
+    ///
+    /// 🕒 `O(log m)`.
+    ///
+    /// ⚠️ Panics if `l > r` or `r >= n`.
+    #[must_use]
+    pub fn prev_value(&self, l: usize, r: usize, x: T) -> Option<T> {
+        match self.rank(l, r, x) {
+            0 => None,
+            cnt => Some(self.kth_smallest(l, r, cnt - 1)),
+        }
+    }
+
+    /// Returns the smallest value at least `x` in `l..=r`,
+    /// or `None` if there is no such value.
+    ///
+    /// 🕒 `O(log m)`.
+    ///
+    /// ⚠️ Panics if `l > r` or `r >= n`.
+    #[must_use]
+    pub fn next_value(&self, l: usize, r: usize, x: T) -> Option<T> {
+        let cnt = self.rank(l, r, x);
+        if cnt > r - l {
+            None
+        } else {
+            Some(self.kth_smallest(l, r, cnt))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::WaveletMatrix;
+
+    enum Query<T> {
+        Kth(usize, usize, usize),
+        Rank(usize, usize, T),
+        Freq(usize, usize, T, T),
+        Prev(usize, usize, T),
+        Next(usize, usize, T),
+    }
+
+    fn do_test_wavelet<T>(arr: &[T], queries: Vec<Query<T>>)
+    where
+        T: Ord + Copy + std::fmt::Debug,
+    {
+        let wm = WaveletMatrix::from(arr.to_vec());
+        assert_eq!(wm.len(), arr.len());
+        for (qi, query) in queries.into_iter().enumerate() {
+            match query {
+                Query::Kth(l, r, k) => {
+                    let mut part = arr[l..=r].to_vec();
+                    part.sort_unstable();
+                    assert_eq!(wm.kth_smallest(l, r, k), part[k], "query {qi}");
+                }
+                Query::Rank(l, r, x) => {
+                    let ans = arr[l..=r].iter().filter(|&&v| v < x).count();
+                    assert_eq!(wm.rank(l, r, x), ans, "query {qi}");
+                }
+                Query::Freq(l, r, lo, hi) => {
+                    let ans = arr[l..=r].iter().filter(|&&v| lo <= v && v < hi).count();
+                    assert_eq!(wm.range_freq(l, r, lo, hi), ans, "query {qi}");
+                }
+                Query::Prev(l, r, x) => {
+                    let ans = arr[l..=r].iter().filter(|&&v| v < x).max().copied();
+                    assert_eq!(wm.prev_value(l, r, x), ans, "query {qi}");
+                }
+                Query::Next(l, r, x) => {
+                    let ans = arr[l..=r].iter().filter(|&&v| v >= x).min().copied();
+                    assert_eq!(wm.next_value(l, r, x), ans, "query {qi}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_wavelet_u64() {
+        do_test_wavelet(
+            &[2u64, 1, 5, 3, 4, 3, 1, 7, 4, 5],
+            vec![
+                Query::Kth(0, 9, 0),
+                Query::Kth(0, 9, 9),
+                Query::Kth(2, 7, 3),
+                Query::Kth(4, 4, 0),
+                Query::Rank(0, 9, 4),
+                Query::Rank(3, 8, 0),
+                Query::Rank(3, 8, 100),
+                Query::Freq(0, 9, 3, 5),
+                Query::Freq(1, 6, 5, 3),
+                Query::Freq(1, 6, 0, 100),
+                Query::Prev(0, 9, 1),
+                Query::Prev(2, 5, 5),
+                Query::Next(0, 9, 6),
+                Query::Next(0, 9, 8),
+                Query::Next(5, 9, 0),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_wavelet_i64() {
+        do_test_wavelet(
+            &[-7i64, 0, i64::MAX, 3, i64::MIN, 3, -7],
+            vec![
+                Query::Kth(0, 6, 0),
+                Query::Kth(0, 6, 6),
+                Query::Kth(1, 5, 2),
+                Query::Rank(0, 6, 3),
+                Query::Rank(0, 6, i64::MAX),
+                Query::Freq(0, 6, -7, 4),
+                Query::Freq(2, 4, i64::MIN, i64::MAX),
+                Query::Prev(0, 6, -7),
+                Query::Prev(0, 3, i64::MAX),
+                Query::Next(1, 4, 1),
+                Query::Next(4, 6, 4),
+            ],
+        );
+    }
+
+    /// Checks every query on every range of a small array.
+    #[test]
+    fn test_wavelet_all() {
+        let arr = [3usize, 0, 6, 3, 7, 1, 1, 5, 0, 6, 2, 7];
+        let n = arr.len();
+        let mut queries = vec![];
+        for l in 0..n {
+            for r in l..n {
+                queries.extend((0..=r - l).map(|k| Query::Kth(l, r, k)));
+                for x in 0..10 {
+                    queries.push(Query::Rank(l, r, x));
+                    queries.push(Query::Prev(l, r, x));
+                    queries.push(Query::Next(l, r, x));
+                    queries.extend((0..10).map(|y| Query::Freq(l, r, x, y)));
+                }
+            }
+        }
+        do_test_wavelet(&arr, queries);
+    }
+}
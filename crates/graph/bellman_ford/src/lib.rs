@@ -0,0 +1,333 @@
+//! [Bellman-Ford algorithm](https://en.wikipedia.org/wiki/Bellman%E2%80%93Ford_algorithm),
+//! shortest paths with negative costs.
+//!
+//! Uses [`Graph`].
+//!
+//! # Edge costs
+//! The edge costs are parametrized over
+//! `T: Copy + Default + Ord + Add<Output = T>`,
+//! where `T::default()` is zero, e.g. `i64`.
+//! Unlike `Dijkstra`, the costs may be negative.
+//!
+//! # Example
+//! ```
+//! # extern crate edge_list;
+//! # use bellman_ford::{bellman_ford, Dist};
+//! # use edge_list::Graph;
+//! let mut graph = Graph::new(5);
+//! graph.direct(0, 1, 4i64);
+//! graph.direct(1, 2, -3);
+//! graph.direct(3, 4, -1);
+//! graph.direct(4, 3, -1);
+//! let bf = bellman_ford(&graph, &[0]);
+//! assert_eq!(bf.dist, vec![Dist::Finite(0), Dist::Finite(4), Dist::Finite(1), Dist::Unreachable, Dist::Unreachable]);
+//! assert_eq!(bf.path(2), Some(vec![0, 1, 2]));
+//! assert_eq!(bf.cycle, None);
+//!
+//! graph.direct(2, 3, 0);
+//! let bf = bellman_ford(&graph, &[0]);
+//! assert_eq!(bf.dist[4], Dist::NegInf);
+//! assert!(bf.cycle == Some(vec![3, 4]) || bf.cycle == Some(vec![4, 3]));
+//! ```
+//!
+//! - [LC Shortest Path](https://judge.yosupo.jp/problem/shortest_path)
+//!   also works, but is much slower than `Dijkstra`.
+//! - [CSES High Score](https://cses.fi/problemset/task/1673)
+//! ```ignore
+//! let (n, m) = (oj.usize(), oj.usize());
+//! let mut graph = Graph::new(n);
+//! for _ in 0..m {
+//!     graph.direct(oj.usize() - 1, oj.usize() - 1, -oj.i64());
+//! }
+//! match bellman_ford(&graph, &[0]).dist[n - 1] {
+//!     Dist::Finite(d) => oj.write(-d).ln(),
+//!     _ => oj.write(-1).ln(),
+//! };
+//! ```
+
+extern crate edge_list;
+
+use edge_list::Graph;
+use std::collections::VecDeque;
+use std::ops::Add;
+
+/// Distance from the sources.
+///
+/// The variants are ordered as `NegInf < Finite(_) < Unreachable`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Dist<T> {
+    /// Arbitrarily small, through a negative cycle.
+    NegInf,
+    /// The minimum cost of a path.
+    Finite(T),
+    /// No path exists.
+    Unreachable,
+}
+
+/// Result of [`bellman_ford`].
+#[derive(Clone, Debug)]
+pub struct BellmanFord<T> {
+    /// `dist[v]` is the distance from the nearest source to `v`.
+    pub dist: Vec<Dist<T>>,
+    /// `parent[v]` is the previous vertex on a shortest path to `v`,
+    /// or `None` if `v` is a source or unreachable.
+    /// Only meaningful if `dist[v]` is finite.
+    pub parent: Vec<Option<usize>>,
+    /// A negative cycle reachable from the sources, if any.
+    /// For each `i`, there is an edge from `cycle[i]` to
+    /// `cycle[i+1]` (cyclically), and the sum of their costs
+    /// is negative.
+    pub cycle: Option<Vec<usize>>,
+}
+
+impl<T> BellmanFord<T> {
+    /// Returns a shortest path from a source to `v`,
+    /// or `None` if `dist[v]` is not finite.
+    ///
+    /// 🕒 `O(n)`.
+    #[must_use]
+    pub fn path(&self, v: usize) -> Option<Vec<usize>> {
+        let Dist::Finite(_) = self.dist[v] else {
+            return None;
+        };
+        let mut path = vec![v];
+        let mut cur = v;
+        while let Some(p) = self.parent[cur] {
+            path.push(p);
+            cur = p;
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+/// Runs Bellman-Ford from every vertex in `sources` at once.
+///
+/// To find any negative cycle in the graph,
+/// pass every vertex as a source.
+///
+/// 🕒 `O(nm)`. The rounds stop early once nothing changes,
+/// so it is usually much faster without negative cycles.
+#[must_use]
+pub fn bellman_ford<T>(graph: &Graph<T>, sources: &[usize]) -> BellmanFord<T>
+where
+    T: Copy + Default + Ord + Add<Output = T>,
+{
+    let n = graph.vertex_count();
+    let mut dist = vec![None; n];
+    let mut parent = vec![None; n];
+    for &s in sources {
+        dist[s] = Some(T::default());
+    }
+
+    // After n-1 rounds, every finite distance is final, so
+    // a vertex relaxed in the n-th round has distance -inf.
+    let mut relaxed = vec![];
+    for round in 0..n {
+        let mut changed = false;
+        for from in 0..n {
+            let Some(d) = dist[from] else {
+                continue;
+            };
+            for (to, &cost) in graph.neighbors(from) {
+                let nd = d + cost;
+                if dist[to].iter().all(|&old| nd < old) {
+                    dist[to] = Some(nd);
+                    parent[to] = Some(from);
+                    changed = true;
+                    if round + 1 == n {
+                        relaxed.push(to);
+                    }
+                }
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+
+    let cycle = relaxed.first().map(|&x| {
+        // Walking back n times surely ends on a cycle of parents,
+        // which is negative.
+        let mut v = x;
+        for _ in 0..n {
+            v = parent[v].unwrap();
+        }
+        let mut cycle = vec![v];
+        let mut cur = parent[v].unwrap();
+        while cur != v {
+            cycle.push(cur);
+            cur = parent[cur].unwrap();
+        }
+        cycle.reverse();
+        cycle
+    });
+
+    let mut neg = vec![false; n];
+    let mut queue = VecDeque::new();
+    for v in relaxed {
+        if !neg[v] {
+            neg[v] = true;
+            queue.push_back(v);
+        }
+    }
+    while let Some(from) = queue.pop_front() {
+        for (to, _) in graph.neighbors(from) {
+            if !neg[to] {
+                neg[to] = true;
+                queue.push_back(to);
+            }
+        }
+    }
+
+    let dist = dist
+        .into_iter()
+        .zip(neg)
+        .map(|(d, neg)| match d {
+            _ if neg => Dist::NegInf,
+            Some(d) => Dist::Finite(d),
+            None => Dist::Unreachable,
+        })
+        .collect();
+    BellmanFord {
+        dist,
+        parent,
+        cycle,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// `walks[v]` is the minimum cost of a walk from `sources`
+    /// to `v` with at most `len` edges.
+    fn walks(graph: &Graph<i64>, sources: &[usize], len: usize) -> Vec<Option<i64>> {
+        let n = graph.vertex_count();
+        let mut dist = vec![None; n];
+        for &s in sources {
+            dist[s] = Some(0);
+        }
+        for _ in 0..len {
+            let mut next = dist.clone();
+            for (from, d) in dist.iter().enumerate() {
+                let Some(d) = d else {
+                    continue;
+                };
+                for (to, &cost) in graph.neighbors(from) {
+                    next[to] = Some(next[to].map_or(d + cost, |old: i64| old.min(d + cost)));
+                }
+            }
+            dist = next;
+        }
+        dist
+    }
+
+    fn naive(graph: &Graph<i64>, sources: &[usize]) -> Vec<Dist<i64>> {
+        let n = graph.vertex_count();
+        let on_cycle = (0..n)
+            .map(|c| {
+                graph
+                    .neighbors(c)
+                    .any(|(to, &cost)| matches!(walks(graph, &[to], n)[c], Some(d) if d + cost < 0))
+            })
+            .collect::<Vec<_>>();
+        let reach = walks(graph, sources, n);
+        let cycles = (0..n)
+            .filter(|&c| on_cycle[c] && reach[c].is_some())
+            .collect::<Vec<_>>();
+        let neg = walks(graph, &cycles, n);
+        (0..n)
+            .map(|v| match reach[v] {
+                _ if neg[v].is_some() => Dist::NegInf,
+                Some(d) => Dist::Finite(d),
+                None => Dist::Unreachable,
+            })
+            .collect()
+    }
+
+    fn do_test_bellman_ford(n: usize, edges: &[(usize, usize, i64)], sources: &[usize]) {
+        let mut graph = Graph::new(n);
+        for &(u, v, cost) in edges {
+            graph.direct(u, v, cost);
+        }
+        let bf = bellman_ford(&graph, sources);
+        let want = naive(&graph, sources);
+        assert_eq!(bf.dist, want);
+
+        for (v, &d) in want.iter().enumerate() {
+            let Some(path) = bf.path(v) else {
+                continue;
+            };
+            assert!(sources.contains(&path[0]));
+            let cost = path.windows(2).map(|w| min_cost(&graph, w[0], w[1])).sum();
+            assert_eq!(Dist::Finite(cost), d);
+        }
+
+        let has_neg = want.contains(&Dist::NegInf);
+        assert_eq!(bf.cycle.is_some(), has_neg);
+        if let Some(cycle) = bf.cycle {
+            let mut sorted = cycle.clone();
+            sorted.sort_unstable();
+            sorted.dedup();
+            assert_eq!(sorted.len(), cycle.len());
+            let cost = (0..cycle.len())
+                .map(|i| min_cost(&graph, cycle[i], cycle[(i + 1) % cycle.len()]))
+                .sum::<i64>();
+            assert!(cost < 0);
+            assert_eq!(want[cycle[0]], Dist::NegInf);
+        }
+    }
+
+    #[test]
+    fn test_bellman_ford() {
+        // Negative edges, but no negative cycle.
+        let dag = [
+            (0, 1, 4),
+            (0, 2, 2),
+            (2, 1, -3),
+            (1, 3, 2),
+            (3, 4, -1),
+            (2, 4, 5),
+            (4, 2, 3),
+        ];
+        // A negative cycle 2 -> 3 -> 4 -> 2, reaching 5 but not 6.
+        let cycle = [
+            (0, 1, 1),
+            (1, 2, 1),
+            (2, 3, -2),
+            (3, 4, 1),
+            (4, 2, 0),
+            (4, 5, 7),
+            (6, 0, -9),
+        ];
+        // A zero-cost cycle, and a negative self-loop on 4.
+        let mixed = [
+            (0, 1, -1),
+            (1, 0, 1),
+            (1, 2, 3),
+            (1, 2, -2),
+            (3, 4, 2),
+            (4, 4, -1),
+            (2, 0, 5),
+        ];
+        for edges in [&dag[..], &cycle, &mixed] {
+            for s in 0..7 {
+                do_test_bellman_ford(7, edges, &[s]);
+            }
+            do_test_bellman_ford(7, edges, &[]);
+            do_test_bellman_ford(7, edges, &[5, 0, 5]);
+            do_test_bellman_ford(7, edges, &[6, 3]);
+        }
+        do_test_bellman_ford(1, &[], &[0]);
+    }
+
+    fn min_cost(graph: &Graph<i64>, from: usize, to: usize) -> i64 {
+        graph
+            .neighbors(from)
+            .filter(|e| e.0 == to)
+            .map(|e| *e.1)
+            .min()
+            .unwrap()
+    }
+}
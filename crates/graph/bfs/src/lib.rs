@@ -1,6 +1,12 @@
 //! [Breadth-first search](https://en.wikipedia.org/wiki/Breadth-first_search).
 //!
 //! Uses [`Graph`].
+//!
+//! - [`bfs`]: distances from one vertex.
+//! - [`bfs_multi`], [`bfs_until`]: distances and parents from
+//!   several vertices, optionally stopping at a target.
+//! - [`bfs01`]: the same for edge weights in `{0, 1}`.
+//! - [`grid_bfs`]: distances on a character grid.
 
 extern crate edge_list;
 
@@ -19,9 +25,6 @@ use edge_list::Graph;
 ///
 /// 🕒 `O(V+E)`.
 ///
-/// TODO: support multiple starts.
-/// TODO: support path recovery.
-///
 /// # Example
 /// ```
 /// # extern crate edge_list;
@@ -34,21 +37,428 @@ use edge_list::Graph;
 /// let dist = bfs(&graph, 0);
 /// assert_eq!(dist, vec![Some(0), Some(1), None, Some(2)]);
 /// ```
+#[must_use]
 pub fn bfs<T>(graph: &Graph<T>, v: usize) -> Vec<Option<u32>> {
-    let mut dist = vec![None; graph.vertex_count()];
-    dist[v] = Some(0);
-    let mut queue = VecDeque::from([v]);
+    bfs_multi(graph, &[v]).dist
+}
+
+/// Result of a search from a set of sources.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BfsTree {
+    /// `dist[i]` is the distance from the nearest source to `i`,
+    /// or `None` if `i` is unreachable (or was not reached
+    /// before an early exit).
+    pub dist: Vec<Option<u32>>,
+    /// `parent[i]` is the previous vertex on a shortest path
+    /// to `i`, or `None` if `i` is a source or unreachable.
+    pub parent: Vec<Option<usize>>,
+}
+
+impl BfsTree {
+    /// Returns a shortest path from a source to `v`,
+    /// or `None` if `v` is unreachable.
+    ///
+    /// 🕒 `O(V)`.
+    #[must_use]
+    pub fn path(&self, v: usize) -> Option<Vec<usize>> {
+        self.dist[v]?;
+        let mut path = vec![v];
+        let mut cur = v;
+        while let Some(p) = self.parent[cur] {
+            path.push(p);
+            cur = p;
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+/// Runs BFS from every vertex in `sources` at once.
+/// Same as [`bfs`], but also tracks parents.
+///
+/// 🕒 `O(V+E)`.
+///
+/// # Example
+/// ```
+/// # extern crate edge_list;
+/// # use bfs::bfs_multi;
+/// # use edge_list::UnweightedGraph;
+/// let mut graph = UnweightedGraph::new(5);
+/// graph.bidirect(0, 1, ());
+/// graph.bidirect(1, 2, ());
+/// graph.bidirect(2, 3, ());
+/// let tree = bfs_multi(&graph, &[0, 3]);
+/// assert_eq!(tree.dist, vec![Some(0), Some(1), Some(1), Some(0), None]);
+/// assert_eq!(tree.path(2), Some(vec![3, 2]));
+/// ```
+#[must_use]
+pub fn bfs_multi<T>(graph: &Graph<T>, sources: &[usize]) -> BfsTree {
+    search(graph, sources, None)
+}
+
+/// Same as [`bfs_multi`], but stops as soon as the distance to
+/// `target` is known. Vertices farther than `target` may be left
+/// unreached, but `dist` and `path` are exact for `target`.
+///
+/// 🕒 `O(V+E)`.
+///
+/// # Example
+/// ```
+/// # extern crate edge_list;
+/// # use bfs::bfs_until;
+/// # use edge_list::UnweightedGraph;
+/// let mut graph = UnweightedGraph::new(4);
+/// graph.direct(0, 1, ());
+/// graph.direct(1, 2, ());
+/// graph.direct(2, 3, ());
+/// let tree = bfs_until(&graph, &[0], 1);
+/// assert_eq!(tree.path(1), Some(vec![0, 1]));
+/// assert_eq!(tree.dist[3], None);
+/// ```
+#[must_use]
+pub fn bfs_until<T>(graph: &Graph<T>, sources: &[usize], target: usize) -> BfsTree {
+    search(graph, sources, Some(target))
+}
+
+fn search<T>(graph: &Graph<T>, sources: &[usize], target: Option<usize>) -> BfsTree {
+    let n = graph.vertex_count();
+    let mut dist = vec![None; n];
+    let mut parent = vec![None; n];
+    let mut queue = VecDeque::new();
+    for &s in sources {
+        if dist[s].is_none() {
+            dist[s] = Some(0);
+            queue.push_back(s);
+        }
+    }
     while let Some(from) = queue.pop_front() {
+        if target == Some(from) {
+            break;
+        }
         let d = dist[from].unwrap();
         for (to, _) in graph.neighbors(from) {
-            if let Some(nd) = dist[to] {
-                if nd <= d + 1 {
+            if dist[to].is_none() {
+                dist[to] = Some(d + 1);
+                parent[to] = Some(from);
+                queue.push_back(to);
+            }
+        }
+    }
+    BfsTree { dist, parent }
+}
+
+/// Edge weight that is either 0 or 1.
+pub trait ZeroOne {
+    /// Returns whether the weight is 1.
+    fn is_one(&self) -> bool;
+}
+
+impl ZeroOne for bool {
+    fn is_one(&self) -> bool {
+        *self
+    }
+}
+
+impl ZeroOne for u8 {
+    /// ⚠️ Panics if the weight is not 0 or 1.
+    fn is_one(&self) -> bool {
+        assert!(*self <= 1, "weight {} is not 0 or 1", self);
+        *self == 1
+    }
+}
+
+/// 0-1 BFS from every vertex in `sources` at once.
+/// `dist[i]` is the minimum total weight of a path to `i`.
+///
+/// 🕒 `O(V+E)`.
+///
+/// ⚠️ Panics if a `u8` weight is not 0 or 1.
+///
+/// # Example
+/// ```
+/// # extern crate edge_list;
+/// # use bfs::bfs01;
+/// # use edge_list::Graph;
+/// let mut graph = Graph::new(3);
+/// graph.direct(0, 2, true);
+/// graph.direct(0, 1, false);
+/// graph.direct(1, 2, false);
+/// let tree = bfs01(&graph, &[0]);
+/// assert_eq!(tree.dist, vec![Some(0), Some(0), Some(0)]);
+/// assert_eq!(tree.path(2), Some(vec![0, 1, 2]));
+/// ```
+#[must_use]
+pub fn bfs01<T: ZeroOne>(graph: &Graph<T>, sources: &[usize]) -> BfsTree {
+    let n = graph.vertex_count();
+    let mut dist: Vec<Option<u32>> = vec![None; n];
+    let mut parent = vec![None; n];
+    let mut done = vec![false; n];
+    let mut deque = VecDeque::new();
+    for &s in sources {
+        dist[s] = Some(0);
+        deque.push_back(s);
+    }
+    while let Some(from) = deque.pop_front() {
+        if std::mem::replace(&mut done[from], true) {
+            continue;
+        }
+        let d = dist[from].unwrap();
+        for (to, w) in graph.neighbors(from) {
+            let one = w.is_one();
+            let nd = d + u32::from(one);
+            if dist[to].iter().any(|&old| old <= nd) {
+                continue;
+            }
+            dist[to] = Some(nd);
+            parent[to] = Some(from);
+            if one {
+                deque.push_back(to);
+            } else {
+                deque.push_front(to);
+            }
+        }
+    }
+    BfsTree { dist, parent }
+}
+
+/// Allowed moves in [`grid_bfs`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Moves {
+    /// Up, down, left, right.
+    Four,
+    /// [`Moves::Four`] and the diagonals.
+    Eight,
+}
+
+impl Moves {
+    const fn deltas(self) -> &'static [(isize, isize)] {
+        const EIGHT: [(isize, isize); 8] = [
+            (-1, 0),
+            (1, 0),
+            (0, -1),
+            (0, 1),
+            (-1, -1),
+            (-1, 1),
+            (1, -1),
+            (1, 1),
+        ];
+        match self {
+            Self::Four => EIGHT.split_at(4).0,
+            Self::Eight => &EIGHT,
+        }
+    }
+}
+
+/// BFS on a character grid from every cell in `sources` at once.
+/// Cells whose character is in `walls` cannot be entered.
+/// Rows may have different lengths.
+///
+/// Returns `dist`, where `dist[r][c]` is the number of moves
+/// to reach `(r, c)`, or `None` if it is unreachable.
+/// Sources are reached even if they are walls.
+///
+/// 🕒 `O(RC)`.
+///
+/// # Example
+/// ```
+/// # use bfs::{grid_bfs, Moves};
+/// let grid = ["..#", ".##", "..."];
+/// let dist = grid_bfs(&grid, &[(0, 0)], b"#", Moves::Four);
+/// assert_eq!(dist[2][2], Some(4));
+/// assert_eq!(dist[0][2], None);
+/// let dist = grid_bfs(&grid, &[(0, 0)], b"#", Moves::Eight);
+/// assert_eq!(dist[2][2], Some(3));
+/// ```
+///
+/// - [AtCoder ABC 007 C](https://atcoder.jp/contests/abc007/tasks/abc007_3)
+/// ```ignore
+/// let (r, c) = (oj.usize(), oj.usize());
+/// let (sy, sx) = (oj.usize() - 1, oj.usize() - 1);
+/// let (gy, gx) = (oj.usize() - 1, oj.usize() - 1);
+/// let grid = (0..r).map(|_| oj.string()).collect::<Vec<_>>();
+/// let dist = grid_bfs(&grid, &[(sy, sx)], b"#", Moves::Four);
+/// oj.write(dist[gy][gx].unwrap()).ln();
+/// ```
+#[must_use]
+pub fn grid_bfs<R: AsRef<[u8]>>(
+    grid: &[R],
+    sources: &[(usize, usize)],
+    walls: &[u8],
+    moves: Moves,
+) -> Vec<Vec<Option<u32>>> {
+    let mut dist: Vec<Vec<Option<u32>>> =
+        grid.iter().map(|r| vec![None; r.as_ref().len()]).collect();
+    let mut queue = VecDeque::new();
+    for &(r, c) in sources {
+        if dist[r][c].is_none() {
+            dist[r][c] = Some(0);
+            queue.push_back((r, c));
+        }
+    }
+    while let Some((r, c)) = queue.pop_front() {
+        let d = dist[r][c].unwrap();
+        for &(dr, dc) in moves.deltas() {
+            let (Some(nr), Some(nc)) = (r.checked_add_signed(dr), c.checked_add_signed(dc)) else {
+                continue;
+            };
+            let Some(&ch) = grid.get(nr).and_then(|row| row.as_ref().get(nc)) else {
+                continue;
+            };
+            if walls.contains(&ch) || dist[nr][nc].is_some() {
+                continue;
+            }
+            dist[nr][nc] = Some(d + 1);
+            queue.push_back((nr, nc));
+        }
+    }
+    dist
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Bellman-Ford distances with weights from `weight`.
+    fn naive<T>(
+        graph: &Graph<T>,
+        sources: &[usize],
+        weight: impl Fn(&T) -> u32,
+    ) -> Vec<Option<u32>> {
+        let mut dist = vec![None; graph.vertex_count()];
+        for &s in sources {
+            dist[s] = Some(0);
+        }
+        for _ in 0..graph.vertex_count() {
+            for v in 0..graph.vertex_count() {
+                let Some(d) = dist[v] else {
                     continue;
+                };
+                for (to, w) in graph.neighbors(v) {
+                    let nd = d + weight(w);
+                    if dist[to].iter().all(|&old| old > nd) {
+                        dist[to] = Some(nd);
+                    }
                 }
             }
-            dist[to] = Some(d + 1);
-            queue.push_back(to);
         }
+        dist
+    }
+
+    fn check_tree<T>(
+        graph: &Graph<T>,
+        tree: &BfsTree,
+        sources: &[usize],
+        weight: impl Fn(&T) -> u32,
+    ) {
+        for v in 0..graph.vertex_count() {
+            let Some(path) = tree.path(v) else {
+                continue;
+            };
+            assert!(sources.contains(&path[0]));
+            let cost = path
+                .windows(2)
+                .map(|w| {
+                    graph
+                        .neighbors(w[0])
+                        .filter(|e| e.0 == w[1])
+                        .map(|e| weight(e.1))
+                        .min()
+                        .unwrap()
+                })
+                .sum::<u32>();
+            assert_eq!(Some(cost), tree.dist[v]);
+        }
+    }
+
+    fn do_test_bfs(n: usize, edges: &[(usize, usize, u8)], sources: &[usize]) {
+        let mut graph = Graph::new(n);
+        for &(u, v, w) in edges {
+            graph.direct(u, v, w);
+        }
+
+        let tree = bfs_multi(&graph, sources);
+        assert_eq!(tree.dist, naive(&graph, sources, |_| 1));
+        check_tree(&graph, &tree, sources, |_| 1);
+        if let [s] = *sources {
+            assert_eq!(bfs(&graph, s), tree.dist);
+        }
+
+        for target in 0..n {
+            let until = bfs_until(&graph, sources, target);
+            assert_eq!(until.dist[target], tree.dist[target]);
+            check_tree(&graph, &until, sources, |_| 1);
+        }
+
+        let tree = bfs01(&graph, sources);
+        let weight = |w: &u8| u32::from(*w);
+        assert_eq!(tree.dist, naive(&graph, sources, weight));
+        check_tree(&graph, &tree, sources, weight);
+    }
+
+    #[test]
+    fn test_bfs() {
+        let edges = [
+            (0, 1, 1),
+            (0, 2, 1),
+            (1, 3, 0),
+            (2, 3, 1),
+            (3, 4, 1),
+            (4, 5, 0),
+            (5, 6, 0),
+            (2, 6, 1),
+            (6, 0, 1),
+            (7, 7, 1),
+            (7, 8, 0),
+            (8, 4, 1),
+            (1, 3, 1),
+        ];
+        do_test_bfs(10, &edges, &[0]);
+        do_test_bfs(10, &edges, &[4]);
+        do_test_bfs(10, &edges, &[9]);
+        do_test_bfs(10, &edges, &[0, 7]);
+        do_test_bfs(10, &edges, &[5, 5, 8]);
+        // The 0-edge path is longer, but cheaper.
+        let edges = [
+            (0, 4, 1),
+            (0, 1, 0),
+            (1, 2, 0),
+            (2, 3, 0),
+            (3, 4, 0),
+            (4, 5, 1),
+        ];
+        do_test_bfs(6, &edges, &[0]);
+        do_test_bfs(1, &[], &[0]);
+    }
+
+    #[test]
+    fn test_grid_bfs() {
+        let grid = vec![
+            "S.#.....".to_string(),
+            ".##.###.".to_string(),
+            "....#...".to_string(),
+            "#.#...#S".to_string(),
+        ];
+        let dist = grid_bfs(&grid, &[(0, 0), (3, 7)], b"#", Moves::Four);
+        let want = ["01#76543", "1##6###2", "2345#321", "#4#654#0"];
+        for (row, want) in dist.iter().zip(want) {
+            let got = row
+                .iter()
+                .map(|d| d.map_or('#', |d| char::from_digit(d, 10).unwrap()))
+                .collect::<String>();
+            assert_eq!(got, want);
+        }
+
+        let dist = grid_bfs(&grid, &[(0, 0)], b"#", Moves::Eight);
+        assert_eq!(dist[0][3], Some(5));
+        assert_eq!(dist[3][7], Some(8));
+        assert_eq!(dist[3][0], None);
+    }
+
+    #[test]
+    #[should_panic(expected = "weight 2 is not 0 or 1")]
+    fn test_bad_weight() {
+        let mut graph = Graph::new(2);
+        graph.direct(0, 1, 2u8);
+        let _ = bfs01(&graph, &[0]);
     }
-    dist
 }
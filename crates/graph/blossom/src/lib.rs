@@ -0,0 +1,890 @@
+//! [Edmonds' blossom algorithm](https://en.wikipedia.org/wiki/Blossom_algorithm),
+//! maximum matching on general (non-bipartite) graphs.
+//!
+//! Uses [`Graph`] as an undirected graph. Each edge may be added
+//! in one or both directions, and loops are ignored.
+//! For bipartite graphs, `hopcroft_karp` is faster.
+//!
+//! # Example
+//! ```
+//! # extern crate edge_list;
+//! # use blossom::{blossom, weighted_blossom};
+//! # use edge_list::{Graph, UnweightedGraph};
+//! // A triangle 0 - 1 - 2 - 0 with a tail 2 - 3.
+//! let mut graph = UnweightedGraph::new(4);
+//! for &(u, v) in &[(0, 1), (1, 2), (2, 0), (2, 3)] {
+//!     graph.bidirect(u, v, ());
+//! }
+//! let mate = blossom(&graph);
+//! assert_eq!(mate.iter().flatten().count(), 4); // 2 pairs
+//! assert_eq!(mate[3], Some(2));
+//!
+//! let mut graph = Graph::new(4);
+//! graph.direct(0, 1, 5);
+//! graph.direct(1, 2, 8);
+//! graph.direct(2, 3, 5);
+//! let (weight, mate) = weighted_blossom(&graph);
+//! assert_eq!(weight, 10);
+//! assert_eq!(mate, [Some(1), Some(0), Some(3), Some(2)]);
+//! ```
+//!
+//! - [LC Matching on General Graph](https://judge.yosupo.jp/problem/general_matching)
+//! ```ignore
+//! let (n, m) = (oj.usize(), oj.usize());
+//! let mut graph = UnweightedGraph::new(n);
+//! for _ in 0..m {
+//!     graph.direct(oj.usize(), oj.usize(), ());
+//! }
+//! let mate = blossom(&graph);
+//! oj.write(mate.iter().flatten().count() / 2).ln();
+//! for (u, &v) in mate.iter().enumerate() {
+//!     if let Some(v) = v.filter(|&v| u < v) {
+//!         oj.write(u).sp().write(v).ln();
+//!     }
+//! }
+//! ```
+//! - [LC Maximum Weighted Matching on General Graph](https://judge.yosupo.jp/problem/general_weighted_matching)
+//! ```ignore
+//! let (n, m) = (oj.usize(), oj.usize());
+//! let mut graph = Graph::new(n);
+//! for _ in 0..m {
+//!     graph.direct(oj.usize(), oj.usize(), oj.i64());
+//! }
+//! let (weight, mate) = weighted_blossom(&graph);
+//! let pairs = (mate.iter().enumerate())
+//!     .filter_map(|(u, &v)| v.filter(|&v| u < v).map(|v| (u, v)))
+//!     .collect::<Vec<_>>();
+//! oj.write(pairs.len()).sp().write(weight).ln();
+//! for (u, v) in pairs {
+//!     oj.write(u).sp().write(v).ln();
+//! }
+//! ```
+
+extern crate edge_list;
+
+use edge_list::Graph;
+use std::collections::VecDeque;
+
+const NOPE: usize = usize::MAX;
+
+/// The alternating forest of a search for an augmenting path.
+struct Forest {
+    /// The base of the (contracted) blossom of each vertex.
+    base: Vec<usize>,
+    /// The parent of each odd vertex.
+    parent: Vec<usize>,
+    /// Whether each vertex is even, i.e. in the queue.
+    even: Vec<bool>,
+    in_blossom: Vec<bool>,
+    mark: Vec<usize>,
+    stamp: usize,
+    queue: VecDeque<usize>,
+}
+
+impl Forest {
+    /// Returns the lowest common ancestor of the blossoms of the
+    /// even vertices `a` and `b`.
+    fn lca(&mut self, mate: &[usize], mut a: usize, mut b: usize) -> usize {
+        self.stamp += 1;
+        loop {
+            a = self.base[a];
+            self.mark[a] = self.stamp;
+            if mate[a] == NOPE {
+                break;
+            }
+            a = self.parent[mate[a]];
+        }
+        loop {
+            b = self.base[b];
+            if self.mark[b] == self.stamp {
+                return b;
+            }
+            b = self.parent[mate[b]];
+        }
+    }
+
+    /// Marks the blossoms on the path from `v` to the base `b`,
+    /// and redirects the parents to go around the new blossom.
+    fn mark_path(&mut self, mate: &[usize], mut v: usize, b: usize, mut child: usize) {
+        while self.base[v] != b {
+            self.in_blossom[self.base[v]] = true;
+            self.in_blossom[self.base[mate[v]]] = true;
+            self.parent[v] = child;
+            child = mate[v];
+            v = self.parent[mate[v]];
+        }
+    }
+
+    /// Returns the end of an augmenting path from `root`, if any.
+    fn find_path(&mut self, adj: &[Vec<usize>], mate: &[usize], root: usize) -> Option<usize> {
+        let n = adj.len();
+        for v in 0..n {
+            self.base[v] = v;
+        }
+        self.parent.fill(NOPE);
+        self.even.fill(false);
+        self.even[root] = true;
+        self.queue.clear();
+        self.queue.push_back(root);
+        while let Some(v) = self.queue.pop_front() {
+            for &to in &adj[v] {
+                if self.base[v] == self.base[to] || mate[v] == to {
+                    continue;
+                }
+                if to == root || mate[to] != NOPE && self.parent[mate[to]] != NOPE {
+                    // An odd cycle, contract it into a blossom.
+                    let b = self.lca(mate, v, to);
+                    self.in_blossom.fill(false);
+                    self.mark_path(mate, v, b, to);
+                    self.mark_path(mate, to, b, v);
+                    for u in 0..n {
+                        if self.in_blossom[self.base[u]] {
+                            self.base[u] = b;
+                            if !self.even[u] {
+                                self.even[u] = true;
+                                self.queue.push_back(u);
+                            }
+                        }
+                    }
+                } else if self.parent[to] == NOPE {
+                    self.parent[to] = v;
+                    if mate[to] == NOPE {
+                        return Some(to);
+                    }
+                    self.even[mate[to]] = true;
+                    self.queue.push_back(mate[to]);
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Returns a maximum cardinality matching of the undirected `graph`,
+/// as the vertex matched with each vertex, if any.
+///
+/// 🕒 `O(V^3)`.
+#[must_use]
+pub fn blossom<T>(graph: &Graph<T>) -> Vec<Option<usize>> {
+    let n = graph.vertex_count();
+    let mut adj = vec![vec![]; n];
+    for u in 0..n {
+        for (v, _) in graph.neighbors(u) {
+            if u != v {
+                adj[u].push(v);
+                adj[v].push(u);
+            }
+        }
+    }
+
+    let mut mate = vec![NOPE; n];
+    for u in 0..n {
+        if mate[u] == NOPE {
+            if let Some(&v) = adj[u].iter().find(|&&v| mate[v] == NOPE) {
+                mate[u] = v;
+                mate[v] = u;
+            }
+        }
+    }
+    let mut forest = Forest {
+        base: vec![0; n],
+        parent: vec![NOPE; n],
+        even: vec![false; n],
+        in_blossom: vec![false; n],
+        mark: vec![0; n],
+        stamp: 0,
+        queue: VecDeque::new(),
+    };
+    for root in 0..n {
+        if mate[root] != NOPE {
+            continue;
+        }
+        let mut v = forest.find_path(&adj, &mate, root).unwrap_or(NOPE);
+        while v != NOPE {
+            let p = forest.parent[v];
+            let next = mate[p];
+            mate[v] = p;
+            mate[p] = v;
+            v = next;
+        }
+    }
+    (mate.into_iter())
+        .map(|v| if v == NOPE { None } else { Some(v) })
+        .collect()
+}
+
+/// An original edge `u - v` of weight `w`, or no edge if `w == 0`.
+#[derive(Clone, Copy, Debug, Default)]
+struct Edge {
+    u: usize,
+    v: usize,
+    w: i64,
+}
+
+/// The label of an outer node at an even depth of the forest.
+const EVEN: i8 = 0;
+/// The label of an outer node at an odd depth of the forest.
+const ODD: i8 = 1;
+/// The label of an outer node not in the forest.
+const UNLABELED: i8 = -1;
+
+/// The state of the weighted blossom algorithm with vertices `1..=n`,
+/// blossoms `n + 1..=last` and `0` as "none", following the
+/// primal-dual method with doubled weights.
+///
+/// A "node" is a vertex or a blossom.
+struct Weighted {
+    n: usize,
+    /// The largest node index in use.
+    last: usize,
+    /// `best_edge[x * slots + y]` is the best original edge
+    /// between the nodes `x` and `y`.
+    best_edge: Vec<Edge>,
+    /// The number of node indices, `2n + 1`.
+    slots: usize,
+    /// The dual variable of each node.
+    dual: Vec<i64>,
+    /// The vertex each node is matched to, through its base.
+    mate: Vec<usize>,
+    /// For each outer node, the even vertex with the
+    /// tightest edge to it, or `0` if there is none.
+    tightest: Vec<usize>,
+    /// The outermost blossom containing each node.
+    outer: Vec<usize>,
+    /// For each odd outer node, the vertex it was reached from.
+    parent: Vec<usize>,
+    /// `child_containing[b * (n + 1) + x]` is the child of
+    /// the blossom `b` containing the vertex `x`.
+    child_containing: Vec<usize>,
+    /// The label of each outer node.
+    label: Vec<i8>,
+    visited: Vec<usize>,
+    stamp: usize,
+    /// The children of each blossom as an odd cycle,
+    /// starting from the one containing the base.
+    children: Vec<Vec<usize>>,
+    /// The even vertices to scan.
+    queue: VecDeque<usize>,
+}
+
+impl Weighted {
+    fn edge(&self, x: usize, y: usize) -> Edge {
+        self.best_edge[x * self.slots + y]
+    }
+
+    /// Returns the doubled slack of `e` in the dual.
+    fn slack(&self, e: Edge) -> i64 {
+        self.dual[e.u] + self.dual[e.v] - e.w * 2
+    }
+
+    /// Records the even vertex `u` as the tightest to `x` if it is.
+    fn update_tightest(&mut self, u: usize, x: usize) {
+        let t = self.tightest[x];
+        if t == 0 || self.slack(self.edge(u, x)) < self.slack(self.edge(t, x)) {
+            self.tightest[x] = u;
+        }
+    }
+
+    /// Recomputes the tightest even vertex to `x` from scratch.
+    fn reset_tightest(&mut self, x: usize) {
+        self.tightest[x] = 0;
+        for u in 1..=self.n {
+            if self.edge(u, x).w > 0 && self.outer[u] != x && self.label[self.outer[u]] == EVEN {
+                self.update_tightest(u, x);
+            }
+        }
+    }
+
+    /// Queues all vertices inside the node `x`.
+    fn push_vertices(&mut self, x: usize) {
+        if x <= self.n {
+            self.queue.push_back(x);
+        } else {
+            for i in 0..self.children[x].len() {
+                self.push_vertices(self.children[x][i]);
+            }
+        }
+    }
+
+    /// Sets the outermost blossom of `x` and everything inside to `b`.
+    fn set_outer(&mut self, x: usize, b: usize) {
+        self.outer[x] = b;
+        if x > self.n {
+            for i in 0..self.children[x].len() {
+                self.set_outer(self.children[x][i], b);
+            }
+        }
+    }
+
+    /// Returns the position of `child` in the blossom `b`,
+    /// reversing the cycle so that the position is even.
+    fn child_position(&mut self, b: usize, child: usize) -> usize {
+        let children = &mut self.children[b];
+        let pos = children.iter().position(|&x| x == child).unwrap();
+        if pos % 2 == 1 {
+            children[1..].reverse();
+            children.len() - pos
+        } else {
+            pos
+        }
+    }
+
+    /// Matches the node `x` through its edge to `y`, rematching
+    /// the children of `x` so that the new base is its endpoint.
+    fn set_mate(&mut self, x: usize, y: usize) {
+        let e = self.edge(x, y);
+        self.mate[x] = e.v;
+        if x <= self.n {
+            return;
+        }
+        let child = self.child_containing[x * (self.n + 1) + e.u];
+        let pos = self.child_position(x, child);
+        for i in 0..pos {
+            self.set_mate(self.children[x][i], self.children[x][i ^ 1]);
+        }
+        self.set_mate(child, y);
+        self.children[x].rotate_left(pos);
+    }
+
+    /// Flips the matching along the tree path from the
+    /// outer node `x`, which gets matched to `y`.
+    fn augment(&mut self, mut x: usize, mut y: usize) {
+        loop {
+            let odd = self.outer[self.mate[x]];
+            self.set_mate(x, y);
+            if odd == 0 {
+                return;
+            }
+            let next = self.outer[self.parent[odd]];
+            self.set_mate(odd, next);
+            x = next;
+            y = odd;
+        }
+    }
+
+    /// Returns the lowest common ancestor of the outer nodes
+    /// `x` and `y`, or `0` if they are in different trees.
+    fn lca(&mut self, mut x: usize, mut y: usize) -> usize {
+        self.stamp += 1;
+        while x != 0 || y != 0 {
+            if x != 0 {
+                if self.visited[x] == self.stamp {
+                    return x;
+                }
+                self.visited[x] = self.stamp;
+                x = self.outer[self.mate[x]];
+                if x != 0 {
+                    x = self.outer[self.parent[x]];
+                }
+            }
+            std::mem::swap(&mut x, &mut y);
+        }
+        0
+    }
+
+    /// Pushes the path from the outer blossom `x` up to `lca`,
+    /// as pairs of an even blossom and its matched odd blossom.
+    fn push_path(&mut self, children: &mut Vec<usize>, mut x: usize, lca: usize) {
+        while x != lca {
+            let y = self.outer[self.mate[x]];
+            children.push(x);
+            children.push(y);
+            self.push_vertices(y);
+            x = self.outer[self.parent[y]];
+        }
+    }
+
+    /// Contracts the cycle through the even outer nodes `x` and `y`
+    /// and their `lca` into a new even blossom.
+    fn add_blossom(&mut self, x: usize, lca: usize, y: usize) {
+        let b = (self.n + 1..=self.last)
+            .find(|&b| self.outer[b] == 0)
+            .unwrap_or(self.last + 1);
+        self.last = self.last.max(b);
+        self.dual[b] = 0;
+        self.label[b] = EVEN;
+        self.mate[b] = self.mate[lca];
+        let mut children = vec![lca];
+        self.push_path(&mut children, x, lca);
+        children[1..].reverse();
+        self.push_path(&mut children, y, lca);
+        self.children[b] = children;
+        self.set_outer(b, b);
+
+        let (slots, stride) = (self.slots, self.n + 1);
+        for z in 1..=self.last {
+            self.best_edge[b * slots + z].w = 0;
+            self.best_edge[z * slots + b].w = 0;
+        }
+        self.child_containing[b * stride..(b + 1) * stride].fill(0);
+        for child in self.children[b].clone() {
+            for z in 1..=self.last {
+                let e = self.edge(child, z);
+                if self.edge(b, z).w == 0 || self.slack(e) < self.slack(self.edge(b, z)) {
+                    self.best_edge[b * slots + z] = e;
+                    self.best_edge[z * slots + b] = self.edge(z, child);
+                }
+            }
+            for v in 1..=self.n {
+                if self.child_containing[child * stride + v] != 0 {
+                    self.child_containing[b * stride + v] = child;
+                }
+            }
+        }
+        self.reset_tightest(b);
+    }
+
+    /// Expands the odd outer blossom `b` of zero dual, keeping
+    /// the children on the tree path labeled.
+    fn expand_blossom(&mut self, b: usize) {
+        for i in 0..self.children[b].len() {
+            let x = self.children[b][i];
+            self.set_outer(x, x);
+        }
+        let entry = self.child_containing[b * (self.n + 1) + self.edge(b, self.parent[b]).u];
+        let pos = self.child_position(b, entry);
+        for i in (0..pos).step_by(2) {
+            let (odd, even) = (self.children[b][i], self.children[b][i + 1]);
+            self.parent[odd] = self.edge(even, odd).u;
+            self.label[odd] = ODD;
+            self.label[even] = EVEN;
+            self.tightest[odd] = 0;
+            self.reset_tightest(even);
+            self.push_vertices(even);
+        }
+        self.label[entry] = ODD;
+        self.parent[entry] = self.parent[b];
+        for i in pos + 1..self.children[b].len() {
+            let x = self.children[b][i];
+            self.label[x] = UNLABELED;
+            self.reset_tightest(x);
+        }
+        self.outer[b] = 0;
+    }
+
+    /// Handles a tight edge, returning whether it augmented the matching.
+    fn on_tight_edge(&mut self, e: Edge) -> bool {
+        let (x, y) = (self.outer[e.u], self.outer[e.v]);
+        if self.label[y] == UNLABELED {
+            self.parent[y] = e.u;
+            self.label[y] = ODD;
+            let z = self.outer[self.mate[y]];
+            self.tightest[y] = 0;
+            self.tightest[z] = 0;
+            self.label[z] = EVEN;
+            self.push_vertices(z);
+        } else if self.label[y] == EVEN {
+            let lca = self.lca(x, y);
+            if lca == 0 {
+                self.augment(x, y);
+                self.augment(y, x);
+                return true;
+            }
+            self.add_blossom(x, lca, y);
+        }
+        false
+    }
+
+    /// Grows the forest from the unmatched nodes, adjusting the duals
+    /// when stuck. Returns whether an augmenting path was found.
+    fn search(&mut self) -> bool {
+        let (n, last) = (self.n, self.last);
+        self.label[1..=last].fill(UNLABELED);
+        self.tightest[1..=last].fill(0);
+        self.queue.clear();
+        for x in 1..=last {
+            if self.outer[x] == x && self.mate[x] == 0 {
+                self.parent[x] = 0;
+                self.label[x] = EVEN;
+                self.push_vertices(x);
+            }
+        }
+        if self.queue.is_empty() {
+            return false;
+        }
+        loop {
+            while let Some(u) = self.queue.pop_front() {
+                if self.label[self.outer[u]] == ODD {
+                    continue;
+                }
+                for v in 1..=n {
+                    let e = self.edge(u, v);
+                    if e.w > 0 && self.outer[u] != self.outer[v] {
+                        if self.slack(e) != 0 {
+                            self.update_tightest(u, self.outer[v]);
+                        } else if self.on_tight_edge(e) {
+                            return true;
+                        }
+                    }
+                }
+            }
+
+            // the largest dual change that keeps every slack nonnegative
+            let last = self.last;
+            let mut step = i64::MAX;
+            for b in n + 1..=last {
+                if self.outer[b] == b && self.label[b] == ODD {
+                    step = step.min(self.dual[b] / 2);
+                }
+            }
+            for x in 1..=last {
+                let t = self.tightest[x];
+                if self.outer[x] == x && t != 0 {
+                    let slack = self.slack(self.edge(t, x));
+                    match self.label[x] {
+                        UNLABELED => step = step.min(slack),
+                        EVEN => step = step.min(slack / 2),
+                        _ => {}
+                    }
+                }
+            }
+            // The dual of an even vertex hits zero, so it stays unmatched.
+            if (1..=n).any(|u| self.label[self.outer[u]] == EVEN && self.dual[u] <= step) {
+                return false;
+            }
+            for u in 1..=n {
+                match self.label[self.outer[u]] {
+                    EVEN => self.dual[u] -= step,
+                    ODD => self.dual[u] += step,
+                    _ => {}
+                }
+            }
+            for b in n + 1..=last {
+                if self.outer[b] == b {
+                    match self.label[b] {
+                        EVEN => self.dual[b] += step * 2,
+                        ODD => self.dual[b] -= step * 2,
+                        _ => {}
+                    }
+                }
+            }
+
+            self.queue.clear();
+            for x in 1..=last {
+                let t = self.tightest[x];
+                if self.outer[x] == x && t != 0 && self.outer[t] != x {
+                    let e = self.edge(t, x);
+                    if self.slack(e) == 0 && self.on_tight_edge(e) {
+                        return true;
+                    }
+                }
+            }
+            for b in n + 1..=last {
+                if self.outer[b] == b && self.label[b] == ODD && self.dual[b] == 0 {
+                    self.expand_blossom(b);
+                }
+            }
+        }
+    }
+}
+
+/// Returns a maximum weight matching of the undirected `graph`,
+/// as `(weight, mate)`, where `mate` is the vertex matched with
+/// each vertex, if any. The matching is not necessarily of
+/// maximum cardinality.
+///
+/// Edges of non-positive weight are ignored, and of parallel edges,
+/// only the heaviest one is used.
+///
+/// 🕒 `O(V^3)`, using `O(V^2)` memory.
+#[must_use]
+pub fn weighted_blossom(graph: &Graph<i64>) -> (i64, Vec<Option<usize>>) {
+    let n = graph.vertex_count();
+    let slots = 2 * n + 1;
+    let mut best_edge = vec![Edge::default(); slots * slots];
+    for u in 1..=n {
+        for v in 1..=n {
+            best_edge[u * slots + v] = Edge { u, v, w: 0 };
+        }
+    }
+    for u in 0..n {
+        for (v, &w) in graph.neighbors(u) {
+            let (a, b) = (u + 1, v + 1);
+            if a != b && w > best_edge[a * slots + b].w {
+                best_edge[a * slots + b].w = w;
+                best_edge[b * slots + a].w = w;
+            }
+        }
+    }
+    let w_max = best_edge.iter().map(|e| e.w).max().unwrap_or(0);
+    let mut child_containing = vec![0; slots * (n + 1)];
+    for u in 1..=n {
+        child_containing[u * (n + 1) + u] = u;
+    }
+    let mut dual = vec![0; slots];
+    dual[1..=n].fill(w_max);
+
+    let mut state = Weighted {
+        n,
+        last: n,
+        best_edge,
+        slots,
+        dual,
+        mate: vec![0; slots],
+        tightest: vec![0; slots],
+        outer: (0..slots).map(|x| if x <= n { x } else { 0 }).collect(),
+        parent: vec![0; slots],
+        child_containing,
+        label: vec![UNLABELED; slots],
+        visited: vec![0; slots],
+        stamp: 0,
+        children: vec![vec![]; slots],
+        queue: VecDeque::new(),
+    };
+    while state.search() {}
+
+    let mut weight = 0;
+    let mut mate = vec![None; n];
+    for u in 1..=n {
+        let v = state.mate[u];
+        if v != 0 {
+            mate[u - 1] = Some(v - 1);
+            if v < u {
+                weight += state.edge(u, v).w;
+            }
+        }
+    }
+    (weight, mate)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use edge_list::UnweightedGraph;
+    use std::convert::TryFrom;
+
+    /// Returns the heaviest weight between each pair of vertices,
+    /// or `0` if there is no edge of positive weight.
+    fn matrix(graph: &Graph<i64>) -> Vec<Vec<i64>> {
+        let n = graph.vertex_count();
+        let mut w = vec![vec![0; n]; n];
+        let edges = (0..n).flat_map(|u| graph.neighbors(u).map(move |(v, &x)| (u, v, x)));
+        for (u, v, weight) in edges {
+            if u != v && weight > w[u][v] {
+                w[u][v] = weight;
+                w[v][u] = weight;
+            }
+        }
+        w
+    }
+
+    /// Returns the maximum total weight of a matching by DP
+    /// over the subsets of vertices, where `w[u][v] == 0`
+    /// means no edge.
+    fn naive(w: &[Vec<i64>]) -> i64 {
+        let n = w.len();
+        let mut dp = vec![0; 1 << n];
+        for set in 1..1_usize << n {
+            let u = set.trailing_zeros() as usize;
+            let rest = set & !(1 << u);
+            dp[set] = dp[rest];
+            for v in (0..n).filter(|&v| rest >> v & 1 == 1 && w[u][v] > 0) {
+                dp[set] = dp[set].max(dp[rest & !(1 << v)] + w[u][v]);
+            }
+        }
+        dp[(1 << n) - 1]
+    }
+
+    /// Checks that `mate` is a matching using the edges of `w`,
+    /// and returns its total weight.
+    fn weight_of(w: &[Vec<i64>], mate: &[Option<usize>]) -> i64 {
+        let mut total = 0;
+        for (u, &v) in mate.iter().enumerate() {
+            if let Some(v) = v {
+                assert_eq!(mate[v], Some(u));
+                assert!(w[u][v] > 0);
+                if u < v {
+                    total += w[u][v];
+                }
+            }
+        }
+        total
+    }
+
+    fn do_test_blossom(n: usize, edges: &[(usize, usize)]) {
+        let mut graph = UnweightedGraph::new(n);
+        let mut w = vec![vec![0; n]; n];
+        for &(u, v) in edges {
+            graph.direct(u, v, ());
+            if u != v {
+                w[u][v] = 1;
+                w[v][u] = 1;
+            }
+        }
+        let mate = blossom(&graph);
+        assert_eq!(weight_of(&w, &mate), naive(&w));
+    }
+
+    #[test]
+    fn test_blossom() {
+        // The Petersen graph, which has a perfect matching
+        // but many odd cycles.
+        let petersen = [
+            (0, 1),
+            (1, 2),
+            (2, 3),
+            (3, 4),
+            (4, 0),
+            (0, 5),
+            (1, 6),
+            (2, 7),
+            (3, 8),
+            (4, 9),
+            (5, 7),
+            (7, 9),
+            (9, 6),
+            (6, 8),
+            (8, 5),
+        ];
+        do_test_blossom(10, &petersen);
+        // A flower: the stem 0-1 and the blossom 1-2-3-4-5,
+        // with 6 and 7 reachable only through the blossom.
+        let edges = [
+            (0, 1),
+            (1, 2),
+            (2, 3),
+            (3, 4),
+            (4, 5),
+            (5, 1),
+            (3, 6),
+            (5, 7),
+        ];
+        do_test_blossom(8, &edges);
+        // A blossom inside a blossom, with edges in both
+        // directions, a loop, and parallel edges.
+        let edges = [
+            (0, 1),
+            (1, 2),
+            (2, 0),
+            (2, 3),
+            (3, 4),
+            (4, 0),
+            (4, 5),
+            (5, 6),
+            (6, 4),
+            (6, 6),
+            (7, 5),
+            (7, 5),
+            (8, 3),
+        ];
+        do_test_blossom(9, &edges);
+        do_test_blossom(0, &[]);
+        do_test_blossom(3, &[(1, 1)]);
+    }
+
+    /// Checks every graph on 5 vertices.
+    #[test]
+    fn test_blossom_all() {
+        let pairs = (0..5)
+            .flat_map(|u| (0..u).map(move |v| (u, v)))
+            .collect::<Vec<_>>();
+        for mask in 0..1 << pairs.len() {
+            let edges = (0..pairs.len())
+                .filter(|&i| mask >> i & 1 == 1)
+                .map(|i| pairs[i])
+                .collect::<Vec<_>>();
+            do_test_blossom(5, &edges);
+        }
+    }
+
+    fn do_test_weighted_blossom(n: usize, edges: &[(usize, usize, i64)]) {
+        let mut graph = Graph::new(n);
+        for &(u, v, weight) in edges {
+            graph.direct(u, v, weight);
+        }
+        let w = matrix(&graph);
+        let (weight, mate) = weighted_blossom(&graph);
+        assert_eq!(weight_of(&w, &mate), weight);
+        assert_eq!(weight, naive(&w));
+    }
+
+    #[test]
+    fn test_weighted_blossom() {
+        // The heavy edge 1-2 is on an odd cycle, and taking it
+        // is worse than the two edges around it.
+        let edges = [(0, 1, 5), (1, 2, 8), (2, 3, 5), (0, 2, 3), (3, 1, 1)];
+        do_test_weighted_blossom(4, &edges);
+        // A triangle and a 4-cycle sharing vertex 0,
+        // joined again by the path 5-6-7-2.
+        let edges = [
+            (0, 1, 9),
+            (0, 2, 8),
+            (1, 2, 10),
+            (0, 3, 5),
+            (3, 4, 4),
+            (0, 5, 3),
+            (4, 5, 7),
+            (5, 6, 2),
+            (6, 7, 6),
+            (2, 7, 1),
+        ];
+        do_test_weighted_blossom(8, &edges);
+        // Zero and negative weights are never used,
+        // and only the heaviest parallel edge counts.
+        let edges = [
+            (0, 1, 0),
+            (1, 2, -5),
+            (2, 3, 1),
+            (2, 3, 4),
+            (3, 2, 2),
+            (3, 3, 9),
+        ];
+        do_test_weighted_blossom(5, &edges);
+        let big = 1_000_000_000;
+        let edges = [(0, 1, big), (1, 2, big + 1), (2, 0, big), (2, 3, big - 1)];
+        do_test_weighted_blossom(4, &edges);
+        do_test_weighted_blossom(0, &[]);
+    }
+
+    /// Checks every graph on 5 vertices, with weights depending
+    /// on the graph so that ties and non-positive weights occur.
+    #[test]
+    fn test_weighted_blossom_all() {
+        let pairs = (0..5)
+            .flat_map(|u| (0..u).map(move |v| (u, v)))
+            .collect::<Vec<_>>();
+        for mask in 0..1 << pairs.len() {
+            let edges = (0..pairs.len())
+                .filter(|&i| mask >> i & 1 == 1)
+                .map(|i| {
+                    (
+                        pairs[i].0,
+                        pairs[i].1,
+                        i64::try_from((i * 7 + mask) % 11).unwrap() - 2,
+                    )
+                })
+                .collect::<Vec<_>>();
+            do_test_weighted_blossom(5, &edges);
+        }
+    }
+
+    #[test]
+    fn test_large() {
+        let n = 500;
+        let mut graph = UnweightedGraph::new(n);
+        // Odd cycles of length 5 sharing vertices, plus scattered edges.
+        for u in 0..n {
+            graph.bidirect(u, (u + 5) % n, ());
+        }
+        for i in 0..n * 20 {
+            // multiplicative hashing scatters the other ends
+            let h = u32::try_from(i).unwrap().wrapping_mul(0x9e37_79b9) >> 8;
+            graph.direct(i % n, h as usize % n, ());
+        }
+        let mate = blossom(&graph);
+        assert_eq!(mate.iter().flatten().count(), n);
+
+        let n = 200;
+        let mut graph = Graph::new(n);
+        for u in 0..n {
+            for v in 0..u {
+                let h = u32::try_from(u * n + v).unwrap().wrapping_mul(0x9e37_79b9) >> 8;
+                let weight = i64::from(h % 1_000_000) + 1;
+                graph.direct(u, v, weight);
+            }
+        }
+        let (weight, mate) = weighted_blossom(&graph);
+        assert_eq!(weight_of(&matrix(&graph), &mate), weight);
+        assert_eq!(mate.iter().flatten().count(), n);
+    }
+}
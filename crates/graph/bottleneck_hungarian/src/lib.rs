@@ -1,25 +1,107 @@
-//! Under construction.
+//! Bottleneck assignment with the
+//! [Hungarian algorithm](https://en.wikipedia.org/wiki/Hungarian_algorithm).
+//!
+//! Assigns each row of an `n` by `m` matrix (`n <= m`) to a distinct
+//! column, minimizing the maximum cost (or maximizing the minimum),
+//! adding one row at a time. Costs may be negative, and cells
+//! may be forbidden with `None`.
+//!
+//! # Example
+//! ```
+//! # use bottleneck_hungarian::{BottleneckHungarian, Infeasible};
+//! let hungarian = BottleneckHungarian::solve(vec![vec![4, 1, 3], vec![2, 0, 5]]);
+//! assert_eq!(hungarian.cost(), 2);
+//! assert_eq!(hungarian.permutation(), [1, 0]);
+//! assert_eq!(hungarian.inverse_permutation(), [1, 0, usize::MAX]);
+//!
+//! let hungarian = BottleneckHungarian::solve_max(vec![vec![4, 1, 3], vec![2, 0, -5]]);
+//! assert_eq!(hungarian.cost(), 2);
+//!
+//! let mut hungarian = BottleneckHungarian::new(false);
+//! assert_eq!(hungarian.try_add_row(vec![Some(1), None]), Ok(()));
+//! assert_eq!(hungarian.try_add_row(vec![Some(-3), None]), Err(Infeasible));
+//! assert_eq!(hungarian.height(), 1); // the row is discarded
+//! ```
 
+/// The error when a row cannot be assigned
+/// without using a forbidden cell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Infeasible;
+
+/// The bottleneck assignment of the rows added so far.
+///
+/// In the method descriptions, let `n` be the number of rows,
+/// and `m` be the number of columns.
 #[derive(Debug, Clone, Default)]
 pub struct BottleneckHungarian {
-    mat: Vec<Vec<i64>>,
+    /// The costs, negated when maximizing.
+    mat: Vec<Vec<Option<i64>>>,
     perm: Vec<usize>,
     inv_perm: Vec<usize>,
+    maximize: bool,
 }
 
 impl BottleneckHungarian {
+    /// Returns a solver without rows, which minimizes the maximum
+    /// cost, or maximizes the minimum cost if `maximize`.
+    #[must_use]
+    pub fn new(maximize: bool) -> Self {
+        Self {
+            maximize,
+            ..Self::default()
+        }
+    }
+
+    /// Returns the assignment of `mat` minimizing the maximum cost.
+    ///
+    /// 🕒 `O(n^2 m)`.
+    ///
+    /// ⚠️ Panics if `n > m` or `mat` is not rectangular.
+    #[must_use]
     pub fn solve(mat: Vec<Vec<i64>>) -> Self {
-        let mut solver = Self::default();
+        let mut solver = Self::new(false);
+        for row in mat {
+            solver.add_row(row);
+        }
+        solver
+    }
+
+    /// Returns the assignment of `mat` maximizing the minimum cost.
+    ///
+    /// 🕒 `O(n^2 m)`.
+    ///
+    /// ⚠️ Panics if `n > m` or `mat` is not rectangular.
+    #[must_use]
+    pub fn solve_max(mat: Vec<Vec<i64>>) -> Self {
+        let mut solver = Self::new(true);
         for row in mat {
             solver.add_row(row);
         }
         solver
     }
 
-    pub fn height(&self) -> usize {
+    /// Returns the optimal assignment of `mat` avoiding the `None`
+    /// cells, or `Err(Infeasible)` if there is none.
+    ///
+    /// 🕒 `O(n^2 m)`.
+    ///
+    /// ⚠️ Panics if `n > m` or `mat` is not rectangular.
+    pub fn try_solve(mat: Vec<Vec<Option<i64>>>, maximize: bool) -> Result<Self, Infeasible> {
+        let mut solver = Self::new(maximize);
+        for row in mat {
+            solver.try_add_row(row)?;
+        }
+        Ok(solver)
+    }
+
+    /// Returns `n`.
+    #[must_use]
+    pub const fn height(&self) -> usize {
         self.mat.len()
     }
 
+    /// Returns `m`, or `0` if there are no rows yet.
+    #[must_use]
     pub fn width(&self) -> usize {
         if self.mat.is_empty() {
             return 0;
@@ -27,20 +109,56 @@ impl BottleneckHungarian {
         self.mat[0].len()
     }
 
+    /// Returns the maximum cost of the assignment,
+    /// or the minimum cost when maximizing.
+    ///
+    /// ⚠️ Panics if there are no rows.
+    #[must_use]
     pub fn cost(&self) -> i64 {
-        self.mat
-            .iter()
-            .zip(self.perm.iter())
-            .map(|(row, j)| row[*j])
+        let cost = (self.mat.iter().zip(&self.perm))
+            .map(|(row, &j)| row[j].unwrap())
             .max()
-            .unwrap()
+            .unwrap();
+        if self.maximize {
+            -cost
+        } else {
+            cost
+        }
     }
 
+    /// Returns the column assigned to each row.
+    #[must_use]
     pub fn permutation(&self) -> &[usize] {
         &self.perm
     }
 
+    /// Returns the row assigned to each column,
+    /// or `usize::MAX` if there is none.
+    #[must_use]
+    pub fn inverse_permutation(&self) -> &[usize] {
+        &self.inv_perm[..self.width()]
+    }
+
+    /// Adds a row and updates the assignment.
+    ///
+    /// 🕒 `O(nm)`.
+    ///
+    /// ⚠️ Panics if there are already `m` rows,
+    /// or the length of `row` is not `m`.
     pub fn add_row(&mut self, row: Vec<i64>) {
+        let result = self.try_add_row(row.into_iter().map(Some).collect());
+        debug_assert_eq!(result, Ok(()));
+    }
+
+    /// Adds a row, where the `None` cells are forbidden, and updates
+    /// the assignment. If the rows cannot be assigned together,
+    /// the row is discarded and `Err(Infeasible)` is returned.
+    ///
+    /// 🕒 `O(nm)`.
+    ///
+    /// ⚠️ Panics if there are already `m` rows,
+    /// or the length of `row` is not `m`.
+    pub fn try_add_row(&mut self, mut row: Vec<Option<i64>>) -> Result<(), Infeasible> {
         let (n, mut m) = (self.height(), self.width());
         if n == 0 {
             // initialization
@@ -48,9 +166,14 @@ impl BottleneckHungarian {
             self.inv_perm = vec![usize::MAX; m + 1];
         } else {
             // dimension check
-            assert!(n <= m, "too many rows");
+            assert!(n < m, "too many rows");
             assert_eq!(m, row.len(), "matrix must be rectangular");
         }
+        if self.maximize {
+            for x in row.iter_mut().flatten() {
+                *x = -*x;
+            }
+        }
         self.mat.push(row);
         self.perm.push(0);
 
@@ -68,22 +191,30 @@ impl BottleneckHungarian {
 
             used[j_cur] = true;
             // find a new reachable one
-            j_cur = used
+            let (min, j_next) = used
                 .iter()
                 .zip(row)
                 .zip(&mut minv)
                 .enumerate()
                 .filter(|(_, ((used, _), _))| !**used)
                 .map(|(j, ((_, rj), mvj))| {
-                    if *rj < *mvj {
-                        *mvj = *rj;
-                        augment[j] = j_cur;
+                    if let Some(rj) = *rj {
+                        if rj < *mvj {
+                            *mvj = rj;
+                            augment[j] = j_cur;
+                        }
                     }
                     (*mvj, j)
                 })
                 .min()
-                .unwrap()
-                .1;
+                .unwrap();
+            if min == i64::MAX {
+                // No free column is reachable.
+                self.mat.pop();
+                self.perm.pop();
+                return Err(Infeasible);
+            }
+            j_cur = j_next;
         }
 
         // apply augmenting paths
@@ -93,5 +224,146 @@ impl BottleneckHungarian {
             self.perm[self.inv_perm[j_cur]] = j_cur;
             j_cur = j_prev;
         }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Returns the optimal bottleneck over all assignments of the
+    /// rows `i..` to the columns not in `used`, if any.
+    fn naive(mat: &[Vec<Option<i64>>], i: usize, used: &mut [bool], maximize: bool) -> Option<i64> {
+        if i == mat.len() {
+            return Some(if maximize { i64::MAX } else { i64::MIN });
+        }
+        let mut best = None;
+        for j in 0..used.len() {
+            if let (false, Some(x)) = (used[j], mat[i][j]) {
+                used[j] = true;
+                if let Some(rest) = naive(mat, i + 1, used, maximize) {
+                    let cost = if maximize { x.min(rest) } else { x.max(rest) };
+                    best =
+                        Some(best.map_or(
+                            cost,
+                            |b: i64| {
+                                if maximize {
+                                    b.max(cost)
+                                } else {
+                                    b.min(cost)
+                                }
+                            },
+                        ));
+                }
+                used[j] = false;
+            }
+        }
+        best
+    }
+
+    enum Query {
+        /// Adds a row, expecting the new bottleneck or `Infeasible`.
+        Add(Vec<Option<i64>>, Result<i64, Infeasible>),
+        /// Expects the permutation, and its inverse.
+        Perm(Vec<usize>, Vec<usize>),
+    }
+
+    fn do_test_bottleneck(maximize: bool, queries: Vec<Query>) {
+        let mut hungarian = BottleneckHungarian::new(maximize);
+        let mut rows = vec![];
+        for (qi, query) in queries.into_iter().enumerate() {
+            match query {
+                Query::Add(row, expected) => {
+                    rows.push(row.clone());
+                    let m = row.len();
+                    let best = naive(&rows, 0, &mut vec![false; m], maximize);
+                    assert_eq!(best.ok_or(Infeasible), expected, "query {qi}");
+                    let result = hungarian.try_add_row(row);
+                    assert_eq!(result.map(|()| hungarian.cost()), expected, "query {qi}");
+                    if expected.is_err() {
+                        rows.pop();
+                    }
+                }
+                Query::Perm(perm, inv) => {
+                    assert_eq!(hungarian.permutation(), perm, "query {qi}");
+                    assert_eq!(hungarian.inverse_permutation(), inv, "query {qi}");
+                }
+            }
+            assert_eq!(hungarian.height(), rows.len(), "query {qi}");
+            for (i, &j) in hungarian.permutation().iter().enumerate() {
+                assert!(rows[i][j].is_some(), "query {}", qi);
+            }
+        }
+    }
+
+    #[test]
+    fn test_bottleneck_negative() {
+        // Pairing -10 with 9 has the smaller sum,
+        // but 5 and 6 have the smaller maximum.
+        do_test_bottleneck(
+            false,
+            vec![
+                Query::Add(vec![Some(-10), Some(5), Some(8)], Ok(-10)),
+                Query::Perm(vec![0], vec![0, usize::MAX, usize::MAX]),
+                Query::Add(vec![Some(6), Some(9), Some(7)], Ok(6)),
+                Query::Perm(vec![1, 0], vec![1, 0, usize::MAX]),
+                Query::Add(vec![Some(-1), Some(4), Some(-2)], Ok(6)),
+                Query::Perm(vec![1, 0, 2], vec![1, 0, 2]),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_bottleneck_maximize() {
+        // The last row moves both earlier rows off their best cells.
+        do_test_bottleneck(
+            true,
+            vec![
+                Query::Add(vec![Some(3), Some(7), Some(-4)], Ok(7)),
+                Query::Add(vec![Some(8), Some(1), Some(2)], Ok(7)),
+                Query::Perm(vec![1, 0], vec![1, 0, usize::MAX]),
+                Query::Add(vec![Some(-5), Some(6), Some(0)], Ok(2)),
+                Query::Perm(vec![0, 2, 1], vec![0, 2, 1]),
+            ],
+        );
+        let hungarian = BottleneckHungarian::solve_max(vec![vec![-7, -2], vec![-1, -9]]);
+        assert_eq!(hungarian.cost(), -2);
+        assert_eq!(hungarian.permutation(), [1, 0]);
+    }
+
+    #[test]
+    fn test_bottleneck_forbidden() {
+        do_test_bottleneck(
+            false,
+            vec![
+                Query::Add(vec![None, Some(4), None], Ok(4)),
+                // Both rows need column 1, so the row is discarded.
+                Query::Add(vec![None, Some(-1), None], Err(Infeasible)),
+                Query::Perm(vec![1], vec![usize::MAX, 0, usize::MAX]),
+                Query::Add(vec![Some(2), Some(-3), None], Ok(4)),
+                Query::Perm(vec![1, 0], vec![1, 0, usize::MAX]),
+                // Column 0 is taken by the previous row.
+                Query::Add(vec![Some(9), None, None], Err(Infeasible)),
+                Query::Perm(vec![1, 0], vec![1, 0, usize::MAX]),
+                Query::Add(vec![None, Some(0), Some(5)], Ok(5)),
+                Query::Perm(vec![1, 0, 2], vec![1, 0, 2]),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_try_solve() {
+        let mat = vec![vec![Some(-1), Some(6)], vec![None, Some(2)]];
+        let hungarian = BottleneckHungarian::try_solve(mat, true).unwrap();
+        assert_eq!(hungarian.cost(), -1);
+        assert_eq!(hungarian.permutation(), [0, 1]);
+        let mat = vec![
+            vec![None, Some(5), None],
+            vec![Some(3), Some(1), Some(2)],
+            vec![None, Some(0), None],
+        ];
+        assert!(BottleneckHungarian::try_solve(mat.clone(), false).is_err());
+        assert!(BottleneckHungarian::try_solve(mat, true).is_err());
     }
 }
@@ -4,9 +4,105 @@
 //! - [LC Shortest Path](https://judge.yosupo.jp/problem/shortest_path) (500,000 edges in 300 ms)
 //!   ([Submission](https://judge.yosupo.jp/submission/165164))
 //! - [DMOJ Single Source Shortest Path](https://dmoj.ca/problem/sssp)
+//!
+//! # Free functions on [`Graph`]
+//! - [`dijkstra`]: distances and parents from several sources.
+//! - [`dijkstra_until`]: the same, but stops once the given
+//!   targets are finalized.
+//! - [`k_shortest_walks`]: the costs of the `k` shortest walks
+//!   to a target, which may repeat vertices.
+//!
+//! ```
+//! # extern crate edge_list;
+//! # use dijkstra::dijkstra;
+//! # use edge_list::Graph;
+//! let mut graph = Graph::new(4);
+//! graph.direct(0, 1, 10);
+//! graph.direct(0, 2, 20);
+//! graph.direct(1, 2, 5);
+//! let paths = dijkstra(&graph, &[0]);
+//! assert_eq!(paths.dist, vec![Some(0), Some(10), Some(15), None]);
+//! assert_eq!(paths.path(2), Some(vec![0, 1, 2]));
+//! ```
+//!
+//! # Heap backends
+//! [`Dijkstra::solve_with`] takes the priority queue as a type
+//! parameter implementing [`Heap`]:
+//! - [`LazyBinaryHeap`], the default used by [`Dijkstra::solve`].
+//!   It pushes duplicate entries and skips stale ones.
+//! - [`RadixHeap`], for unsigned integer costs.
+//!   About twice as fast as the default when the costs are small.
+//! - [`IndexedHeap`], a 4-ary heap with decrease-key,
+//!   for any costs. Uses `O(n)` memory instead of `O(m)`.
+//!
+//! ```
+//! # use dijkstra::{Dijkstra, RadixHeap};
+//! let mut graph = Dijkstra::new(3);
+//! graph.connect(0, 1, 10u64);
+//! graph.connect(1, 2, 5);
+//! let dist = graph.solve_with::<RadixHeap<u64, usize>>(0);
+//! assert_eq!(dist, &vec![Some(0), Some(10), Some(15)]);
+//! ```
+
+extern crate edge_list;
+extern crate indexed_heap;
+extern crate radix_heap;
 
+use edge_list::Graph;
+pub use indexed_heap::IndexedHeap;
+pub use radix_heap::{RadixHeap, RadixKey};
 use std::{cmp::Reverse, collections::BinaryHeap, ops::Add};
 
+/// Priority queue of vertices, keyed by tentative distances.
+pub trait Heap<T> {
+    /// Creates an empty queue for vertices `0..n`.
+    fn new_heap(n: usize) -> Self;
+    /// Records that the distance of `v` decreased to `d`.
+    fn decrease(&mut self, v: usize, d: T);
+    /// Removes and returns a vertex with the minimum distance.
+    /// It may return stale entries, which are skipped.
+    fn pop_min(&mut self) -> Option<(usize, T)>;
+}
+
+/// Binary heap with lazy deletion.
+pub type LazyBinaryHeap<T> = BinaryHeap<Reverse<(T, usize)>>;
+
+impl<T: Ord> Heap<T> for LazyBinaryHeap<T> {
+    fn new_heap(_: usize) -> Self {
+        Self::new()
+    }
+    fn decrease(&mut self, v: usize, d: T) {
+        self.push(Reverse((d, v)));
+    }
+    fn pop_min(&mut self) -> Option<(usize, T)> {
+        self.pop().map(|Reverse((d, v))| (v, d))
+    }
+}
+
+impl<T: RadixKey> Heap<T> for RadixHeap<T, usize> {
+    fn new_heap(_: usize) -> Self {
+        Self::new()
+    }
+    fn decrease(&mut self, v: usize, d: T) {
+        self.push(d, v);
+    }
+    fn pop_min(&mut self) -> Option<(usize, T)> {
+        self.pop().map(|(d, v)| (v, d))
+    }
+}
+
+impl<T: Ord> Heap<T> for IndexedHeap<T> {
+    fn new_heap(n: usize) -> Self {
+        Self::new(n)
+    }
+    fn decrease(&mut self, v: usize, d: T) {
+        self.push(v, d);
+    }
+    fn pop_min(&mut self) -> Option<(usize, T)> {
+        self.pop()
+    }
+}
+
 /// A graph supporting Dijkstra's algorithm. Each vertex
 /// is numbered `0, 1, 2, ...`.
 ///
@@ -58,7 +154,7 @@ where
     /// ```
     #[inline]
     #[must_use]
-    pub fn n(&self) -> usize {
+    pub const fn n(&self) -> usize {
         self.adj.len()
     }
     #[inline]
@@ -120,14 +216,21 @@ where
     /// assert_eq!(graph.solve(0), &vec![Some(0), Some(10), Some(15), None]);
     /// ```
     pub fn solve(&mut self, start: usize) -> &Vec<Option<T>> {
+        self.solve_with::<LazyBinaryHeap<T>>(start)
+    }
+
+    /// Same as [`solve`](Self::solve), but uses the heap `H`.
+    /// See the [module docs](crate) for the available heaps.
+    pub fn solve_with<H: Heap<T>>(&mut self, start: usize) -> &Vec<Option<T>> {
         self.verify_vertex(start);
 
         let mut dist = vec![None; self.n()];
         dist[start] = Some(T::default());
         self.prv = vec![None; self.n()];
-        let mut pq = BinaryHeap::from([Reverse((T::default(), start))]);
+        let mut pq = H::new_heap(self.n());
+        pq.decrease(start, T::default());
 
-        while let Some(Reverse((d, v))) = pq.pop() {
+        while let Some((v, d)) = pq.pop_min() {
             if dist[v].as_ref() != Some(&d) {
                 continue;
             }
@@ -140,7 +243,7 @@ where
                 }
                 dist[*u] = Some(nd.clone());
                 self.prv[*u] = Some(v);
-                pq.push(Reverse((nd, *u)));
+                pq.decrease(*u, nd);
             }
         }
         self.dist = dist;
@@ -180,8 +283,439 @@ where
             } else {
                 path.reverse();
                 return Some(path);
-            };
+            }
         }
         unreachable!()
     }
 }
+
+/// Result of [`dijkstra`] and [`dijkstra_until`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ShortestPaths<T> {
+    /// `dist[v]` is the distance from the nearest source to `v`,
+    /// or `None` if `v` is unreachable (or was not finalized
+    /// before an early exit).
+    pub dist: Vec<Option<T>>,
+    /// `parent[v]` is the previous vertex on a shortest path
+    /// to `v`, or `None` if `v` is a source or unreachable.
+    pub parent: Vec<Option<usize>>,
+}
+
+impl<T> ShortestPaths<T> {
+    /// Returns a shortest path from a source to `v`,
+    /// or `None` if `dist[v]` is `None`.
+    ///
+    /// 🕒 `O(n)`.
+    #[must_use]
+    pub fn path(&self, v: usize) -> Option<Vec<usize>> {
+        self.dist[v].as_ref()?;
+        let mut path = vec![v];
+        let mut cur = v;
+        while let Some(p) = self.parent[cur] {
+            path.push(p);
+            cur = p;
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+/// Runs Dijkstra's algorithm on `graph` from every vertex
+/// in `sources` at once. The costs follow the same rules
+/// as [`Dijkstra`].
+///
+/// 🕒 `O(n + mlogn)`.
+///
+/// ⚠️ Panics if a reachable edge has a negative cost.
+#[must_use]
+pub fn dijkstra<T>(graph: &Graph<T>, sources: &[usize]) -> ShortestPaths<T>
+where
+    T: Clone + Default + Ord + Add<Output = T>,
+{
+    search(graph, sources, &[])
+}
+
+/// Same as [`dijkstra`], but stops as soon as every vertex in
+/// `targets` is finalized or known to be unreachable.
+///
+/// `dist` and `path` are exact for the targets, but vertices
+/// farther than all targets may be left unfinalized.
+///
+/// 🕒 `O(n + mlogn)`.
+///
+/// ⚠️ Panics if a reachable edge has a negative cost.
+///
+/// # Example
+/// ```
+/// # extern crate edge_list;
+/// # use dijkstra::dijkstra_until;
+/// # use edge_list::Graph;
+/// let mut graph = Graph::new(3);
+/// graph.direct(0, 1, 1);
+/// graph.direct(1, 2, 1);
+/// let paths = dijkstra_until(&graph, &[0], &[1]);
+/// assert_eq!(paths.dist[1], Some(1));
+/// assert_eq!(paths.dist[2], None);
+/// ```
+#[must_use]
+pub fn dijkstra_until<T>(graph: &Graph<T>, sources: &[usize], targets: &[usize]) -> ShortestPaths<T>
+where
+    T: Clone + Default + Ord + Add<Output = T>,
+{
+    assert!(!targets.is_empty(), "no targets");
+    search(graph, sources, targets)
+}
+
+/// Stops when every vertex in `targets` is finalized,
+/// or never if `targets` is empty.
+fn search<T>(graph: &Graph<T>, sources: &[usize], targets: &[usize]) -> ShortestPaths<T>
+where
+    T: Clone + Default + Ord + Add<Output = T>,
+{
+    let n = graph.vertex_count();
+    let mut dist = vec![None; n];
+    let mut parent = vec![None; n];
+    let mut done = vec![false; n];
+    let mut is_target = vec![false; n];
+    let mut remaining = 0;
+    for &t in targets {
+        if !std::mem::replace(&mut is_target[t], true) {
+            remaining += 1;
+        }
+    }
+    let mut pq = LazyBinaryHeap::new();
+    for &s in sources {
+        dist[s] = Some(T::default());
+        pq.push(Reverse((T::default(), s)));
+    }
+
+    while let Some(Reverse((d, v))) = pq.pop() {
+        if std::mem::replace(&mut done[v], true) {
+            continue;
+        }
+        if is_target[v] {
+            remaining -= 1;
+            if remaining == 0 {
+                break;
+            }
+        }
+        for (u, c) in graph.neighbors(v) {
+            assert!(*c >= T::default(), "negative cost");
+            let nd = d.clone() + c.clone();
+            if dist[u].iter().any(|pd| pd <= &nd) {
+                continue;
+            }
+            dist[u] = Some(nd.clone());
+            parent[u] = Some(v);
+            pq.push(Reverse((nd, u)));
+        }
+    }
+    for (d, done) in dist.iter_mut().zip(done) {
+        if !done {
+            *d = None;
+        }
+    }
+    ShortestPaths { dist, parent }
+}
+
+/// Returns the costs of the `k` shortest walks from any vertex in
+/// `sources` to `target`, in nondecreasing order.
+///
+/// Walks may repeat vertices and edges;
+/// parallel edges give different walks.
+/// Returns fewer than `k` costs if there are fewer walks.
+///
+/// Each vertex is popped from the heap at most `k` times.
+///
+/// 🕒 `O(k(n + mlog(km)))`.
+///
+/// ⚠️ Panics if a reachable edge has a negative cost.
+///
+/// # Example
+/// ```
+/// # extern crate edge_list;
+/// # use dijkstra::k_shortest_walks;
+/// # use edge_list::Graph;
+/// let mut graph = Graph::new(3);
+/// graph.direct(0, 1, 1);
+/// graph.direct(1, 0, 1);
+/// graph.direct(1, 2, 5);
+/// assert_eq!(k_shortest_walks(&graph, &[0], 2, 3), vec![6, 8, 10]);
+/// assert_eq!(k_shortest_walks(&graph, &[2], 0, 3), vec![]);
+/// ```
+///
+/// - [LC K-Shortest Walk](https://judge.yosupo.jp/problem/k_shortest_walk)
+///   is too large for this, but smaller versions of it are common.
+#[must_use]
+pub fn k_shortest_walks<T>(graph: &Graph<T>, sources: &[usize], target: usize, k: usize) -> Vec<T>
+where
+    T: Clone + Default + Ord + Add<Output = T>,
+{
+    let mut popped = vec![0; graph.vertex_count()];
+    let mut costs = vec![];
+    let mut pq = sources
+        .iter()
+        .map(|&s| Reverse((T::default(), s)))
+        .collect::<LazyBinaryHeap<T>>();
+    while let Some(Reverse((d, v))) = pq.pop() {
+        if popped[v] == k {
+            continue;
+        }
+        popped[v] += 1;
+        if v == target {
+            costs.push(d.clone());
+            if costs.len() == k {
+                break;
+            }
+        }
+        for (u, c) in graph.neighbors(v) {
+            assert!(*c >= T::default(), "negative cost");
+            if popped[u] < k {
+                pq.push(Reverse((d.clone() + c.clone(), u)));
+            }
+        }
+    }
+    costs
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::time::Instant;
+
+    fn do_test_backends(n: usize, edges: &[(usize, usize, u64)], start: usize) {
+        let mut graph = Dijkstra::new(n);
+        for &(a, b, c) in edges {
+            graph.connect(a, b, c);
+        }
+
+        // Floyd-Warshall
+        let mut naive = vec![vec![None; n]; n];
+        for (v, row) in naive.iter_mut().enumerate() {
+            row[v] = Some(0);
+        }
+        for &(a, b, c) in edges {
+            naive[a][b] = naive[a][b].min(Some(c)).or(Some(c));
+        }
+        for k in 0..n {
+            for i in 0..n {
+                for j in 0..n {
+                    if let (Some(x), Some(y)) = (naive[i][k], naive[k][j]) {
+                        naive[i][j] = Some(naive[i][j].map_or(x + y, |d: u64| d.min(x + y)));
+                    }
+                }
+            }
+        }
+
+        assert_eq!(graph.solve(start), &naive[start]);
+        assert_eq!(graph.solve_with::<IndexedHeap<_>>(start), &naive[start]);
+        assert_eq!(graph.solve_with::<RadixHeap<_, _>>(start), &naive[start]);
+        for (v, &want) in naive[start].iter().enumerate() {
+            let Some(path) = graph.path(v) else {
+                assert_eq!(want, None);
+                continue;
+            };
+            assert_eq!(path[0], start);
+            let cost = path
+                .windows(2)
+                .map(|w| {
+                    edges
+                        .iter()
+                        .filter(|e| (e.0, e.1) == (w[0], w[1]))
+                        .map(|e| e.2)
+                        .min()
+                        .unwrap()
+                })
+                .sum::<u64>();
+            assert_eq!(Some(cost), want);
+        }
+    }
+
+    #[test]
+    fn test_backends() {
+        let edges = [
+            (0, 1, 7),
+            (0, 2, 9),
+            (0, 5, 14),
+            (1, 2, 10),
+            (1, 3, 15),
+            (2, 3, 11),
+            (2, 5, 2),
+            (3, 4, 6),
+            (5, 4, 9),
+            (4, 0, 1),
+            (2, 5, 1),
+            (6, 6, 0),
+            (6, 0, 3),
+        ];
+        for start in 0..7 {
+            do_test_backends(7, &edges, start);
+        }
+        // Zero costs, and costs far apart for the radix heap.
+        let edges = [
+            (0, 1, 0),
+            (1, 2, 0),
+            (0, 2, 1),
+            (2, 3, 1 << 40),
+            (1, 3, (1 << 40) + 1),
+            (3, 4, 0),
+            (0, 4, u64::from(u32::MAX) << 20),
+        ];
+        do_test_backends(5, &edges, 0);
+        do_test_backends(1, &[], 0);
+    }
+
+    fn do_test_free_functions(
+        n: usize,
+        edges: &[(usize, usize, u64)],
+        sources: &[usize],
+        targets: &[usize],
+    ) {
+        let mut old = Dijkstra::new(n);
+        let mut graph = Graph::new(n);
+        for &(a, b, c) in edges {
+            old.connect(a, b, c);
+            graph.direct(a, b, c);
+        }
+
+        let want = (0..n)
+            .map(|v| sources.iter().filter_map(|&s| old.solve(s)[v]).min())
+            .collect::<Vec<_>>();
+        let paths = dijkstra(&graph, sources);
+        assert_eq!(paths.dist, want);
+        for (v, &d) in want.iter().enumerate() {
+            let Some(path) = paths.path(v) else {
+                continue;
+            };
+            assert!(sources.contains(&path[0]));
+            let cost = path
+                .windows(2)
+                .map(|w| {
+                    graph
+                        .neighbors(w[0])
+                        .filter(|e| e.0 == w[1])
+                        .map(|e| *e.1)
+                        .min()
+                        .unwrap()
+                })
+                .sum::<u64>();
+            assert_eq!(Some(cost), d);
+        }
+
+        let until = dijkstra_until(&graph, sources, targets);
+        for (v, d) in until.dist.iter().enumerate() {
+            assert!(d.is_none() || *d == want[v]);
+        }
+        for &t in targets {
+            assert_eq!(until.dist[t], want[t]);
+            assert_eq!(until.path(t), paths.path(t));
+        }
+    }
+
+    #[test]
+    fn test_free_functions() {
+        let edges = [
+            (0, 1, 4),
+            (0, 2, 1),
+            (2, 1, 2),
+            (1, 3, 1),
+            (2, 3, 5),
+            (3, 4, 3),
+            (4, 0, 0),
+            (5, 4, 1),
+            (5, 6, 2),
+            (2, 1, 1),
+            (6, 6, 3),
+        ];
+        do_test_free_functions(8, &edges, &[0], &[3]);
+        do_test_free_functions(8, &edges, &[0], &[7, 4, 0]);
+        do_test_free_functions(8, &edges, &[5], &[1, 6]);
+        do_test_free_functions(8, &edges, &[1, 5], &[2]);
+        do_test_free_functions(8, &edges, &[7, 7], &[0, 7]);
+        do_test_free_functions(8, &edges, &[], &[0]);
+        do_test_free_functions(1, &[], &[0], &[0]);
+    }
+
+    fn do_test_k_shortest_walks(
+        n: usize,
+        edges: &[(usize, usize, usize)],
+        sources: &[usize],
+        target: usize,
+        k: usize,
+    ) {
+        let mut graph = Graph::new(n);
+        for &(a, b, c) in edges {
+            graph.direct(a, b, c);
+        }
+
+        // count[c][v] is the number of walks to v of cost c, capped at k.
+        let max_cost = 5 * (n + k);
+        let mut count = vec![vec![0; n]; max_cost + 1];
+        for &s in sources {
+            count[0][s] += 1;
+        }
+        for c in 0..max_cost {
+            for v in 0..n {
+                let ways = count[c][v];
+                for (u, &w) in graph.neighbors(v) {
+                    if c + w <= max_cost {
+                        count[c + w][u] = (count[c + w][u] + ways).min(k);
+                    }
+                }
+            }
+        }
+        let want = (0..=max_cost)
+            .flat_map(|c| vec![c; count[c][target]])
+            .take(k)
+            .collect::<Vec<_>>();
+        assert_eq!(k_shortest_walks(&graph, sources, target, k), want);
+    }
+
+    #[test]
+    fn test_k_shortest_walks() {
+        let edges = [
+            (0, 1, 1),
+            (1, 2, 2),
+            (0, 2, 3),
+            (2, 0, 1),
+            (2, 3, 5),
+            (1, 1, 4),
+            (3, 2, 2),
+        ];
+        for target in 0..5 {
+            for k in [1, 2, 7, 20] {
+                do_test_k_shortest_walks(5, &edges, &[0], target, k);
+                do_test_k_shortest_walks(5, &edges, &[3, 0], target, k);
+                do_test_k_shortest_walks(5, &edges, &[1, 1], target, k);
+            }
+        }
+        // Parallel edges give distinct walks of the same cost.
+        do_test_k_shortest_walks(2, &[(0, 1, 2), (0, 1, 2), (1, 0, 1)], &[0], 1, 10);
+    }
+
+    /// 10^6 edges with costs below 1000.
+    /// Run with `cargo test --release -p dijkstra -- --ignored`.
+    #[test]
+    #[ignore = "benchmark"]
+    fn bench_backends() {
+        let n = 200_000;
+        let mut graph = Dijkstra::new(n);
+        for i in 0..1_000_000u32 {
+            // multiplicative hashing scatters the heads and costs
+            let h = i.wrapping_mul(0x9e37_79b9) >> 8;
+            graph.connect(i as usize % n, h as usize % n, u64::from(h % 1000));
+        }
+
+        let now = Instant::now();
+        let binary = graph.solve(0).clone();
+        let binary_time = now.elapsed();
+        let indexed = graph.solve_with::<IndexedHeap<_>>(0).clone();
+        let now = Instant::now();
+        let radix = graph.solve_with::<RadixHeap<_, _>>(0).clone();
+        let radix_time = now.elapsed();
+
+        assert_eq!(binary, indexed);
+        assert_eq!(binary, radix);
+        assert!(radix_time < binary_time);
+    }
+}
@@ -0,0 +1,453 @@
+//! [Dinic's algorithm](https://en.wikipedia.org/wiki/Dinic%27s_algorithm),
+//! maximum flow and minimum cut.
+//!
+//! # Usage
+//! The edges are stored as linked lists as in `edge_list`, where
+//! each edge added by [`Dinic::add_edge`] is paired with its reverse.
+//! The capacities are parametrized over [`Capacity`],
+//! which is implemented for the primitive integers.
+//!
+//! [`Dinic::max_flow`] can be called again after adding more edges,
+//! and then only pushes the additional flow.
+//!
+//! # Example
+//! ```
+//! # use dinic::Dinic;
+//! let mut graph = Dinic::new(4);
+//! let a = graph.add_edge(0, 1, 3u32);
+//! let b = graph.add_edge(0, 2, 2);
+//! graph.add_edge(1, 2, 5);
+//! graph.add_edge(1, 3, 2);
+//! let c = graph.add_edge(2, 3, 3);
+//! assert_eq!(graph.max_flow(0, 3), 5);
+//! assert_eq!(graph.flow(a), 3);
+//! assert_eq!(graph.flow(c), 3);
+//! assert_eq!(graph.min_cut(0), vec![true, false, false, false]);
+//!
+//! // Re-augment after adding an edge.
+//! graph.add_edge(0, 3, 4);
+//! assert_eq!(graph.max_flow(0, 3), 4);
+//! assert_eq!(graph.edge(b).flow, 2);
+//! ```
+//!
+//! - [AOJ GRL_6_A Maximum Flow](https://onlinejudge.u-aizu.ac.jp/problems/GRL_6_A)
+//! ```ignore
+//! let (n, m) = (oj.usize(), oj.usize());
+//! let mut graph = Dinic::new(n);
+//! for _ in 0..m {
+//!     graph.add_edge(oj.usize(), oj.usize(), oj.u32());
+//! }
+//! oj.write(graph.max_flow(0, n - 1)).ln();
+//! ```
+
+use std::collections::VecDeque;
+use std::convert::TryFrom;
+use std::ops::{Add, AddAssign, Sub, SubAssign};
+
+/// Integer usable as a capacity in [`Dinic`].
+pub trait Capacity:
+    Copy + Ord + Default + Add<Output = Self> + Sub<Output = Self> + AddAssign + SubAssign
+{
+    /// The maximum value, which limits the total flow.
+    const MAX: Self;
+}
+
+macro_rules! impl_capacity {
+    ($($t:ty)*) => {$(
+        impl Capacity for $t {
+            const MAX: Self = <$t>::MAX;
+        }
+    )*};
+}
+impl_capacity!(u8 u16 u32 u64 u128 usize i8 i16 i32 i64 i128 isize);
+
+const NOPE: u32 = u32::MAX;
+
+/// A directed edge of the residual graph.
+#[derive(Clone, Debug)]
+struct Arc<T> {
+    to: u32,
+    prev: u32,
+    /// The residual capacity.
+    cap: T,
+}
+
+/// An edge added by [`Dinic::add_edge`], with its flow.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FlowEdge<T> {
+    /// The start vertex.
+    pub from: usize,
+    /// The end vertex.
+    pub to: usize,
+    /// The capacity.
+    pub cap: T,
+    /// The current flow, in `0..=cap`.
+    pub flow: T,
+}
+
+/// A flow network.
+///
+/// In the method descriptions, let `n` and `m` be
+/// the number of vertices and edges.
+#[derive(Clone, Debug)]
+pub struct Dinic<T> {
+    heads: Vec<u32>,
+    /// Edge `i` is `arcs[2i]`, and its reverse is `arcs[2i+1]`.
+    arcs: Vec<Arc<T>>,
+    level: Vec<u32>,
+    /// The current arc of each vertex, which is
+    /// the first arc that may still be usable.
+    iter: Vec<u32>,
+}
+
+impl<T: Capacity> Dinic<T> {
+    /// Returns an edge-less network with `n` vertices.
+    ///
+    /// ⚠️ Panics if `n >= u32::MAX`.
+    #[must_use]
+    pub fn new(n: usize) -> Self {
+        assert!(n < NOPE as usize, "why do you need {} vertices?!", n);
+        Self {
+            heads: vec![NOPE; n],
+            arcs: vec![],
+            level: vec![NOPE; n],
+            iter: vec![NOPE; n],
+        }
+    }
+
+    /// Returns `n`.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.heads.len()
+    }
+
+    /// Returns whether `n == 0`.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.heads.is_empty()
+    }
+
+    /// Returns `m`.
+    #[must_use]
+    pub const fn edge_count(&self) -> usize {
+        self.arcs.len() / 2
+    }
+
+    fn push_arc(&mut self, from: usize, to: usize, cap: T) {
+        let index = u32::try_from(self.arcs.len()).unwrap();
+        let prev = std::mem::replace(&mut self.heads[from], index);
+        self.arcs.push(Arc {
+            to: u32::try_from(to).unwrap(),
+            prev,
+            cap,
+        });
+    }
+
+    /// Adds an edge from `from` to `to` with capacity `cap`,
+    /// and returns its index, counting from `0`.
+    ///
+    /// ⚠️ Panics if `from >= n`, `to >= n`, or `cap < 0`.
+    pub fn add_edge(&mut self, from: usize, to: usize, cap: T) -> usize {
+        assert!(from < self.len() && to < self.len(), "invalid vertex");
+        assert!(cap >= T::default(), "negative capacity");
+        self.push_arc(from, to, cap);
+        self.push_arc(to, from, T::default());
+        self.edge_count() - 1
+    }
+
+    /// Returns the current flow through edge `i`.
+    ///
+    /// ⚠️ Panics if `i >= m`.
+    #[must_use]
+    pub fn flow(&self, i: usize) -> T {
+        self.arcs[2 * i + 1].cap
+    }
+
+    /// Returns edge `i` with its current flow.
+    ///
+    /// ⚠️ Panics if `i >= m`.
+    #[must_use]
+    pub fn edge(&self, i: usize) -> FlowEdge<T> {
+        let (arc, rev) = (&self.arcs[2 * i], &self.arcs[2 * i + 1]);
+        FlowEdge {
+            from: rev.to as usize,
+            to: arc.to as usize,
+            cap: arc.cap + rev.cap,
+            flow: rev.cap,
+        }
+    }
+
+    /// Returns all edges with their current flows, by index.
+    #[must_use]
+    pub fn edges(&self) -> Vec<FlowEdge<T>> {
+        (0..self.edge_count()).map(|i| self.edge(i)).collect()
+    }
+
+    /// Computes the BFS levels from `s` in the residual graph.
+    fn bfs(&mut self, s: usize) {
+        self.level.fill(NOPE);
+        self.level[s] = 0;
+        let mut queue = VecDeque::from([s]);
+        while let Some(v) = queue.pop_front() {
+            let mut e = self.heads[v];
+            while let Some(arc) = self.arcs.get(e as usize) {
+                let to = arc.to as usize;
+                if arc.cap > T::default() && self.level[to] == NOPE {
+                    self.level[to] = self.level[v] + 1;
+                    queue.push_back(to);
+                }
+                e = arc.prev;
+            }
+        }
+    }
+
+    /// Pushes up to `limit` along the level graph from `s` to `t`,
+    /// and returns the amount pushed.
+    ///
+    /// Keeps the current path as a stack of arcs, and retreats
+    /// to the first saturated arc after each push.
+    fn blocking_flow(&mut self, s: usize, t: usize, limit: T) -> T {
+        let mut total = T::default();
+        let mut path: Vec<u32> = vec![];
+        let mut v = s;
+        loop {
+            if v == t {
+                let mut push = limit - total;
+                for &e in &path {
+                    push = push.min(self.arcs[e as usize].cap);
+                }
+                for &e in &path {
+                    self.arcs[e as usize].cap -= push;
+                    self.arcs[e as usize ^ 1].cap += push;
+                }
+                total += push;
+                if total == limit {
+                    return total;
+                }
+                let k = path
+                    .iter()
+                    .position(|&e| self.arcs[e as usize].cap == T::default())
+                    .unwrap();
+                path.truncate(k);
+                v = path
+                    .last()
+                    .map_or(s, |&e| self.arcs[e as usize].to as usize);
+                continue;
+            }
+            let mut e = self.iter[v];
+            while let Some(arc) = self.arcs.get(e as usize) {
+                let to = arc.to as usize;
+                if arc.cap > T::default() && self.level[to] == self.level[v] + 1 {
+                    break;
+                }
+                e = arc.prev;
+            }
+            self.iter[v] = e;
+            if e == NOPE {
+                // No admissible arc is left, so drop `v` from the level graph.
+                self.level[v] = NOPE;
+                let Some(back) = path.pop() else {
+                    return total;
+                };
+                v = self.arcs[back as usize ^ 1].to as usize;
+            } else {
+                path.push(e);
+                v = self.arcs[e as usize].to as usize;
+            }
+        }
+    }
+
+    /// Pushes flow from `s` to `t` until the flow is maximum
+    /// or the pushed amount reaches `limit`, and returns
+    /// the pushed amount.
+    ///
+    /// 🕒 `O(n^2 m)`, but much faster in practice.
+    /// `O(m sqrt(n))` on unit-capacity bipartite graphs.
+    ///
+    /// ⚠️ Panics if `s >= n`, `t >= n`, or `s == t`.
+    pub fn max_flow_with_limit(&mut self, s: usize, t: usize, limit: T) -> T {
+        assert_ne!(s, t, "the source and the sink must differ");
+        let mut total = T::default();
+        while total < limit {
+            self.bfs(s);
+            if self.level[t] == NOPE {
+                break;
+            }
+            self.iter.copy_from_slice(&self.heads);
+            total += self.blocking_flow(s, t, limit - total);
+        }
+        total
+    }
+
+    /// Pushes the maximum flow from `s` to `t`, on top of the
+    /// current flow, and returns the pushed amount.
+    ///
+    /// After adding edges, call it again to re-augment.
+    /// The flow from `s` to `t` is then the sum of the returned values.
+    ///
+    /// 🕒 `O(n^2 m)`, but much faster in practice.
+    ///
+    /// ⚠️ Panics if `s >= n`, `t >= n`, or `s == t`.
+    pub fn max_flow(&mut self, s: usize, t: usize) -> T {
+        self.max_flow_with_limit(s, t, T::MAX)
+    }
+
+    /// Returns whether each vertex is reachable from `s` in the
+    /// residual graph. After [`Dinic::max_flow`], these vertices
+    /// form the source side of a minimum cut, which consists of
+    /// the saturated edges leaving them.
+    ///
+    /// 🕒 `O(n+m)`.
+    #[must_use]
+    pub fn min_cut(&self, s: usize) -> Vec<bool> {
+        let mut seen = vec![false; self.len()];
+        seen[s] = true;
+        let mut stack = vec![s];
+        while let Some(v) = stack.pop() {
+            let mut e = self.heads[v];
+            while let Some(arc) = self.arcs.get(e as usize) {
+                let to = arc.to as usize;
+                if arc.cap > T::default() && !seen[to] {
+                    seen[to] = true;
+                    stack.push(to);
+                }
+                e = arc.prev;
+            }
+        }
+        seen
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Returns the capacity of the cut, where `side[v]`
+    /// is whether `v` is on the source side.
+    fn cut_cap(edges: &[(usize, usize, i64)], side: &[bool]) -> i64 {
+        edges
+            .iter()
+            .filter(|&&(a, b, _)| side[a] && !side[b])
+            .map(|e| e.2)
+            .sum()
+    }
+
+    /// Checks the capacity and conservation constraints,
+    /// and returns the flow out of `s`.
+    fn check_flow(graph: &Dinic<i64>, s: usize, t: usize) -> i64 {
+        let mut excess = vec![0; graph.len()];
+        for e in graph.edges() {
+            assert!(0 <= e.flow && e.flow <= e.cap);
+            excess[e.from] -= e.flow;
+            excess[e.to] += e.flow;
+        }
+        for (v, &x) in excess.iter().enumerate() {
+            if v != s && v != t {
+                assert_eq!(x, 0);
+            }
+        }
+        assert_eq!(excess[s], -excess[t]);
+        excess[t]
+    }
+
+    /// Adds the first half of `edges`, computes the flow from `0`
+    /// to `n - 1`, then adds the rest and augments the flow. Also
+    /// augments by at most `limit` on a copy after the first half.
+    fn do_test_dinic(n: usize, edges: &[(usize, usize, i64)], limit: i64) {
+        let (s, t) = (0, n - 1);
+        let want = (0..1 << n)
+            .filter(|&mask: &usize| mask & 1 == 1 && mask >> t & 1 == 0)
+            .map(|mask| {
+                let side = (0..n).map(|v| mask >> v & 1 == 1).collect::<Vec<_>>();
+                cut_cap(edges, &side)
+            })
+            .min()
+            .unwrap();
+
+        let mut graph = Dinic::new(n);
+        let mid = edges.len() / 2;
+        for (i, &(a, b, cap)) in edges[..mid].iter().enumerate() {
+            assert_eq!(graph.add_edge(a, b, cap), i);
+        }
+        let first = graph.max_flow(s, t);
+        assert_eq!(check_flow(&graph, s, t), first);
+        for &(a, b, cap) in &edges[mid..] {
+            graph.add_edge(a, b, cap);
+        }
+        let mut limited = graph.clone();
+        let second = graph.max_flow(s, t);
+        assert_eq!(first + second, want);
+        assert_eq!(check_flow(&graph, s, t), want);
+        for (i, &(a, b, cap)) in edges.iter().enumerate() {
+            let e = graph.edge(i);
+            assert_eq!((e.from, e.to, e.cap, e.flow), (a, b, cap, graph.flow(i)));
+        }
+
+        let side = graph.min_cut(s);
+        assert!(side[s] && !side[t]);
+        assert_eq!(cut_cap(edges, &side), want);
+
+        let pushed = limited.max_flow_with_limit(s, t, limit);
+        assert_eq!(pushed, limit.min(second));
+        assert_eq!(check_flow(&limited, s, t), first + pushed);
+    }
+
+    #[test]
+    fn test_dinic() {
+        let edges = [
+            (0, 1, 10),
+            (0, 2, 10),
+            (1, 3, 4),
+            (1, 4, 8),
+            (2, 4, 9),
+            (4, 3, 6),
+            (3, 5, 10),
+            (4, 5, 10),
+            (1, 2, 2),
+            (5, 0, 7),
+            (2, 2, 3),
+            (0, 1, 5),
+        ];
+        for limit in [0, 3, 100] {
+            do_test_dinic(6, &edges, limit);
+        }
+        // Flow must be pushed back along 1 -> 2 in the second round.
+        let edges = [
+            (0, 1, 1),
+            (1, 2, 1),
+            (2, 3, 1),
+            (0, 2, 1),
+            (1, 3, 1),
+            (3, 3, 0),
+        ];
+        do_test_dinic(4, &edges, 1);
+        do_test_dinic(3, &[(1, 0, 5), (2, 1, 5)], 4);
+        do_test_dinic(2, &[], 1);
+    }
+
+    #[test]
+    fn test_large() {
+        // A long path, which is deep for the DFS.
+        let n = 200_000;
+        let mut graph = Dinic::new(n);
+        for v in 1..n {
+            graph.add_edge(v - 1, v, 5u32);
+        }
+        assert_eq!(graph.max_flow(0, n - 1), 5);
+
+        let n = 5_000;
+        let mut graph = Dinic::new(n);
+        let mut edges = vec![];
+        for i in 0..100_000u32 {
+            // multiplicative hashing scatters the heads and capacities
+            let h = i.wrapping_mul(0x9e37_79b9);
+            let (from, to) = (i as usize % n, (h >> 8) as usize % n);
+            let cap = i64::from(h % 1_000_000_000);
+            graph.add_edge(from, to, cap);
+            edges.push((from, to, cap));
+        }
+        let flow = graph.max_flow(0, n - 1);
+        assert_eq!(check_flow(&graph, 0, n - 1), flow);
+        assert_eq!(cut_cap(&edges, &graph.min_cut(0)), flow);
+    }
+}
@@ -144,6 +144,21 @@ impl<T> Graph<T> {
         })
     }
 
+    /// Returns the indices of the edges starting from
+    /// the vertex `v`, in the same order as `edges_from`.
+    /// These are the indices returned by `direct`.
+    ///
+    /// ⚠️ Panics if `v >= n`.
+    #[inline]
+    pub fn edge_indices_from(&self, v: usize) -> impl Iterator<Item = usize> + '_ {
+        let mut index = self.heads[v];
+        std::iter::from_fn(move || {
+            let edge = self.edges.get(index as usize)?;
+            let cur = std::mem::replace(&mut index, edge.prev);
+            Some(cur as usize)
+        })
+    }
+
     /// Returns the neighboring vertices of `v`
     /// and the contents of the corresponding edges.
     ///
@@ -0,0 +1,306 @@
+//! [Floyd-Warshall algorithm](https://en.wikipedia.org/wiki/Floyd%E2%80%93Warshall_algorithm),
+//! all-pairs shortest paths.
+//!
+//! Uses [`Graph`].
+//!
+//! # Edge costs
+//! The edge costs are parametrized over
+//! `T: Copy + Default + Ord + Add<Output = T>`,
+//! where `T::default()` is zero, e.g. `i64`.
+//! The costs may be negative. Pairs connected through a
+//! negative cycle get [`Dist::NegInf`], and the other
+//! distances stay exact without overflowing.
+//!
+//! # Example
+//! ```
+//! # extern crate edge_list;
+//! # use floyd_warshall::{Dist, FloydWarshall};
+//! # use edge_list::Graph;
+//! let mut graph = Graph::new(4);
+//! graph.direct(0, 1, 4i64);
+//! graph.direct(1, 2, -3);
+//! graph.direct(0, 2, 2);
+//! graph.direct(3, 3, -1);
+//! let fw = FloydWarshall::new(&graph);
+//! assert_eq!(fw.dist(0, 2), Dist::Finite(1));
+//! assert_eq!(fw.path(0, 2), Some(vec![0, 1, 2]));
+//! assert_eq!(fw.dist(2, 0), Dist::Unreachable);
+//! assert_eq!(fw.dist(3, 3), Dist::NegInf);
+//! assert!(fw.has_negative_cycle());
+//! ```
+//!
+//! - [AOJ GRL_1_C All Pairs Shortest Path](https://onlinejudge.u-aizu.ac.jp/problems/GRL_1_C)
+//! ```ignore
+//! let (n, m) = (oj.usize(), oj.usize());
+//! let mut graph = Graph::new(n);
+//! for _ in 0..m {
+//!     graph.direct(oj.usize(), oj.usize(), oj.i64());
+//! }
+//! let fw = FloydWarshall::new(&graph);
+//! if fw.has_negative_cycle() {
+//!     oj.write("NEGATIVE CYCLE").ln();
+//!     return;
+//! }
+//! for u in 0..n {
+//!     for v in 0..n {
+//!         match fw.dist(u, v) {
+//!             Dist::Finite(d) => oj.write(d),
+//!             _ => oj.write("INF"),
+//!         };
+//!         if v + 1 < n { oj.sp(); }
+//!     }
+//!     oj.ln();
+//! }
+//! ```
+
+extern crate edge_list;
+
+use edge_list::Graph;
+use std::ops::Add;
+
+/// Distance between two vertices.
+///
+/// The variants are ordered as `NegInf < Finite(_) < Unreachable`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Dist<T> {
+    /// Arbitrarily small, through a negative cycle.
+    NegInf,
+    /// The minimum cost of a path.
+    Finite(T),
+    /// No path exists.
+    Unreachable,
+}
+
+const NONE: usize = usize::MAX;
+
+/// All-pairs shortest paths of a graph.
+///
+/// In the method descriptions, let `n` be the number of vertices.
+#[derive(Clone, Debug)]
+pub struct FloydWarshall<T> {
+    dist: Vec<Vec<Dist<T>>>,
+    /// `next[u][v]` is the vertex after `u` on a shortest path to `v`.
+    next: Vec<Vec<usize>>,
+}
+
+impl<T> FloydWarshall<T>
+where
+    T: Copy + Default + Ord + Add<Output = T>,
+{
+    /// Computes the shortest paths between every pair of vertices.
+    ///
+    /// 🕒 `O(n^3)`.
+    #[must_use]
+    pub fn new(graph: &Graph<T>) -> Self {
+        let n = graph.vertex_count();
+        let zero = Dist::Finite(T::default());
+        let mut dist = vec![vec![Dist::Unreachable; n]; n];
+        let mut next = vec![vec![NONE; n]; n];
+        for u in 0..n {
+            dist[u][u] = zero;
+            next[u][u] = u;
+            for (v, &cost) in graph.neighbors(u) {
+                if Dist::Finite(cost) < dist[u][v] {
+                    dist[u][v] = Dist::Finite(cost);
+                    next[u][v] = v;
+                }
+            }
+        }
+
+        for k in 0..n {
+            let row_k = dist[k].clone();
+            let neg = row_k[k] < zero;
+            for i in 0..n {
+                let ik = dist[i][k];
+                let Dist::Finite(a) = ik else {
+                    if ik == Dist::NegInf {
+                        for (d, &kj) in dist[i].iter_mut().zip(&row_k) {
+                            if kj != Dist::Unreachable {
+                                *d = Dist::NegInf;
+                            }
+                        }
+                    }
+                    continue;
+                };
+                for j in 0..n {
+                    let nd = match row_k[j] {
+                        Dist::Unreachable => continue,
+                        _ if neg => Dist::NegInf,
+                        Dist::NegInf => Dist::NegInf,
+                        Dist::Finite(b) => Dist::Finite(a + b),
+                    };
+                    if nd < dist[i][j] {
+                        dist[i][j] = nd;
+                        next[i][j] = next[i][k];
+                    }
+                }
+            }
+        }
+        Self { dist, next }
+    }
+
+    /// Returns `n`.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.dist.len()
+    }
+
+    /// Returns whether `n == 0`.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.dist.is_empty()
+    }
+
+    /// Returns the distance from `u` to `v`.
+    ///
+    /// 🕒 `O(1)`.
+    ///
+    /// ⚠️ Panics if `u >= n` or `v >= n`.
+    #[must_use]
+    pub fn dist(&self, u: usize, v: usize) -> Dist<T> {
+        self.dist[u][v]
+    }
+
+    /// Returns whether the graph has a negative cycle.
+    ///
+    /// 🕒 `O(n)`.
+    #[must_use]
+    pub fn has_negative_cycle(&self) -> bool {
+        (0..self.len()).any(|v| self.dist[v][v] == Dist::NegInf)
+    }
+
+    /// Returns a shortest path from `u` to `v`,
+    /// or `None` if the distance is not finite.
+    ///
+    /// 🕒 `O(n)`.
+    ///
+    /// ⚠️ Panics if `u >= n` or `v >= n`.
+    #[must_use]
+    pub fn path(&self, u: usize, v: usize) -> Option<Vec<usize>> {
+        let Dist::Finite(_) = self.dist[u][v] else {
+            return None;
+        };
+        let mut path = vec![u];
+        let mut cur = u;
+        while cur != v {
+            cur = self.next[cur][v];
+            path.push(cur);
+        }
+        Some(path)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// `walks[v]` is the minimum cost of a walk from `sources`
+    /// to `v` with at most `len` edges.
+    fn walks(graph: &Graph<i64>, sources: &[usize], len: usize) -> Vec<Option<i64>> {
+        let n = graph.vertex_count();
+        let mut dist = vec![None; n];
+        for &s in sources {
+            dist[s] = Some(0);
+        }
+        for _ in 0..len {
+            let mut next = dist.clone();
+            for (from, d) in dist.iter().enumerate() {
+                let Some(d) = d else {
+                    continue;
+                };
+                for (to, &cost) in graph.neighbors(from) {
+                    next[to] = Some(next[to].map_or(d + cost, |old: i64| old.min(d + cost)));
+                }
+            }
+            dist = next;
+        }
+        dist
+    }
+
+    fn do_test_floyd_warshall(n: usize, edges: &[(usize, usize, i64)]) {
+        let mut graph = Graph::new(n);
+        for &(u, v, cost) in edges {
+            graph.direct(u, v, cost);
+        }
+        let fw = FloydWarshall::new(&graph);
+        assert_eq!(fw.len(), n);
+
+        let on_cycle = (0..n)
+            .filter(|&c| matches!(walks(&graph, &[c], n + 1)[c], Some(d) if d < 0))
+            .collect::<Vec<_>>();
+        assert_eq!(fw.has_negative_cycle(), !on_cycle.is_empty());
+        for u in 0..n {
+            let reach = walks(&graph, &[u], n);
+            let cycles = on_cycle
+                .iter()
+                .copied()
+                .filter(|&c| reach[c].is_some())
+                .collect::<Vec<_>>();
+            let neg = walks(&graph, &cycles, n);
+            for v in 0..n {
+                let want = match reach[v] {
+                    _ if neg[v].is_some() => Dist::NegInf,
+                    Some(d) => Dist::Finite(d),
+                    None => Dist::Unreachable,
+                };
+                assert_eq!(fw.dist(u, v), want, "{u} -> {v}");
+
+                let Some(path) = fw.path(u, v) else {
+                    continue;
+                };
+                assert_eq!((path[0], path[path.len() - 1]), (u, v));
+                let cost = path
+                    .windows(2)
+                    .map(|w| {
+                        graph
+                            .neighbors(w[0])
+                            .filter(|e| e.0 == w[1])
+                            .map(|e| *e.1)
+                            .min()
+                            .unwrap()
+                    })
+                    .sum();
+                assert_eq!(Dist::Finite(cost), want, "{u} -> {v}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_floyd_warshall() {
+        // Negative edges, but no negative cycle.
+        let dag = [
+            (0, 1, 4),
+            (0, 2, 2),
+            (2, 1, -3),
+            (1, 3, 2),
+            (3, 4, -1),
+            (2, 4, 5),
+            (4, 2, 3),
+        ];
+        // A negative cycle 2 -> 3 -> 4 -> 2, reaching 5 but not 6.
+        let cycle = [
+            (0, 1, 1),
+            (1, 2, 1),
+            (2, 3, -2),
+            (3, 4, 1),
+            (4, 2, 0),
+            (4, 5, 7),
+            (6, 0, -9),
+        ];
+        // A zero-cost cycle, and a negative self-loop on 4.
+        let mixed = [
+            (0, 1, -1),
+            (1, 0, 1),
+            (1, 2, 3),
+            (1, 2, -2),
+            (3, 4, 2),
+            (4, 4, -1),
+            (2, 0, 5),
+        ];
+        for edges in [&dag[..], &cycle, &mixed] {
+            do_test_floyd_warshall(7, edges);
+        }
+        do_test_floyd_warshall(1, &[]);
+        do_test_floyd_warshall(2, &[(0, 1, -5), (1, 0, 5)]);
+    }
+}
@@ -0,0 +1,440 @@
+//! [Hopcroft-Karp algorithm](https://en.wikipedia.org/wiki/Hopcroft%E2%80%93Karp_algorithm),
+//! maximum bipartite matching.
+//!
+//! Uses [`Graph`], with edges from the left vertices `0..L` to the
+//! right vertices `0..R`, so it needs at least `max(L, R)` vertices.
+//!
+//! By [König's theorem](https://en.wikipedia.org/wiki/K%C5%91nig%27s_theorem_(graph_theory)),
+//! the matching also gives a minimum vertex cover,
+//! and its complement, a maximum independent set.
+//!
+//! # Example
+//! ```
+//! # extern crate edge_list;
+//! # use hopcroft_karp::hopcroft_karp;
+//! # use edge_list::UnweightedGraph;
+//! let mut graph = UnweightedGraph::new(3);
+//! graph.direct(0, 0, ());
+//! graph.direct(0, 1, ());
+//! graph.direct(1, 0, ());
+//! graph.direct(2, 0, ());
+//! let matching = hopcroft_karp(&graph, 3, 2);
+//! assert_eq!(matching.size(), 2);
+//! assert_eq!(matching.left[0], Some(1));
+//!
+//! let (left, right) = matching.vertex_cover(&graph);
+//! assert_eq!((left, right), (vec![true, false, false], vec![true, false]));
+//! ```
+//!
+//! An undirected bipartite graph can be split into the two sides
+//! by the coloring from `bicolor::bicolor_graph`:
+//! ```
+//! # extern crate bicolor;
+//! # extern crate edge_list;
+//! # use bicolor::bicolor_graph;
+//! # use edge_list::Graph;
+//! # use hopcroft_karp::{hopcroft_karp, split_by_color};
+//! // 0 - 1 - 2 - 3
+//! let mut graph = Graph::new(4);
+//! for v in 1..4 {
+//!     graph.bidirect(v - 1, v, true); // `true` for different colors
+//! }
+//! let color = bicolor_graph(&graph).unwrap();
+//! let (sides, left, right) = split_by_color(&graph, &color);
+//! assert_eq!((left, right), (vec![0, 2], vec![1, 3]));
+//! assert_eq!(hopcroft_karp(&sides, 2, 2).size(), 2);
+//! ```
+//!
+//! - [LC Matching on Bipartite Graph](https://judge.yosupo.jp/problem/bipartitematching)
+//! ```ignore
+//! let (l, r, m) = (oj.usize(), oj.usize(), oj.usize());
+//! let mut graph = UnweightedGraph::new(l.max(r));
+//! for _ in 0..m {
+//!     graph.direct(oj.usize(), oj.usize(), ());
+//! }
+//! let matching = hopcroft_karp(&graph, l, r);
+//! oj.write(matching.size()).ln();
+//! for (a, b) in matching.pairs() {
+//!     oj.write(a).sp().write(b).ln();
+//! }
+//! ```
+
+extern crate edge_list;
+
+use edge_list::{Graph, UnweightedGraph};
+use std::collections::VecDeque;
+
+const NOPE: usize = usize::MAX;
+
+/// A matching of a bipartite graph.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Matching {
+    /// `left[u]` is the right vertex matched with
+    /// the left vertex `u`, if any.
+    pub left: Vec<Option<usize>>,
+    /// `right[v]` is the left vertex matched with
+    /// the right vertex `v`, if any.
+    pub right: Vec<Option<usize>>,
+}
+
+/// Returns a maximum matching of `graph`, where the edges go from
+/// the left vertices `0..left_count` to the right vertices
+/// `0..right_count`. The edges from other vertices are ignored.
+///
+/// 🕒 `O(E sqrt(V))`.
+///
+/// ⚠️ Panics if `left_count` is more than the number of vertices,
+/// or an edge goes to a vertex `>= right_count`.
+#[must_use]
+pub fn hopcroft_karp<T>(graph: &Graph<T>, left_count: usize, right_count: usize) -> Matching {
+    let n = left_count;
+    let mut start = vec![0; n + 1];
+    let mut adj = Vec::with_capacity(graph.edge_count());
+    for u in 0..n {
+        adj.extend(graph.neighbors(u).map(|(v, _)| v));
+        start[u + 1] = adj.len();
+    }
+    assert!(adj.iter().all(|&v| v < right_count), "invalid right vertex");
+
+    let mut left = vec![NOPE; n];
+    let mut right = vec![NOPE; right_count];
+    let mut dist = vec![NOPE; n];
+    // The current arc of each left vertex.
+    let mut iter = vec![0; n];
+    loop {
+        // Layers of alternating paths from the free left vertices.
+        let mut queue = VecDeque::new();
+        for u in 0..n {
+            dist[u] = if left[u] == NOPE { 0 } else { NOPE };
+            if left[u] == NOPE {
+                queue.push_back(u);
+            }
+        }
+        let mut found = false;
+        while let Some(u) = queue.pop_front() {
+            for &v in &adj[start[u]..start[u + 1]] {
+                let w = right[v];
+                if w == NOPE {
+                    found = true;
+                } else if dist[w] == NOPE {
+                    dist[w] = dist[u] + 1;
+                    queue.push_back(w);
+                }
+            }
+        }
+        if !found {
+            break;
+        }
+
+        iter.copy_from_slice(&start[..n]);
+        for root in 0..n {
+            if left[root] != NOPE {
+                continue;
+            }
+            let mut stack = vec![root];
+            while let Some(&u) = stack.last() {
+                if iter[u] == start[u + 1] {
+                    // Every edge of `u` failed; skip it until the next BFS.
+                    dist[u] = NOPE;
+                    stack.pop();
+                    if let Some(&p) = stack.last() {
+                        iter[p] += 1;
+                    }
+                    continue;
+                }
+                let w = right[adj[iter[u]]];
+                if w == NOPE {
+                    for &x in &stack {
+                        let y = adj[iter[x]];
+                        left[x] = y;
+                        right[y] = x;
+                    }
+                    break;
+                }
+                if dist[w] == dist[u] + 1 {
+                    stack.push(w);
+                } else {
+                    iter[u] += 1;
+                }
+            }
+        }
+    }
+
+    let wrap = |v: usize| if v == NOPE { None } else { Some(v) };
+    Matching {
+        left: left.into_iter().map(wrap).collect(),
+        right: right.into_iter().map(wrap).collect(),
+    }
+}
+
+impl Matching {
+    /// Returns the number of matched pairs.
+    #[must_use]
+    pub fn size(&self) -> usize {
+        self.left.iter().flatten().count()
+    }
+
+    /// Returns the matched pairs `(u, v)`, in increasing order of `u`.
+    #[must_use]
+    pub fn pairs(&self) -> Vec<(usize, usize)> {
+        (self.left.iter().enumerate())
+            .filter_map(|(u, v)| v.map(|v| (u, v)))
+            .collect()
+    }
+
+    /// Returns a minimum vertex cover, as whether each left and
+    /// each right vertex is in it. Its size equals the matching size.
+    ///
+    /// `graph` must be the graph of this maximum matching.
+    ///
+    /// 🕒 `O(V+E)`.
+    #[must_use]
+    pub fn vertex_cover<T>(&self, graph: &Graph<T>) -> (Vec<bool>, Vec<bool>) {
+        // The vertices reachable from the free left vertices
+        // by alternating paths.
+        let mut seen_left = self.left.iter().map(Option::is_none).collect::<Vec<_>>();
+        let mut seen_right = vec![false; self.right.len()];
+        let mut stack = (0..self.left.len())
+            .filter(|&u| seen_left[u])
+            .collect::<Vec<_>>();
+        while let Some(u) = stack.pop() {
+            for (v, _) in graph.neighbors(u) {
+                if seen_right[v] {
+                    continue;
+                }
+                seen_right[v] = true;
+                if let Some(w) = self.right[v] {
+                    if !seen_left[w] {
+                        seen_left[w] = true;
+                        stack.push(w);
+                    }
+                }
+            }
+        }
+        for x in &mut seen_left {
+            *x = !*x;
+        }
+        (seen_left, seen_right)
+    }
+
+    /// Returns a maximum independent set, as whether each left
+    /// and each right vertex is in it. It is the complement of
+    /// [`Matching::vertex_cover`].
+    ///
+    /// `graph` must be the graph of this maximum matching.
+    ///
+    /// 🕒 `O(V+E)`.
+    #[must_use]
+    pub fn independent_set<T>(&self, graph: &Graph<T>) -> (Vec<bool>, Vec<bool>) {
+        let (mut left, mut right) = self.vertex_cover(graph);
+        for x in left.iter_mut().chain(&mut right) {
+            *x = !*x;
+        }
+        (left, right)
+    }
+}
+
+/// Splits an undirected bipartite `graph` by `color`, e.g. from
+/// `bicolor::bicolor_graph`, into the left vertices of color `false`
+/// and the right vertices of color `true`.
+///
+/// Returns `(sides, left, right)`, where `left[i]` and `right[j]` are
+/// the vertices of `graph` numbered `i` and `j` in their sides, and
+/// `sides` has an edge from `i` to `j` for each edge between them,
+/// as the input of [`hopcroft_karp`].
+///
+/// 🕒 `O(V+E)`.
+///
+/// ⚠️ Panics if an edge connects two vertices of the same color.
+#[must_use]
+pub fn split_by_color<T>(
+    graph: &Graph<T>,
+    color: &[bool],
+) -> (UnweightedGraph, Vec<usize>, Vec<usize>) {
+    let mut index = vec![0; color.len()];
+    let (mut left, mut right) = (vec![], vec![]);
+    for (v, &c) in color.iter().enumerate() {
+        let side = if c { &mut right } else { &mut left };
+        index[v] = side.len();
+        side.push(v);
+    }
+    let mut sides = UnweightedGraph::new(left.len().max(right.len()));
+    for u in 0..graph.vertex_count() {
+        for (v, _) in graph.neighbors(u) {
+            assert_ne!(color[u], color[v], "not bipartite");
+            if !color[u] {
+                sides.direct(index[u], index[v], ());
+            }
+        }
+    }
+    (sides, left, right)
+}
+
+#[cfg(test)]
+mod test {
+    extern crate bicolor;
+
+    use self::bicolor::bicolor_graph;
+    use super::*;
+    use std::convert::TryFrom;
+
+    /// Returns the maximum matching size by Kuhn's algorithm.
+    fn naive(graph: &UnweightedGraph, l: usize, r: usize) -> usize {
+        fn augment(
+            graph: &UnweightedGraph,
+            u: usize,
+            seen: &mut [bool],
+            right: &mut [usize],
+        ) -> bool {
+            for (v, ()) in graph.neighbors(u) {
+                if !seen[v] {
+                    seen[v] = true;
+                    if right[v] == NOPE || augment(graph, right[v], seen, right) {
+                        right[v] = u;
+                        return true;
+                    }
+                }
+            }
+            false
+        }
+        let mut right = vec![NOPE; r];
+        (0..l)
+            .filter(|&u| augment(graph, u, &mut vec![false; r], &mut right))
+            .count()
+    }
+
+    /// Checks that `matching` is a maximum matching of `graph`,
+    /// with a valid vertex cover and independent set.
+    fn check(graph: &UnweightedGraph, l: usize, r: usize, matching: &Matching) {
+        let size = matching.size();
+        assert_eq!(size, naive(graph, l, r));
+        assert_eq!(matching.pairs().len(), size);
+        for (u, v) in matching.pairs() {
+            assert_eq!(matching.right[v], Some(u));
+            assert!(graph.neighbors(u).any(|(w, ())| w == v));
+        }
+        assert_eq!(matching.right.iter().flatten().count(), size);
+
+        let (left, right) = matching.vertex_cover(graph);
+        let count = left.iter().chain(&right).filter(|&&x| x).count();
+        assert_eq!(count, size);
+        let (in_left, in_right) = matching.independent_set(graph);
+        for u in 0..l {
+            assert_ne!(left[u], in_left[u]);
+            for (v, ()) in graph.neighbors(u) {
+                assert!(left[u] || right[v]);
+                assert!(!(in_left[u] && in_right[v]));
+            }
+        }
+    }
+
+    fn do_test_hopcroft_karp(l: usize, r: usize, edges: &[(usize, usize)]) {
+        let mut graph = UnweightedGraph::new(l.max(r));
+        for &(u, v) in edges {
+            graph.direct(u, v, ());
+        }
+        check(&graph, l, r, &hopcroft_karp(&graph, l, r));
+    }
+
+    #[test]
+    fn test_hopcroft_karp() {
+        // A greedy matching 0-0, 1-1, 2-2 blocks 3, and needs
+        // the augmenting path 3-2-2-1-1-0-0-3.
+        let edges = [(0, 0), (1, 1), (2, 2), (0, 3), (1, 0), (2, 1), (3, 2)];
+        do_test_hopcroft_karp(4, 4, &edges);
+        // Unbalanced sides, parallel edges, and isolated vertices.
+        let edges = [(0, 1), (0, 1), (1, 1), (2, 1), (2, 4), (4, 0), (4, 4)];
+        do_test_hopcroft_karp(5, 6, &edges);
+        do_test_hopcroft_karp(5, 2, &[(0, 0), (1, 0), (2, 1), (3, 1), (4, 1)]);
+        do_test_hopcroft_karp(0, 3, &[]);
+        do_test_hopcroft_karp(3, 1, &[]);
+    }
+
+    /// Checks every bipartite graph with 3 vertices on each side.
+    #[test]
+    fn test_hopcroft_karp_all() {
+        let pairs = (0..9).map(|i| (i / 3, i % 3)).collect::<Vec<_>>();
+        for mask in 0..1 << 9 {
+            let edges = (0..9)
+                .filter(|&i| mask >> i & 1 == 1)
+                .map(|i| pairs[i])
+                .collect::<Vec<_>>();
+            do_test_hopcroft_karp(3, 3, &edges);
+        }
+    }
+
+    fn do_test_split_by_color(n: usize, edges: &[(usize, usize)]) {
+        let mut graph = Graph::new(n);
+        for &(a, b) in edges {
+            graph.bidirect(a, b, true);
+        }
+        let color = bicolor_graph(&graph).unwrap();
+        let (sides, left, right) = split_by_color(&graph, &color);
+        assert_eq!(left.len() + right.len(), n);
+        assert_eq!(sides.edge_count() * 2, graph.edge_count());
+        for (i, &u) in left.iter().enumerate() {
+            for (j, ()) in sides.neighbors(i) {
+                assert!(graph.neighbors(u).any(|(v, _)| v == right[j]));
+            }
+        }
+        let (l, r) = (left.len(), right.len());
+        check(&sides, l, r, &hopcroft_karp(&sides, l, r));
+    }
+
+    #[test]
+    fn test_split_by_color() {
+        // An even cycle with a pendant path, and a separate star.
+        let edges = [
+            (0, 1),
+            (1, 2),
+            (2, 3),
+            (3, 0),
+            (3, 4),
+            (4, 5),
+            (6, 7),
+            (6, 8),
+            (6, 9),
+        ];
+        do_test_split_by_color(11, &edges);
+        // A grid, where vertex 0 is not on the left.
+        let edges = [
+            (1, 0),
+            (1, 2),
+            (4, 3),
+            (4, 5),
+            (0, 3),
+            (1, 4),
+            (2, 5),
+            (2, 1),
+        ];
+        do_test_split_by_color(6, &edges);
+        do_test_split_by_color(1, &[]);
+    }
+
+    #[test]
+    fn test_large() {
+        // Greedy matching of `u` with `u` leaves one augmenting
+        // path through every vertex.
+        let n = 100_000;
+        let mut graph = UnweightedGraph::new(n);
+        for u in 0..n {
+            if u + 1 < n {
+                graph.direct(u, u + 1, ());
+            }
+            graph.direct(u, u, ());
+        }
+        let matching = hopcroft_karp(&graph, n, n);
+        assert_eq!(matching.size(), n);
+
+        let mut graph = UnweightedGraph::new(n);
+        for i in 0..2 * n {
+            // multiplicative hashing scatters the right ends
+            let h = u32::try_from(i).unwrap().wrapping_mul(0x9e37_79b9) >> 8;
+            graph.direct(i % n, h as usize % n, ());
+        }
+        let matching = hopcroft_karp(&graph, n, n);
+        let (left, right) = matching.vertex_cover(&graph);
+        let count = left.iter().chain(&right).filter(|&&x| x).count();
+        assert_eq!(count, matching.size());
+    }
+}
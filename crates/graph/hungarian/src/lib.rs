@@ -1,10 +1,57 @@
-//! Under construction (usable though).
+//! [Hungarian algorithm](https://en.wikipedia.org/wiki/Hungarian_algorithm),
+//! minimum (or maximum) cost assignment.
+//!
+//! Assigns each row of an `n` by `m` matrix (`n <= m`) to a distinct
+//! column, adding one row at a time. Costs may be negative, and cells
+//! may be forbidden with `None`.
+//!
+//! # Example
+//! ```
+//! # use hungarian::{Hungarian, Infeasible};
+//! let hungarian = Hungarian::solve(vec![vec![4, 1, 3], vec![2, 0, 5]]);
+//! assert_eq!(hungarian.cost(), 3);
+//! assert_eq!(hungarian.permutation(), [1, 0]);
+//! assert_eq!(hungarian.inverse_permutation(), [1, 0, usize::MAX]);
+//!
+//! let hungarian = Hungarian::solve_max(vec![vec![4, 1, 3], vec![2, 0, -5]]);
+//! assert_eq!(hungarian.cost(), 5);
+//!
+//! let mut hungarian = Hungarian::new(false);
+//! assert_eq!(hungarian.try_add_row(vec![Some(1), None]), Ok(()));
+//! assert_eq!(hungarian.try_add_row(vec![Some(-3), None]), Err(Infeasible));
+//! assert_eq!(hungarian.height(), 1); // the row is discarded
+//! ```
+//!
+//! - [LC Assignment Problem](https://judge.yosupo.jp/problem/assignment)
+//! ```ignore
+//! let n = oj.usize();
+//! let mat = (0..n)
+//!     .map(|_| (0..n).map(|_| oj.i64()).collect())
+//!     .collect();
+//! let hungarian = Hungarian::solve(mat);
+//! oj.write(hungarian.cost()).ln();
+//! for &j in hungarian.permutation() {
+//!     oj.write(j).sp();
+//! }
+//! oj.ln();
+//! ```
 
+/// The error when a row cannot be assigned
+/// without using a forbidden cell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Infeasible;
+
+/// The assignment of the rows added so far.
+///
+/// In the method descriptions, let `n` be the number of rows,
+/// and `m` be the number of columns.
 #[derive(Debug, Clone, Default)]
 pub struct Hungarian {
-    mat: Vec<Vec<i64>>,
+    /// The costs, negated when maximizing.
+    mat: Vec<Vec<Option<i64>>>,
     perm: Vec<usize>,
     inv_perm: Vec<usize>,
+    maximize: bool,
 
     /// One half of the potential.
     u: Vec<i64>,
@@ -13,40 +60,126 @@ pub struct Hungarian {
 }
 
 impl Hungarian {
+    /// Returns a solver without rows, which minimizes
+    /// the cost, or maximizes it if `maximize`.
+    #[must_use]
+    pub fn new(maximize: bool) -> Self {
+        Self {
+            maximize,
+            ..Self::default()
+        }
+    }
+
+    /// Returns the minimum cost assignment of `mat`.
+    ///
+    /// 🕒 `O(n^2 m)`.
+    ///
+    /// ⚠️ Panics if `n > m` or `mat` is not rectangular.
+    #[must_use]
     pub fn solve(mat: Vec<Vec<i64>>) -> Self {
-        let mut solver = Self::default();
+        let mut solver = Self::new(false);
+        for row in mat {
+            solver.add_row(row);
+        }
+        solver
+    }
+
+    /// Returns the maximum cost assignment of `mat`.
+    ///
+    /// 🕒 `O(n^2 m)`.
+    ///
+    /// ⚠️ Panics if `n > m` or `mat` is not rectangular.
+    #[must_use]
+    pub fn solve_max(mat: Vec<Vec<i64>>) -> Self {
+        let mut solver = Self::new(true);
         for row in mat {
             solver.add_row(row);
         }
         solver
     }
 
-    pub fn height(&self) -> usize {
+    /// Returns the optimal assignment of `mat` avoiding the `None`
+    /// cells, or `Err(Infeasible)` if there is none.
+    ///
+    /// 🕒 `O(n^2 m)`.
+    ///
+    /// ⚠️ Panics if `n > m` or `mat` is not rectangular.
+    pub fn try_solve(mat: Vec<Vec<Option<i64>>>, maximize: bool) -> Result<Self, Infeasible> {
+        let mut solver = Self::new(maximize);
+        for row in mat {
+            solver.try_add_row(row)?;
+        }
+        Ok(solver)
+    }
+
+    /// Returns `n`.
+    #[must_use]
+    pub const fn height(&self) -> usize {
         self.u.len()
     }
 
-    pub fn width(&self) -> usize {
+    /// Returns `m`, or `0` if there are no rows yet.
+    #[must_use]
+    pub const fn width(&self) -> usize {
         self.v.len()
     }
 
+    /// Returns the total cost of the assignment.
+    #[must_use]
     pub fn cost(&self) -> i64 {
-        self.mat
-            .iter()
-            .zip(self.perm.iter())
-            .map(|(row, j)| row[*j])
-            .sum::<i64>()
+        let cost = (self.mat.iter().zip(&self.perm))
+            .map(|(row, &j)| row[j].unwrap())
+            .sum::<i64>();
+        if self.maximize {
+            -cost
+        } else {
+            cost
+        }
     }
 
+    /// Returns the column assigned to each row.
+    #[must_use]
     pub fn permutation(&self) -> &[usize] {
         &self.perm
     }
 
+    /// Returns the row assigned to each column,
+    /// or `usize::MAX` if there is none.
+    #[must_use]
+    pub fn inverse_permutation(&self) -> &[usize] {
+        &self.inv_perm[..self.width()]
+    }
+
+    /// Returns the potentials `(u, v)` of the rows and the columns,
+    /// with `u[i] + v[j] <= mat[i][j]` for the allowed cells, where
+    /// equality holds for the assigned ones.
+    ///
+    /// When maximizing, `mat` is negated here.
+    #[must_use]
     pub fn potentials(&self) -> (&[i64], &[i64]) {
         (&self.u, &self.v)
     }
 
-    /// TODO: handle negative weights
+    /// Adds a row and updates the assignment.
+    ///
+    /// 🕒 `O(nm)`.
+    ///
+    /// ⚠️ Panics if there are already `m` rows,
+    /// or the length of `row` is not `m`.
     pub fn add_row(&mut self, row: Vec<i64>) {
+        let result = self.try_add_row(row.into_iter().map(Some).collect());
+        debug_assert_eq!(result, Ok(()));
+    }
+
+    /// Adds a row, where the `None` cells are forbidden, and updates
+    /// the assignment. If the rows cannot be assigned together,
+    /// the row is discarded and `Err(Infeasible)` is returned.
+    ///
+    /// 🕒 `O(nm)`.
+    ///
+    /// ⚠️ Panics if there are already `m` rows,
+    /// or the length of `row` is not `m`.
+    pub fn try_add_row(&mut self, mut row: Vec<Option<i64>>) -> Result<(), Infeasible> {
         let (n, mut m) = (self.height(), self.width());
         if n == 0 {
             // initialization
@@ -55,11 +188,18 @@ impl Hungarian {
             self.inv_perm = vec![usize::MAX; m + 1];
         } else {
             // dimension check
-            assert!(n <= m, "too many rows");
+            assert!(n < m, "too many rows");
             assert_eq!(m, row.len(), "matrix must be rectangular");
         }
+        if self.maximize {
+            for x in row.iter_mut().flatten() {
+                *x = -*x;
+            }
+        }
         self.mat.push(row);
         self.perm.push(0);
+        // The first step moves it to the minimum reduced cost,
+        // which keeps the potential feasible for negative costs.
         self.u.push(0);
 
         let mut minv = vec![i64::MAX; m];
@@ -85,22 +225,35 @@ impl Hungarian {
                 .enumerate()
                 .filter(|(_, (((used, _), _), _))| !**used)
                 .map(|(j, (((_, rj), vj), mvj))| {
-                    let diff = *rj - ui - vj;
-                    if diff < *mvj {
-                        *mvj = diff;
-                        augment[j] = j_cur;
+                    if let Some(rj) = *rj {
+                        let diff = rj - ui - vj;
+                        if diff < *mvj {
+                            *mvj = diff;
+                            augment[j] = j_cur;
+                        }
                     }
                     (*mvj, j)
                 })
                 .min()
                 .unwrap();
+            if delta == i64::MAX {
+                // No free column is reachable. The potential
+                // is still feasible for the other rows.
+                self.mat.pop();
+                self.perm.pop();
+                self.u.pop();
+                if n == 0 {
+                    self.v.clear();
+                }
+                return Err(Infeasible);
+            }
 
             // recalculate potential
             for j in 0..m {
                 if used[j] {
                     self.u[self.inv_perm[j]] += delta;
                     self.v[j] -= delta;
-                } else {
+                } else if minv[j] != i64::MAX {
                     minv[j] -= delta;
                 }
             }
@@ -117,5 +270,179 @@ impl Hungarian {
             self.perm[self.inv_perm[j_cur]] = j_cur;
             j_cur = j_prev;
         }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Returns the optimal cost over all assignments of the rows
+    /// `i..` to the columns not in `used`, if any.
+    fn naive(mat: &[Vec<Option<i64>>], i: usize, used: &mut [bool], maximize: bool) -> Option<i64> {
+        if i == mat.len() {
+            return Some(0);
+        }
+        let mut best = None;
+        for j in 0..used.len() {
+            if let (false, Some(x)) = (used[j], mat[i][j]) {
+                used[j] = true;
+                if let Some(rest) = naive(mat, i + 1, used, maximize) {
+                    let cost = x + rest;
+                    best =
+                        Some(best.map_or(
+                            cost,
+                            |b: i64| if maximize { b.max(cost) } else { b.min(cost) },
+                        ));
+                }
+                used[j] = false;
+            }
+        }
+        best
+    }
+
+    /// Checks the assignment and the potentials against `mat`.
+    fn check(mat: &[Vec<Option<i64>>], hungarian: &Hungarian) {
+        let (perm, inv) = (hungarian.permutation(), hungarian.inverse_permutation());
+        let mut cost = 0;
+        for (i, &j) in perm.iter().enumerate() {
+            assert_eq!(inv[j], i);
+            cost += mat[i][j].unwrap();
+        }
+        assert_eq!(inv.iter().filter(|&&i| i != usize::MAX).count(), perm.len());
+        assert_eq!(hungarian.cost(), cost);
+
+        let sign = if hungarian.maximize { -1 } else { 1 };
+        let (u, v) = hungarian.potentials();
+        for (i, row) in mat.iter().enumerate() {
+            for (j, x) in row.iter().enumerate() {
+                if let Some(x) = x {
+                    assert!(x * sign - u[i] - v[j] >= 0);
+                }
+            }
+            assert_eq!(u[i] + v[perm[i]], mat[i][perm[i]].unwrap() * sign);
+        }
+    }
+
+    enum Query {
+        /// Adds a row, expecting the new optimal cost or `Infeasible`.
+        Add(Vec<Option<i64>>, Result<i64, Infeasible>),
+        /// Expects the inverse permutation.
+        Inv(Vec<usize>),
+    }
+
+    fn do_test_hungarian(maximize: bool, queries: Vec<Query>) {
+        let mut hungarian = Hungarian::new(maximize);
+        let mut rows = vec![];
+        for (qi, query) in queries.into_iter().enumerate() {
+            match query {
+                Query::Add(row, expected) => {
+                    rows.push(row.clone());
+                    let m = row.len();
+                    let best = naive(&rows, 0, &mut vec![false; m], maximize);
+                    assert_eq!(best.ok_or(Infeasible), expected, "query {qi}");
+                    let result = hungarian.try_add_row(row);
+                    assert_eq!(result.map(|()| hungarian.cost()), expected, "query {qi}");
+                    if expected.is_err() {
+                        rows.pop();
+                    }
+                }
+                Query::Inv(inv) => {
+                    assert_eq!(hungarian.inverse_permutation(), inv, "query {qi}");
+                }
+            }
+            assert_eq!(hungarian.height(), rows.len(), "query {qi}");
+            check(&rows, &hungarian);
+        }
+    }
+
+    #[test]
+    fn test_hungarian_negative() {
+        // The diagonal takes the minimum of every row.
+        do_test_hungarian(
+            false,
+            vec![
+                Query::Add(vec![Some(-3), Some(2), Some(0)], Ok(-3)),
+                Query::Inv(vec![0, usize::MAX, usize::MAX]),
+                Query::Add(vec![Some(1), Some(-5), Some(4)], Ok(-8)),
+                Query::Add(vec![Some(2), Some(3), Some(-1)], Ok(-9)),
+                Query::Inv(vec![0, 1, 2]),
+            ],
+        );
+        // Row 0 gives up its cheaper column to row 1.
+        do_test_hungarian(
+            false,
+            vec![
+                Query::Add(vec![Some(0), Some(1)], Ok(0)),
+                Query::Inv(vec![0, usize::MAX]),
+                Query::Add(vec![Some(-4), Some(-1)], Ok(-3)),
+                Query::Inv(vec![1, 0]),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_hungarian_maximize() {
+        // The same matrix as the first negative case,
+        // where the best permutation is (1, 2, 0).
+        do_test_hungarian(
+            true,
+            vec![
+                Query::Add(vec![Some(-3), Some(2), Some(0)], Ok(2)),
+                Query::Inv(vec![usize::MAX, 0, usize::MAX]),
+                Query::Add(vec![Some(1), Some(-5), Some(4)], Ok(6)),
+                Query::Inv(vec![usize::MAX, 0, 1]),
+                Query::Add(vec![Some(2), Some(3), Some(-1)], Ok(8)),
+                Query::Inv(vec![2, 0, 1]),
+            ],
+        );
+        let hungarian = Hungarian::solve_max(vec![vec![-7, -2], vec![-1, -9]]);
+        assert_eq!(hungarian.cost(), -3);
+        assert_eq!(hungarian.permutation(), [1, 0]);
+    }
+
+    #[test]
+    fn test_hungarian_forbidden() {
+        do_test_hungarian(
+            false,
+            vec![
+                Query::Add(vec![Some(1), None, None], Ok(1)),
+                // Both rows need column 0, so the row is discarded.
+                Query::Add(vec![Some(0), None, None], Err(Infeasible)),
+                Query::Inv(vec![0, usize::MAX, usize::MAX]),
+                Query::Add(vec![Some(-5), Some(7), None], Ok(8)),
+                Query::Inv(vec![0, 1, usize::MAX]),
+                // Column 1 is taken by the previous row.
+                Query::Add(vec![None, Some(-2), None], Err(Infeasible)),
+                Query::Inv(vec![0, 1, usize::MAX]),
+                Query::Add(vec![Some(-4), Some(-2), Some(9)], Ok(17)),
+                Query::Inv(vec![0, 1, 2]),
+            ],
+        );
+        do_test_hungarian(
+            true,
+            vec![
+                Query::Add(vec![None, Some(3), Some(-6)], Ok(3)),
+                // Row 0 moves to its negative cell to free column 1.
+                Query::Add(vec![Some(-2), Some(8), None], Ok(2)),
+                Query::Inv(vec![usize::MAX, 1, 0]),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_try_solve() {
+        let mat = vec![vec![None, Some(3)], vec![Some(-2), Some(-8)]];
+        let hungarian = Hungarian::try_solve(mat, false).unwrap();
+        assert_eq!(hungarian.cost(), 1);
+        assert_eq!(hungarian.permutation(), [1, 0]);
+        let mat = vec![
+            vec![Some(1), None, Some(2)],
+            vec![Some(4), None, None],
+            vec![Some(0), None, None],
+        ];
+        assert!(Hungarian::try_solve(mat.clone(), false).is_err());
+        assert!(Hungarian::try_solve(mat, true).is_err());
     }
 }
@@ -0,0 +1,597 @@
+//! Low-links of undirected graphs: bridges, articulation points,
+//! 2-edge-connected and biconnected components.
+//!
+//! Uses [`Graph`], which must be built with [`Graph::bidirect`] only,
+//! so that edges `2i` and `2i+1` are the two directions of the same edge.
+//! An edge is identified by the (even) index returned by `bidirect`,
+//! so parallel edges are told apart. Loops are allowed.
+//!
+//! # Example
+//! ```
+//! # extern crate edge_list;
+//! # use lowlink::make_lowlink;
+//! # use edge_list::UnweightedGraph;
+//! // 0 - 1       3
+//! //  \ /        ‖
+//! //   2 ------- 4 --- 5
+//! let mut graph = UnweightedGraph::new(6);
+//! for (a, b) in [(0, 1), (1, 2), (2, 0), (2, 4), (3, 4), (3, 4), (4, 5)] {
+//!     graph.bidirect(a, b, ());
+//! }
+//! let lowlink = make_lowlink(&graph);
+//! assert_eq!(lowlink.bridges(), vec![6, 12]);
+//! assert_eq!(lowlink.articulation_points(), vec![2, 4]);
+//!
+//! let tecc = lowlink.two_edge_cc();
+//! assert_eq!(tecc.count, 3);
+//! assert_eq!(tecc.bridge_tree(&graph).edge_count(), 2 * 2);
+//!
+//! let bcc = lowlink.bcc(&graph);
+//! assert_eq!(bcc.count, 4);
+//! assert_eq!(bcc.edge_indices[8], bcc.edge_indices[10]);
+//! assert_eq!(bcc.block_cut_tree().vertex_count(), 6 + 4);
+//! ```
+//!
+//! - [LC Two-Edge-Connected Components](https://judge.yosupo.jp/problem/two_edge_connected_components)
+//! ```ignore
+//! let (n, m) = (oj.usize(), oj.usize());
+//! let mut graph = UnweightedGraph::new(n);
+//! for _ in 0..m {
+//!     graph.bidirect(oj.usize(), oj.usize(), ());
+//! }
+//! let comps = make_lowlink(&graph).two_edge_cc().components();
+//! oj.write(comps.len()).ln();
+//! for comp in comps {
+//!     oj.write(comp.len()).sp();
+//!     for v in comp { oj.write(v).sp(); }
+//!     oj.ln();
+//! }
+//! ```
+//! - [LC Biconnected Components](https://judge.yosupo.jp/problem/biconnected_components)
+//! ```ignore
+//! let (n, m) = (oj.usize(), oj.usize());
+//! let mut graph = UnweightedGraph::new(n);
+//! for _ in 0..m {
+//!     graph.bidirect(oj.usize(), oj.usize(), ());
+//! }
+//! let bcc = make_lowlink(&graph).bcc(&graph);
+//! oj.write(bcc.count).ln();
+//! for block in bcc.vertices {
+//!     oj.write(block.len()).sp();
+//!     for v in block { oj.write(v).sp(); }
+//!     oj.ln();
+//! }
+//! ```
+
+extern crate edge_list;
+
+use edge_list::{Graph, UnweightedGraph};
+use std::convert::TryFrom;
+
+const NOPE: u32 = u32::MAX;
+
+/// The DFS forest of an undirected graph, with low-links.
+///
+/// In the method descriptions, let `V` and `E` be
+/// the number of vertices and edges.
+#[derive(Clone, Debug)]
+pub struct LowLink {
+    /// `ord[v]` is the preorder index of `v` in the DFS.
+    pub ord: Vec<u32>,
+    /// `low[v]` is the minimum `ord` reachable from `v` by going
+    /// down the DFS tree, then along at most one other edge.
+    pub low: Vec<u32>,
+    /// `parent[v]` is the parent of `v` in the DFS forest,
+    /// with the index of the edge between them,
+    /// or `None` if `v` is a root.
+    pub parent: Vec<Option<(usize, usize)>>,
+    /// The vertices in preorder.
+    order: Vec<u32>,
+}
+
+/// Returns the low-links of `graph`.
+///
+/// 🕒 `O(V+E)`.
+#[must_use]
+pub fn make_lowlink<T>(graph: &Graph<T>) -> LowLink {
+    let n = graph.vertex_count();
+    let edges = graph.edges();
+    let mut ord = vec![NOPE; n];
+    let mut low = vec![NOPE; n];
+    let mut parent = vec![None; n];
+    let mut order = Vec::with_capacity(n);
+    for root in 0..n {
+        if ord[root] != NOPE {
+            continue;
+        }
+        ord[root] = u32::try_from(order.len()).unwrap();
+        low[root] = ord[root];
+        order.push(u32::try_from(root).unwrap());
+        let mut calls = vec![(root, graph.edge_indices_from(root))];
+        while let Some((v, indices)) = calls.last_mut() {
+            let v = *v;
+            if let Some(e) = indices.next() {
+                // Skip only the tree edge itself, not its parallel edges.
+                if matches!(parent[v], Some((_, pe)) if pe == e & !1) {
+                    continue;
+                }
+                let w = edges[e].to as usize;
+                if ord[w] == NOPE {
+                    ord[w] = u32::try_from(order.len()).unwrap();
+                    low[w] = ord[w];
+                    order.push(edges[e].to);
+                    parent[w] = Some((v, e & !1));
+                    calls.push((w, graph.edge_indices_from(w)));
+                } else {
+                    low[v] = low[v].min(ord[w]);
+                }
+                continue;
+            }
+            calls.pop();
+            if let Some((p, _)) = parent[v] {
+                low[p] = low[p].min(low[v]);
+            }
+        }
+    }
+    LowLink {
+        ord,
+        low,
+        parent,
+        order,
+    }
+}
+
+impl LowLink {
+    /// Returns `V`.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.ord.len()
+    }
+
+    /// Returns whether `V == 0`.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.ord.is_empty()
+    }
+
+    /// Returns the indices of the bridges, in increasing order.
+    ///
+    /// 🕒 `O(V log V)`.
+    #[must_use]
+    pub fn bridges(&self) -> Vec<usize> {
+        let mut bridges = (0..self.len())
+            .filter_map(|v| {
+                let (p, e) = self.parent[v]?;
+                (self.low[v] > self.ord[p]).then_some(e)
+            })
+            .collect::<Vec<_>>();
+        bridges.sort_unstable();
+        bridges
+    }
+
+    /// Returns the articulation points, in increasing order.
+    ///
+    /// 🕒 `O(V)`.
+    #[must_use]
+    pub fn articulation_points(&self) -> Vec<usize> {
+        let n = self.len();
+        let mut children = vec![0; n];
+        let mut cut = vec![false; n];
+        for v in 0..n {
+            if let Some((p, _)) = self.parent[v] {
+                children[p] += 1;
+                if self.parent[p].is_some() && self.low[v] >= self.ord[p] {
+                    cut[p] = true;
+                }
+            }
+        }
+        (0..n)
+            .filter(|&v| cut[v] || (self.parent[v].is_none() && children[v] >= 2))
+            .collect()
+    }
+
+    /// Returns the 2-edge-connected components,
+    /// i.e. the components after removing the bridges.
+    ///
+    /// 🕒 `O(V)`.
+    #[must_use]
+    pub fn two_edge_cc(&self) -> TwoEdgeCc {
+        let mut indices = vec![NOPE; self.len()];
+        let mut count = 0;
+        for &v in &self.order {
+            let v = v as usize;
+            indices[v] = match self.parent[v] {
+                Some((p, _)) if self.low[v] <= self.ord[p] => indices[p],
+                _ => {
+                    count += 1;
+                    count - 1
+                }
+            };
+        }
+        TwoEdgeCc {
+            count: count as usize,
+            indices,
+        }
+    }
+
+    /// Returns the biconnected components of `graph`, which must
+    /// be the graph these low-links were computed from.
+    ///
+    /// 🕒 `O(V+E)`.
+    #[must_use]
+    pub fn bcc<T>(&self, graph: &Graph<T>) -> Bcc {
+        let n = self.len();
+        let mut has_child = vec![false; n];
+        for &(p, _) in self.parent.iter().flatten() {
+            has_child[p] = true;
+        }
+        // `block[v]` is the block of the tree edge above `v`,
+        // or of `v` alone if it is isolated.
+        let mut block = vec![NOPE; n];
+        let mut vertices: Vec<Vec<usize>> = vec![];
+        for &v in &self.order {
+            let v = v as usize;
+            match self.parent[v] {
+                None if has_child[v] => continue,
+                Some((p, _)) if self.low[v] < self.ord[p] => {
+                    block[v] = block[p];
+                    vertices[block[p] as usize].push(v);
+                    continue;
+                }
+                None => vertices.push(vec![v]),
+                Some((p, _)) => vertices.push(vec![p, v]),
+            }
+            block[v] = u32::try_from(vertices.len() - 1).unwrap();
+        }
+
+        // An edge is in the block of its lower endpoint,
+        // either through the tree edge or as a back edge.
+        let edges = graph.edges();
+        let mut edge_indices = vec![NOPE; edges.len()];
+        for v in 0..n {
+            for e in graph.edge_indices_from(v) {
+                let w = edges[e].to as usize;
+                if w != v {
+                    let lower = if self.ord[v] < self.ord[w] { w } else { v };
+                    edge_indices[e] = block[lower];
+                }
+            }
+        }
+        Bcc {
+            count: vertices.len(),
+            edge_indices,
+            vertices,
+        }
+    }
+}
+
+/// The 2-edge-connected components.
+#[derive(Clone, Debug)]
+pub struct TwoEdgeCc {
+    /// The number of components.
+    pub count: usize,
+    /// `indices[v]` is the index of the component
+    /// that contains vertex `v`.
+    pub indices: Vec<u32>,
+}
+
+impl TwoEdgeCc {
+    /// Returns the vertices of each component.
+    #[must_use]
+    pub fn components(&self) -> Vec<Vec<usize>> {
+        let mut comps = vec![vec![]; self.count];
+        for (v, idx) in self.indices.iter().enumerate() {
+            comps[*idx as usize].push(v);
+        }
+        comps
+    }
+
+    /// Returns the bridge tree of `graph`, where vertex `c` is
+    /// component `c`, and each bridge connects its two components.
+    /// It is a forest, with a tree per connected component of `graph`.
+    ///
+    /// 🕒 `O(V+E)`.
+    #[must_use]
+    pub fn bridge_tree<T>(&self, graph: &Graph<T>) -> UnweightedGraph {
+        let mut tree = UnweightedGraph::new(self.count);
+        for pair in graph.edges().chunks_exact(2) {
+            let (a, b) = (
+                self.indices[pair[1].to as usize],
+                self.indices[pair[0].to as usize],
+            );
+            if a != b {
+                tree.bidirect(a as usize, b as usize, ());
+            }
+        }
+        tree
+    }
+}
+
+/// The biconnected components, or blocks.
+///
+/// Every edge except loops is in exactly one block. A vertex is in
+/// one block per incident bridge or cycle, so the cut vertices are in
+/// several blocks, and an isolated vertex forms a block alone.
+#[derive(Clone, Debug)]
+pub struct Bcc {
+    /// The number of blocks.
+    pub count: usize,
+    /// `edge_indices[e]` is the index of the block that contains
+    /// edge `e`, in either direction, or `u32::MAX` if `e` is a loop.
+    pub edge_indices: Vec<u32>,
+    /// `vertices[b]` is the vertices of block `b`.
+    pub vertices: Vec<Vec<usize>>,
+}
+
+impl Bcc {
+    /// Returns the indices of the edges of each block.
+    #[must_use]
+    pub fn edges(&self) -> Vec<Vec<usize>> {
+        let mut blocks = vec![vec![]; self.count];
+        for (e, &b) in self.edge_indices.iter().enumerate().step_by(2) {
+            if b != NOPE {
+                blocks[b as usize].push(e);
+            }
+        }
+        blocks
+    }
+
+    /// Returns the block-cut tree, where vertex `v < V` is vertex `v`,
+    /// and vertex `V + b` is block `b`, connected to its vertices.
+    /// It is a forest, where the cut vertices are the vertices `v < V`
+    /// with at least two neighbors.
+    ///
+    /// 🕒 `O(V+E)`.
+    #[must_use]
+    pub fn block_cut_tree(&self) -> UnweightedGraph {
+        let n = self.vertices.iter().flatten().max().map_or(0, |&v| v + 1);
+        let mut tree = UnweightedGraph::new(n + self.count);
+        for (b, block) in self.vertices.iter().enumerate() {
+            for &v in block {
+                tree.bidirect(v, n + b, ());
+            }
+        }
+        tree
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Returns small graphs, with loops, parallel edges,
+    /// isolated vertices and several components.
+    fn graphs() -> Vec<(usize, Vec<(usize, usize)>)> {
+        vec![
+            // Two triangles joined by a bridge, with a doubled
+            // pendant edge, a bridge to a loop, and an isolated vertex.
+            (
+                9,
+                vec![
+                    (0, 1),
+                    (1, 2),
+                    (2, 0),
+                    (2, 3),
+                    (3, 4),
+                    (4, 5),
+                    (5, 3),
+                    (5, 6),
+                    (6, 6),
+                    (4, 7),
+                    (7, 4),
+                ],
+            ),
+            // Two triangles sharing vertex 2, and a separate edge.
+            (
+                7,
+                vec![(0, 1), (1, 2), (2, 0), (2, 3), (3, 4), (4, 2), (5, 6)],
+            ),
+            // A cycle with a chord.
+            (
+                6,
+                vec![(0, 1), (1, 2), (2, 3), (3, 4), (4, 5), (5, 0), (1, 4)],
+            ),
+            // A tree.
+            (6, vec![(0, 1), (0, 2), (2, 3), (2, 4), (4, 5)]),
+            (1, vec![]),
+            (3, vec![]),
+            (2, vec![(0, 0)]),
+        ]
+    }
+
+    fn graph(n: usize, pairs: &[(usize, usize)]) -> UnweightedGraph {
+        let mut graph = UnweightedGraph::new(n);
+        for &(a, b) in pairs {
+            graph.bidirect(a, b, ());
+        }
+        graph
+    }
+
+    /// Returns the connected component labels,
+    /// without the `i`-th edges where `skip(i, edge)`.
+    fn labels(
+        n: usize,
+        pairs: &[(usize, usize)],
+        skip: impl Fn(usize, (usize, usize)) -> bool,
+    ) -> Vec<usize> {
+        let mut label = (0..n).collect::<Vec<_>>();
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (i, &(a, b)) in pairs.iter().enumerate() {
+                if !skip(i, (a, b)) && label[a] != label[b] {
+                    let min = label[a].min(label[b]);
+                    label[a] = min;
+                    label[b] = min;
+                    changed = true;
+                }
+            }
+        }
+        label
+    }
+
+    /// Returns the number of connected components,
+    /// without the `i`-th edges where `skip(i, edge)`.
+    fn count(
+        n: usize,
+        pairs: &[(usize, usize)],
+        skip: impl Fn(usize, (usize, usize)) -> bool,
+    ) -> usize {
+        let label = labels(n, pairs, skip);
+        (0..n).filter(|&v| label[v] == v).count()
+    }
+
+    /// Returns the edges of a forest as pairs.
+    fn tree_pairs(tree: &UnweightedGraph) -> Vec<(usize, usize)> {
+        let edges = tree.edges();
+        (0..edges.len())
+            .step_by(2)
+            .map(|e| (edges[e + 1].to as usize, edges[e].to as usize))
+            .collect()
+    }
+
+    #[test]
+    fn test_lowlink() {
+        for (n, pairs) in graphs() {
+            let graph = graph(n, &pairs);
+            let m = pairs.len();
+            let whole = count(n, &pairs, |_, _| false);
+            let lowlink = make_lowlink(&graph);
+            assert_eq!(lowlink.len(), n);
+
+            let bridges = (0..m)
+                .filter(|&i| count(n, &pairs, |j, _| i == j) > whole)
+                .map(|i| 2 * i)
+                .collect::<Vec<_>>();
+            assert_eq!(lowlink.bridges(), bridges);
+
+            // Removing `v` leaves it alone.
+            let points = (0..n)
+                .filter(|&v| count(n, &pairs, |_, (a, b)| a == v || b == v) > whole + 1)
+                .collect::<Vec<_>>();
+            assert_eq!(lowlink.articulation_points(), points);
+
+            let tecc = lowlink.two_edge_cc();
+            let is_bridge = |i: usize, _: (usize, usize)| bridges.contains(&(2 * i));
+            assert_eq!(tecc.count, count(n, &pairs, is_bridge));
+            for (i, &(a, b)) in pairs.iter().enumerate() {
+                let same = tecc.indices[a] == tecc.indices[b];
+                assert_eq!(same, !is_bridge(i, (a, b)));
+            }
+            let tree = tecc.bridge_tree(&graph);
+            assert_eq!(tree.vertex_count(), tecc.count);
+            assert_eq!(tree.edge_count(), bridges.len() * 2);
+            assert_eq!(count(tecc.count, &tree_pairs(&tree), |_, _| false), whole);
+        }
+        let (n, pairs) = &graphs()[0];
+        let lowlink = make_lowlink(&graph(*n, pairs));
+        assert_eq!(lowlink.bridges(), [6, 14]);
+        assert_eq!(lowlink.articulation_points(), [2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn test_bcc() {
+        for (n, pairs) in graphs() {
+            let graph = graph(n, &pairs);
+            let m = pairs.len();
+            let lowlink = make_lowlink(&graph);
+            let bcc = lowlink.bcc(&graph);
+
+            // Two edges sharing a vertex `x` are in the same block
+            // iff their other ends are connected without `x`.
+            let mut block = (0..m).collect::<Vec<_>>();
+            let mut changed = true;
+            while changed {
+                changed = false;
+                for i in 0..m {
+                    for j in 0..m {
+                        let (e, f) = (pairs[i], pairs[j]);
+                        if e.0 == e.1 || f.0 == f.1 || block[i] <= block[j] {
+                            continue;
+                        }
+                        let ends = [(e.0, e.1), (e.1, e.0)];
+                        let same = ends.iter().any(|&(x, y)| {
+                            let other = if f.0 == x { f.1 } else { f.0 };
+                            let label = labels(n, &pairs, |_, (a, b)| a == x || b == x);
+                            (f.0 == x || f.1 == x) && label[y] == label[other]
+                        });
+                        if same {
+                            block[i] = block[j];
+                            changed = true;
+                        }
+                    }
+                }
+            }
+            for (i, &(a, b)) in pairs.iter().enumerate() {
+                let got = bcc.edge_indices[2 * i];
+                assert_eq!(got, bcc.edge_indices[2 * i + 1]);
+                if a == b {
+                    assert_eq!(got, NOPE);
+                    continue;
+                }
+                for (j, &(c, d)) in pairs.iter().enumerate() {
+                    if c != d {
+                        let same = got == bcc.edge_indices[2 * j];
+                        assert_eq!(same, block[i] == block[j]);
+                    }
+                }
+            }
+
+            let lonely = (0..n)
+                .filter(|&v| graph.neighbors(v).all(|(w, ())| w == v))
+                .count();
+            let mut classes = (0..m)
+                .filter(|&i| pairs[i].0 != pairs[i].1)
+                .map(|i| block[i])
+                .collect::<Vec<_>>();
+            classes.sort_unstable();
+            classes.dedup();
+            assert_eq!(bcc.count, classes.len() + lonely);
+            for (vertices, edges) in bcc.vertices.iter().zip(bcc.edges()) {
+                let mut want = edges
+                    .iter()
+                    .flat_map(|&e| [pairs[e / 2].0, pairs[e / 2].1])
+                    .collect::<Vec<_>>();
+                want.sort_unstable();
+                want.dedup();
+                let mut got = vertices.clone();
+                got.sort_unstable();
+                if want.is_empty() {
+                    assert_eq!(got.len(), 1);
+                } else {
+                    assert_eq!(got, want);
+                }
+            }
+
+            let whole = count(n, &pairs, |_, _| false);
+            let tree = bcc.block_cut_tree();
+            let size = n + bcc.count;
+            assert_eq!(tree.vertex_count(), size);
+            assert_eq!(count(size, &tree_pairs(&tree), |_, _| false), whole);
+            assert_eq!(tree.edge_count() / 2, size - whole);
+            let points = (0..n)
+                .filter(|&v| tree.neighbors(v).count() >= 2)
+                .collect::<Vec<_>>();
+            assert_eq!(lowlink.articulation_points(), points);
+        }
+    }
+
+    #[test]
+    fn test_line() {
+        let n = 1_000_000;
+        let mut graph = UnweightedGraph::new(n);
+        for v in 1..n {
+            graph.bidirect(v - 1, v, ());
+        }
+        let lowlink = make_lowlink(&graph);
+        assert_eq!(lowlink.bridges().len(), n - 1);
+        assert_eq!(lowlink.articulation_points().len(), n - 2);
+        assert_eq!(lowlink.bcc(&graph).count, n - 1);
+
+        graph.bidirect(n - 1, 0, ());
+        let lowlink = make_lowlink(&graph);
+        assert!(lowlink.bridges().is_empty());
+        assert_eq!(lowlink.two_edge_cc().count, 1);
+        assert_eq!(lowlink.bcc(&graph).count, 1);
+    }
+}
@@ -0,0 +1,514 @@
+//! Minimum-cost maximum flow, by successive shortest paths.
+//!
+//! # Usage
+//! The edges are stored as in `dinic`, where each edge added by
+//! [`MinCostFlow::add_edge`] is paired with its reverse.
+//! The capacities and costs are parametrized over [`Cost`],
+//! which is implemented for the signed primitive integers.
+//!
+//! Each shortest path is found by Dijkstra with Johnson potentials.
+//! The initial potentials are found by Bellman-Ford if any
+//! residual edge has a negative cost, and are zero otherwise.
+//! They are kept for the later calls with the same source,
+//! until an edge is added.
+//! The costs may be negative, but there must be no negative cycle.
+//!
+//! The minimum cost is a convex piecewise linear function of the
+//! flow amount. [`MinCostFlow::slope`] returns its breakpoints.
+//!
+//! # Example
+//! ```
+//! # use mcmf::MinCostFlow;
+//! let mut graph = MinCostFlow::new(4);
+//! graph.add_edge(0, 1, 2, 1i64);
+//! graph.add_edge(0, 2, 1, 2);
+//! graph.add_edge(1, 3, 1, 3);
+//! graph.add_edge(2, 3, 2, 1);
+//! let e = graph.add_edge(1, 2, 1, -1);
+//! // Unit costs 1 - 1 + 1, then 2 + 1, then 1 + 3.
+//! assert_eq!(graph.clone().slope(0, 3, i64::MAX), vec![(0, 0), (1, 1), (2, 4), (3, 8)]);
+//! assert_eq!(graph.flow(0, 3, 2), (2, 4));
+//! assert_eq!(graph.edge(e).flow, 1);
+//! ```
+//!
+//! - [AOJ GRL_6_B Minimum Cost Flow](https://onlinejudge.u-aizu.ac.jp/problems/GRL_6_B)
+//! ```ignore
+//! let (n, m, f) = (oj.usize(), oj.usize(), oj.i64());
+//! let mut graph = MinCostFlow::new(n);
+//! for _ in 0..m {
+//!     graph.add_edge(oj.usize(), oj.usize(), oj.i64(), oj.i64());
+//! }
+//! match graph.flow(0, n - 1, f) {
+//!     (flow, cost) if flow == f => oj.write(cost).ln(),
+//!     _ => oj.write(-1).ln(),
+//! };
+//! ```
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::convert::TryFrom;
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+/// Signed integer usable as a capacity and a cost in [`MinCostFlow`].
+pub trait Cost:
+    Copy
+    + Ord
+    + Default
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Neg<Output = Self>
+    + AddAssign
+    + SubAssign
+{
+    /// The maximum value, used as infinity.
+    const MAX: Self;
+}
+
+macro_rules! impl_cost {
+    ($($t:ty)*) => {$(
+        impl Cost for $t {
+            const MAX: Self = <$t>::MAX;
+        }
+    )*};
+}
+impl_cost!(i8 i16 i32 i64 i128 isize);
+
+const NOPE: u32 = u32::MAX;
+
+/// A directed edge of the residual graph.
+#[derive(Clone, Debug)]
+struct Arc<T> {
+    to: u32,
+    prev: u32,
+    /// The residual capacity.
+    cap: T,
+    cost: T,
+}
+
+/// An edge added by [`MinCostFlow::add_edge`], with its flow.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FlowEdge<T> {
+    /// The start vertex.
+    pub from: usize,
+    /// The end vertex.
+    pub to: usize,
+    /// The capacity.
+    pub cap: T,
+    /// The current flow, in `0..=cap`.
+    pub flow: T,
+    /// The cost per unit of flow.
+    pub cost: T,
+}
+
+/// A flow network with costs.
+///
+/// In the method descriptions, let `n` and `m` be
+/// the number of vertices and edges, and `F` be
+/// the amount of flow.
+#[derive(Clone, Debug)]
+pub struct MinCostFlow<T> {
+    heads: Vec<u32>,
+    /// Edge `i` is `arcs[2i]`, and its reverse is `arcs[2i+1]`.
+    arcs: Vec<Arc<T>>,
+    /// The source of the last call and the potentials after it,
+    /// or `None` if an edge has been added since.
+    potential: Option<(usize, Vec<T>)>,
+}
+
+impl<T: Cost> MinCostFlow<T> {
+    /// Returns an edge-less network with `n` vertices.
+    ///
+    /// ⚠️ Panics if `n >= u32::MAX`.
+    #[must_use]
+    pub fn new(n: usize) -> Self {
+        assert!(n < NOPE as usize, "why do you need {} vertices?!", n);
+        Self {
+            heads: vec![NOPE; n],
+            arcs: vec![],
+            potential: None,
+        }
+    }
+
+    /// Returns `n`.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.heads.len()
+    }
+
+    /// Returns whether `n == 0`.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.heads.is_empty()
+    }
+
+    /// Returns `m`.
+    #[must_use]
+    pub const fn edge_count(&self) -> usize {
+        self.arcs.len() / 2
+    }
+
+    fn push_arc(&mut self, from: usize, to: usize, cap: T, cost: T) {
+        let index = u32::try_from(self.arcs.len()).unwrap();
+        let prev = std::mem::replace(&mut self.heads[from], index);
+        self.arcs.push(Arc {
+            to: u32::try_from(to).unwrap(),
+            prev,
+            cap,
+            cost,
+        });
+    }
+
+    /// Adds an edge from `from` to `to` with capacity `cap` and
+    /// cost `cost` per unit, and returns its index, counting from `0`.
+    ///
+    /// ⚠️ Panics if `from >= n`, `to >= n`, or `cap < 0`.
+    pub fn add_edge(&mut self, from: usize, to: usize, cap: T, cost: T) -> usize {
+        assert!(from < self.len() && to < self.len(), "invalid vertex");
+        assert!(cap >= T::default(), "negative capacity");
+        self.push_arc(from, to, cap, cost);
+        self.push_arc(to, from, T::default(), -cost);
+        self.potential = None;
+        self.edge_count() - 1
+    }
+
+    /// Returns edge `i` with its current flow.
+    ///
+    /// ⚠️ Panics if `i >= m`.
+    #[must_use]
+    pub fn edge(&self, i: usize) -> FlowEdge<T> {
+        let (arc, rev) = (&self.arcs[2 * i], &self.arcs[2 * i + 1]);
+        FlowEdge {
+            from: rev.to as usize,
+            to: arc.to as usize,
+            cap: arc.cap + rev.cap,
+            flow: rev.cap,
+            cost: arc.cost,
+        }
+    }
+
+    /// Returns all edges with their current flows, by index.
+    #[must_use]
+    pub fn edges(&self) -> Vec<FlowEdge<T>> {
+        (0..self.edge_count()).map(|i| self.edge(i)).collect()
+    }
+
+    /// Returns the residual arcs from `v`, with their indices.
+    fn arcs_from(&self, v: usize) -> impl Iterator<Item = (usize, &Arc<T>)> + '_ {
+        let mut e = self.heads[v];
+        std::iter::from_fn(move || {
+            let arc = self.arcs.get(e as usize)?;
+            let cur = std::mem::replace(&mut e, arc.prev);
+            Some((cur as usize, arc))
+        })
+    }
+
+    /// Returns the distances from `s` in the residual graph,
+    /// or zeros if no residual arc has a negative cost.
+    fn initial_potential(&self, s: usize) -> Vec<T> {
+        let zero = T::default();
+        let n = self.len();
+        if self
+            .arcs
+            .iter()
+            .all(|arc| arc.cap == zero || arc.cost >= zero)
+        {
+            return vec![zero; n];
+        }
+        let mut dist = vec![None; n];
+        dist[s] = Some(zero);
+        for round in 0.. {
+            let mut changed = false;
+            for v in 0..n {
+                let Some(d) = dist[v] else {
+                    continue;
+                };
+                for (_, arc) in self.arcs_from(v) {
+                    let nd = d + arc.cost;
+                    let to = arc.to as usize;
+                    if arc.cap > zero && dist[to].iter().all(|&old| nd < old) {
+                        dist[to] = Some(nd);
+                        changed = true;
+                    }
+                }
+            }
+            if !changed {
+                break;
+            }
+            assert!(round < n, "negative cycle");
+        }
+        dist.into_iter().map(|d| d.unwrap_or(zero)).collect()
+    }
+
+    /// Computes the distances from `s` in the residual graph
+    /// with the reduced costs, which are nonnegative,
+    /// and the last arc of a shortest path to each vertex.
+    fn dijkstra(&self, s: usize, potential: &[T], dist: &mut [T], prev_arc: &mut [u32]) {
+        let zero = T::default();
+        dist.fill(T::MAX);
+        dist[s] = zero;
+        let mut heap = BinaryHeap::from([Reverse((zero, s))]);
+        while let Some(Reverse((d, v))) = heap.pop() {
+            if dist[v] < d {
+                continue;
+            }
+            for (e, arc) in self.arcs_from(v) {
+                let to = arc.to as usize;
+                let nd = d + arc.cost + potential[v] - potential[to];
+                if arc.cap > zero && nd < dist[to] {
+                    dist[to] = nd;
+                    prev_arc[to] = u32::try_from(e).unwrap();
+                    heap.push(Reverse((nd, to)));
+                }
+            }
+        }
+    }
+
+    /// Pushes up to `limit` along the path to `t` given by
+    /// `prev_arc`, and returns the amount pushed.
+    fn augment(&mut self, t: usize, prev_arc: &[u32], limit: T) -> T {
+        let path = std::iter::successors(Some(t), |&v| {
+            let e = prev_arc[v];
+            (e != NOPE).then(|| self.arcs[e as usize ^ 1].to as usize)
+        });
+        let arcs = path
+            .map(|v| prev_arc[v] as usize)
+            .take_while(|&e| e != NOPE as usize)
+            .collect::<Vec<_>>();
+        let push = arcs
+            .iter()
+            .fold(limit, |push, &e| push.min(self.arcs[e].cap));
+        for e in arcs {
+            self.arcs[e].cap -= push;
+            self.arcs[e ^ 1].cap += push;
+        }
+        push
+    }
+
+    /// Pushes flow from `s` to `t` along shortest paths, until the flow
+    /// is maximum or reaches `limit`, on top of the current flow.
+    /// Returns the breakpoints `(flow, cost)` of the minimum cost as a
+    /// function of the pushed amount, starting with `(0, 0)`.
+    /// Consecutive breakpoints have increasing slopes,
+    /// and the cost between them is linear.
+    ///
+    /// 🕒 `O(F m log m)`, plus `O(nm)` if there are negative costs
+    /// and this is the first call from `s` since adding an edge.
+    ///
+    /// ⚠️ Panics if `s >= n`, `t >= n`, `s == t`,
+    /// or there is a negative cycle reachable from `s`.
+    pub fn slope(&mut self, s: usize, t: usize, limit: T) -> Vec<(T, T)> {
+        assert_ne!(s, t, "the source and the sink must differ");
+        let zero = T::default();
+        let mut potential = match self.potential.take() {
+            Some((source, potential)) if source == s => potential,
+            _ => self.initial_potential(s),
+        };
+        let mut dist = vec![T::MAX; self.len()];
+        let mut prev_arc = vec![NOPE; self.len()];
+        let mut result = vec![(zero, zero)];
+        let (mut flow, mut cost) = (zero, zero);
+        let mut prev_unit = None;
+        while flow < limit {
+            prev_arc.fill(NOPE);
+            self.dijkstra(s, &potential, &mut dist, &mut prev_arc);
+            if dist[t] == T::MAX {
+                break;
+            }
+            for (p, &d) in potential.iter_mut().zip(&dist) {
+                if d != T::MAX {
+                    *p += d;
+                }
+            }
+            let unit = potential[t] - potential[s];
+            let push = self.augment(t, &prev_arc, limit - flow);
+            flow += push;
+            cost += push * unit;
+            if prev_unit == Some(unit) {
+                result.pop();
+            }
+            result.push((flow, cost));
+            prev_unit = Some(unit);
+        }
+        self.potential = Some((s, potential));
+        result
+    }
+
+    /// Pushes the minimum-cost flow from `s` to `t`, until the flow
+    /// is maximum or reaches `limit`, on top of the current flow.
+    /// Returns the pushed amount and its cost.
+    /// For the maximum flow, let `limit` be `T::MAX`.
+    ///
+    /// 🕒 `O(F m log m)`, plus `O(nm)` if there are negative costs
+    /// and this is the first call from `s` since adding an edge.
+    ///
+    /// ⚠️ Panics if `s >= n`, `t >= n`, `s == t`,
+    /// or there is a negative cycle reachable from `s`.
+    pub fn flow(&mut self, s: usize, t: usize, limit: T) -> (T, T) {
+        *self.slope(s, t, limit).last().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Returns `min_cost[f]`, the minimum cost of a flow of amount `f`
+    /// from `s` to `t`, by trying every integer flow on the edges.
+    fn naive(n: usize, edges: &[(usize, usize, i64, i64)], s: usize, t: usize) -> Vec<i64> {
+        let mut min_cost: Vec<i64> = vec![];
+        let mut flows = vec![0; edges.len()];
+        loop {
+            let mut excess = vec![0; n];
+            let mut cost = 0;
+            for (&(a, b, _, c), &f) in edges.iter().zip(&flows) {
+                excess[a] -= f;
+                excess[b] += f;
+                cost += f * c;
+            }
+            if (0..n).all(|v| v == s || v == t || excess[v] == 0) && excess[t] >= 0 {
+                let f = usize::try_from(excess[t]).unwrap();
+                if min_cost.len() <= f {
+                    min_cost.resize(f + 1, i64::MAX);
+                }
+                min_cost[f] = min_cost[f].min(cost);
+            }
+            // Next assignment, like an odometer.
+            let Some(i) = (0..edges.len()).find(|&i| flows[i] < edges[i].2) else {
+                return min_cost;
+            };
+            flows[i] += 1;
+            flows[..i].fill(0);
+        }
+    }
+
+    /// Checks the capacity and conservation constraints,
+    /// and returns the flow into `t` and the total cost.
+    fn check_flow(graph: &MinCostFlow<i64>, s: usize, t: usize) -> (i64, i64) {
+        let mut excess = vec![0; graph.len()];
+        let mut cost = 0;
+        for e in graph.edges() {
+            assert!(0 <= e.flow && e.flow <= e.cap);
+            excess[e.from] -= e.flow;
+            excess[e.to] += e.flow;
+            cost += e.flow * e.cost;
+        }
+        for (v, &x) in excess.iter().enumerate() {
+            if v != s && v != t {
+                assert_eq!(x, 0);
+            }
+        }
+        (excess[t], cost)
+    }
+
+    /// Checks the slope, then the flow from `0` to `n - 1` of at
+    /// most `limit`, then re-augments up to the maximum.
+    fn do_test_mcmf(n: usize, edges: &[(usize, usize, i64, i64)], limit: i64) {
+        let (s, t) = (0, n - 1);
+        let want = naive(n, edges, s, t);
+
+        let mut graph = MinCostFlow::new(n);
+        for (i, &(a, b, cap, cost)) in edges.iter().enumerate() {
+            assert_eq!(graph.add_edge(a, b, cap, cost), i);
+        }
+        let slope = graph.clone().slope(s, t, i64::MAX);
+        assert_eq!(slope[0], (0, 0));
+        let max = usize::try_from(slope.last().unwrap().0).unwrap();
+        assert_eq!(max + 1, want.len());
+        for w in slope.windows(3) {
+            let (dx0, dy0) = (w[1].0 - w[0].0, w[1].1 - w[0].1);
+            let (dx1, dy1) = (w[2].0 - w[1].0, w[2].1 - w[1].1);
+            assert!(dy0 * dx1 < dy1 * dx0);
+        }
+        for w in slope.windows(2) {
+            let ((x0, y0), (x1, y1)) = (w[0], w[1]);
+            for x in x0..=x1 {
+                let y = y0 + (y1 - y0) / (x1 - x0) * (x - x0);
+                assert_eq!(want[usize::try_from(x).unwrap()], y);
+            }
+        }
+
+        let (flow, cost) = graph.flow(s, t, limit);
+        assert_eq!(flow, limit.min(i64::try_from(max).unwrap()));
+        assert_eq!(cost, want[usize::try_from(flow).unwrap()]);
+        assert_eq!(check_flow(&graph, s, t), (flow, cost));
+
+        // Re-augment up to the maximum.
+        let (more, more_cost) = graph.flow(s, t, i64::MAX);
+        assert_eq!(flow + more, i64::try_from(max).unwrap());
+        assert_eq!(cost + more_cost, want[max]);
+    }
+
+    #[test]
+    fn test_mcmf() {
+        // The cheapest path 0 -> 1 -> 2 -> 3 blocks both others,
+        // so the second unit of flow must cancel 1 -> 2.
+        let edges = [
+            (0, 1, 1, 1),
+            (1, 2, 1, 1),
+            (2, 3, 1, 1),
+            (0, 2, 1, 5),
+            (1, 3, 1, 5),
+        ];
+        for limit in 0..4 {
+            do_test_mcmf(4, &edges, limit);
+        }
+        // Parallel edges, a loop, and an edge into the source.
+        let edges = [
+            (0, 2, 1, 7),
+            (0, 2, 2, 3),
+            (1, 0, 2, 1),
+            (2, 2, 1, 0),
+            (0, 1, 1, 0),
+            (1, 2, 1, 2),
+        ];
+        do_test_mcmf(3, &edges, 1);
+        do_test_mcmf(3, &edges, 5);
+        // Negative costs, on edges to larger vertices only,
+        // so that there is no negative cycle.
+        let edges = [
+            (0, 1, 2, -3),
+            (1, 4, 1, 2),
+            (1, 2, 2, -4),
+            (2, 4, 1, 0),
+            (0, 3, 1, 1),
+            (3, 4, 2, -5),
+            (2, 3, 1, 4),
+        ];
+        for limit in 0..4 {
+            do_test_mcmf(5, &edges, limit);
+        }
+        do_test_mcmf(2, &[(1, 0, 1, 1)], 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "negative cycle")]
+    fn test_negative_cycle() {
+        let mut graph = MinCostFlow::new(3);
+        graph.add_edge(0, 1, 1, 1);
+        graph.add_edge(1, 0, 1, -2);
+        graph.add_edge(1, 2, 1, 1);
+        let _ = graph.flow(0, 2, 1);
+    }
+
+    #[test]
+    fn test_large() {
+        // Dense assignment with negative costs.
+        let k = 200;
+        let (s, t) = (2 * k, 2 * k + 1);
+        let mut graph = MinCostFlow::new(2 * k + 2);
+        for i in 0..k {
+            graph.add_edge(s, i, 1, 0);
+            graph.add_edge(k + i, t, 1, 0);
+            for j in 0..k {
+                // multiplicative hashing scatters the costs
+                let h = u32::try_from(i * k + j).unwrap().wrapping_mul(0x9e37_79b9) >> 8;
+                let cost = i64::from(h % 1_000_000) - 500_000;
+                graph.add_edge(i, k + j, 1, cost);
+            }
+        }
+        let (flow, cost) = graph.flow(s, t, i64::MAX);
+        assert_eq!(flow, i64::try_from(k).unwrap());
+        assert_eq!(check_flow(&graph, s, t), (flow, cost));
+    }
+}
@@ -31,7 +31,7 @@
 
 extern crate edge_list;
 
-use edge_list::Graph;
+use edge_list::{Graph, UnweightedGraph};
 use std::convert::TryInto;
 
 struct SccProcessor<'a, T> {
@@ -45,29 +45,44 @@ struct SccProcessor<'a, T> {
 }
 
 const NOPE: u32 = u32::MAX;
-impl<'a, T> SccProcessor<'a, T> {
-    fn dfs(&mut self, v: u32) {
-        let vs = v as usize;
-        self.up[vs] = self.cnt;
-        self.visit[vs] = self.cnt;
+impl<T> SccProcessor<'_, T> {
+    fn enter(&mut self, v: usize) {
+        self.up[v] = self.cnt;
+        self.visit[v] = self.cnt;
         self.cnt += 1;
-        self.stack.push(v);
-        for (nxt, _) in self.graph.neighbors(vs) {
-            if self.visit[nxt] == NOPE {
-                self.dfs(nxt.try_into().unwrap());
-                self.up[vs] = self.up[vs].min(self.up[nxt]);
-            } else if self.scx[nxt] == NOPE {
-                self.up[vs] = self.up[vs].min(self.visit[nxt]);
+        self.stack.push(v.try_into().unwrap());
+    }
+
+    /// Iterative DFS from `root`, with an explicit call stack
+    /// of vertices and their remaining neighbors.
+    fn dfs(&mut self, root: usize) {
+        let graph = self.graph;
+        self.enter(root);
+        let mut calls = vec![(root, graph.neighbors(root))];
+        while let Some((v, neighbors)) = calls.last_mut() {
+            let v = *v;
+            if let Some((nxt, _)) = neighbors.next() {
+                if self.visit[nxt] == NOPE {
+                    self.enter(nxt);
+                    calls.push((nxt, graph.neighbors(nxt)));
+                } else if self.scx[nxt] == NOPE {
+                    self.up[v] = self.up[v].min(self.visit[nxt]);
+                }
+                continue;
             }
-        }
-        if self.up[vs] == self.visit[vs] {
-            while let Some(t) = self.stack.pop() {
-                self.scx[t as usize] = self.sccnt;
-                if t == v {
-                    break;
+            calls.pop();
+            if self.up[v] == self.visit[v] {
+                while let Some(t) = self.stack.pop() {
+                    self.scx[t as usize] = self.sccnt;
+                    if t as usize == v {
+                        break;
+                    }
                 }
+                self.sccnt += 1;
+            }
+            if let Some(&(p, _)) = calls.last() {
+                self.up[p] = self.up[p].min(self.up[v]);
             }
-            self.sccnt += 1;
         }
     }
 }
@@ -84,6 +99,9 @@ pub struct Scc {
 }
 
 /// Returns the SCC of `graph`.
+///
+/// 🕒 `O(V+E)`.
+#[must_use]
 pub fn make_scc<T>(graph: &Graph<T>) -> Scc {
     let n = graph.vertex_count();
     let mut me = SccProcessor {
@@ -97,7 +115,7 @@ pub fn make_scc<T>(graph: &Graph<T>) -> Scc {
     };
     for v in 0..n {
         if me.visit[v] == NOPE {
-            me.dfs(v.try_into().unwrap());
+            me.dfs(v);
         }
     }
     let count = me.sccnt;
@@ -126,6 +144,7 @@ impl Scc {
     /// assert!(comps[0] == vec![0, 1] || comps[0] == vec![1, 0]);
     /// assert_eq!(comps[1], vec![2]);
     /// ```
+    #[must_use]
     pub fn components(&self) -> Vec<Vec<usize>> {
         let mut comps = vec![vec![]; self.count];
         for (v, idx) in self.indices.iter().enumerate() {
@@ -133,4 +152,313 @@ impl Scc {
         }
         comps
     }
+
+    /// Returns the condensation of `graph`, where vertex `c`
+    /// is component `c`, with an edge `c -> d` if some edge
+    /// goes from component `c` to another component `d`.
+    /// Parallel edges are merged, so every edge goes from a
+    /// lower index to a higher one, exactly once.
+    ///
+    /// 🕒 `O(V+E)`.
+    /// ```
+    /// # extern crate edge_list;
+    /// # use scc::make_scc;
+    /// # use edge_list::UnweightedGraph;
+    /// let mut graph = UnweightedGraph::new(4);
+    /// graph.direct(0, 1, ());
+    /// graph.direct(1, 0, ());
+    /// graph.direct(0, 2, ());
+    /// graph.direct(1, 2, ());
+    /// graph.direct(3, 2, ());
+    /// let scc = make_scc(&graph);
+    /// let dag = scc.condense(&graph);
+    /// assert_eq!(dag.vertex_count(), 3);
+    /// assert_eq!(dag.edge_count(), 2);
+    /// ```
+    #[must_use]
+    pub fn condense<T>(&self, graph: &Graph<T>) -> UnweightedGraph {
+        let mut dag = UnweightedGraph::new(self.count);
+        let mut last = vec![NOPE; self.count];
+        for (c, comp) in self.components().into_iter().enumerate() {
+            for v in comp {
+                for (w, _) in graph.neighbors(v) {
+                    let d = self.indices[w];
+                    if d as usize != c && last[d as usize] as usize != c {
+                        last[d as usize] = c.try_into().unwrap();
+                        dag.direct(c, d as usize, ());
+                    }
+                }
+            }
+        }
+        dag
+    }
+
+    /// Returns whether each component has an edge from
+    /// another component, and to another component.
+    fn has_in_out<T>(&self, graph: &Graph<T>) -> (Vec<bool>, Vec<bool>) {
+        let mut has_in = vec![false; self.count];
+        let mut has_out = vec![false; self.count];
+        for v in 0..graph.vertex_count() {
+            let c = self.indices[v] as usize;
+            for (w, _) in graph.neighbors(v) {
+                let d = self.indices[w] as usize;
+                if c != d {
+                    has_out[c] = true;
+                    has_in[d] = true;
+                }
+            }
+        }
+        (has_in, has_out)
+    }
+
+    /// Returns the components without incoming edges from
+    /// other components, in increasing order.
+    ///
+    /// 🕒 `O(V+E)`.
+    #[must_use]
+    pub fn sources<T>(&self, graph: &Graph<T>) -> Vec<usize> {
+        let (has_in, _) = self.has_in_out(graph);
+        (0..self.count).filter(|&c| !has_in[c]).collect()
+    }
+
+    /// Returns the components without outgoing edges to
+    /// other components, in increasing order.
+    ///
+    /// 🕒 `O(V+E)`.
+    #[must_use]
+    pub fn sinks<T>(&self, graph: &Graph<T>) -> Vec<usize> {
+        let (_, has_out) = self.has_in_out(graph);
+        (0..self.count).filter(|&c| !has_out[c]).collect()
+    }
+
+    /// Returns the minimum number of edges to add
+    /// to make `graph` strongly connected.
+    ///
+    /// This is `max(sources, sinks)`, or `0` if
+    /// there is only one component.
+    ///
+    /// 🕒 `O(V+E)`.
+    /// ```
+    /// # extern crate edge_list;
+    /// # use scc::make_scc;
+    /// # use edge_list::UnweightedGraph;
+    /// let mut graph = UnweightedGraph::new(4);
+    /// graph.direct(0, 1, ());
+    /// graph.direct(0, 2, ());
+    /// graph.direct(0, 3, ());
+    /// // e.g. 1 -> 2 -> 3 -> 0
+    /// assert_eq!(make_scc(&graph).augmentation_count(&graph), 3);
+    /// ```
+    #[must_use]
+    pub fn augmentation_count<T>(&self, graph: &Graph<T>) -> usize {
+        if self.count <= 1 {
+            return 0;
+        }
+        let (has_in, has_out) = self.has_in_out(graph);
+        let sources = has_in.iter().filter(|&&x| !x).count();
+        let sinks = has_out.iter().filter(|&&x| !x).count();
+        sources.max(sinks)
+    }
+
+    /// Computes a DP over the condensation `dag`, from the
+    /// sinks back to the sources.
+    ///
+    /// `dp[c]` starts as `init(c)`, then `combine(c, &mut dp[c], &dp[d])`
+    /// is called for each edge `c -> d` of `dag`. Since the indices are
+    /// topologically ordered, `dp[d]` is already final by then.
+    ///
+    /// `dag` must be [`Scc::condense`] of the graph.
+    ///
+    /// 🕒 `O(V+E)`.
+    /// ```
+    /// # extern crate edge_list;
+    /// # use scc::make_scc;
+    /// # use edge_list::UnweightedGraph;
+    /// let mut graph = UnweightedGraph::new(4);
+    /// graph.direct(0, 1, ());
+    /// graph.direct(1, 0, ());
+    /// graph.direct(1, 2, ());
+    /// graph.direct(0, 3, ());
+    /// let weight = [5, 1, 7, 2];
+    ///
+    /// // The maximum total weight of vertices on a walk from each component.
+    /// let scc = make_scc(&graph);
+    /// let comps = scc.components();
+    /// let dag = scc.condense(&graph);
+    /// let own = |c: usize| comps[c].iter().map(|&v| weight[v]).sum::<i64>();
+    /// let best = scc.dp(&dag, own, |c, x, &y| *x = (*x).max(own(c) + y));
+    /// assert_eq!(best[scc.indices[0] as usize], 5 + 1 + 7);
+    /// assert_eq!(best[scc.indices[3] as usize], 2);
+    /// ```
+    pub fn dp<V>(
+        &self,
+        dag: &UnweightedGraph,
+        mut init: impl FnMut(usize) -> V,
+        mut combine: impl FnMut(usize, &mut V, &V),
+    ) -> Vec<V> {
+        let mut dp = (0..self.count).map(&mut init).collect::<Vec<_>>();
+        for c in (0..self.count).rev() {
+            let (done, rest) = dp.split_at_mut(c + 1);
+            for (d, ()) in dag.neighbors(c) {
+                combine(c, &mut done[c], &rest[d - c - 1]);
+            }
+        }
+        dp
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use edge_list::UnweightedGraph;
+
+    fn do_test_scc(n: usize, edges: &[(usize, usize)]) {
+        let mut graph = UnweightedGraph::new(n);
+        for &(a, b) in edges {
+            graph.direct(a, b, ());
+        }
+        let mut reach = vec![vec![false; n]; n];
+        for (v, row) in reach.iter_mut().enumerate() {
+            row[v] = true;
+            let mut stack = vec![v];
+            while let Some(x) = stack.pop() {
+                for (y, ()) in graph.neighbors(x) {
+                    if !row[y] {
+                        row[y] = true;
+                        stack.push(y);
+                    }
+                }
+            }
+        }
+
+        let scc = make_scc(&graph);
+        assert!(scc.indices.iter().all(|&i| (i as usize) < scc.count));
+        for (a, row) in reach.iter().enumerate() {
+            for (b, &ab) in row.iter().enumerate() {
+                let same = ab && reach[b][a];
+                assert_eq!(scc.indices[a] == scc.indices[b], same, "{a} {b}");
+                if ab {
+                    assert!(scc.indices[a] <= scc.indices[b], "{} {}", a, b);
+                }
+            }
+        }
+
+        let count = scc.count;
+        let comps = scc.components();
+        let dag = scc.condense(&graph);
+        let mut seen = vec![vec![false; count]; count];
+        for c in 0..count {
+            for (d, ()) in dag.neighbors(c) {
+                assert!(c < d && !seen[c][d]);
+                seen[c][d] = true;
+                let direct = comps[c]
+                    .iter()
+                    .any(|&v| graph.neighbors(v).any(|(w, ())| comps[d].contains(&w)));
+                assert!(direct);
+            }
+        }
+        let mut pairs = (0..n)
+            .flat_map(|v| graph.neighbors(v).map(move |(w, ())| (v, w)))
+            .map(|(v, w)| (scc.indices[v], scc.indices[w]))
+            .filter(|(c, d)| c != d)
+            .collect::<Vec<_>>();
+        pairs.sort_unstable();
+        pairs.dedup();
+        assert_eq!(dag.edge_count(), pairs.len());
+        let reach_comp = |c: usize, d: usize| reach[comps[c][0]][comps[d][0]];
+        let sources = (0..count)
+            .filter(|&c| (0..count).all(|d| d == c || !reach_comp(d, c)))
+            .collect::<Vec<_>>();
+        let sinks = (0..count)
+            .filter(|&c| (0..count).all(|d| d == c || !reach_comp(c, d)))
+            .collect::<Vec<_>>();
+        assert_eq!(scc.sources(&graph), sources);
+        assert_eq!(scc.sinks(&graph), sinks);
+        let want = if count == 1 {
+            0
+        } else {
+            sources.len().max(sinks.len())
+        };
+        assert_eq!(scc.augmentation_count(&graph), want);
+
+        let weight = (0..n).map(|v| v * v % 10).collect::<Vec<_>>();
+        let own = |c: usize| comps[c].iter().map(|&v| weight[v]).sum::<usize>();
+        let best = scc.dp(&dag, own, |c, x, &y| *x = (*x).max(own(c) + y));
+        let mut want = vec![0; count];
+        for c in (0..count).rev() {
+            want[c] = own(c)
+                + (c + 1..count)
+                    .filter(|&d| reach_comp(c, d))
+                    .map(|d| want[d])
+                    .max()
+                    .unwrap_or(0);
+        }
+        assert_eq!(best, want);
+    }
+
+    #[test]
+    fn test_scc() {
+        // {0, 1, 2} -> {3, 4} -> {5}, {6} -> {0, 1, 2}, and {7} alone.
+        let edges = [
+            (0, 1),
+            (1, 2),
+            (2, 0),
+            (2, 3),
+            (3, 4),
+            (4, 3),
+            (4, 5),
+            (6, 1),
+            (5, 5),
+        ];
+        do_test_scc(8, &edges);
+        // The same, with the edges reversed.
+        do_test_scc(8, &edges.map(|(a, b)| (b, a)));
+        // Nested cycles sharing vertices, and parallel edges.
+        let edges = [
+            (0, 1),
+            (1, 2),
+            (2, 3),
+            (3, 1),
+            (3, 0),
+            (4, 2),
+            (4, 5),
+            (5, 4),
+            (0, 1),
+            (6, 4),
+        ];
+        do_test_scc(7, &edges);
+        // A DAG with sources {0, 1} and sinks {4, 5, 6}.
+        let edges = [(0, 2), (1, 2), (2, 3), (3, 4), (3, 5), (0, 6), (1, 3)];
+        do_test_scc(7, &edges);
+        do_test_scc(3, &[]);
+        do_test_scc(1, &[(0, 0)]);
+    }
+
+    #[test]
+    fn test_line() {
+        let n = 1_000_000;
+        let mut graph = UnweightedGraph::new(n);
+        for v in 1..n {
+            graph.direct(v - 1, v, ());
+        }
+        let scc = make_scc(&graph);
+        assert_eq!(scc.count, n);
+        assert!((0..n).all(|v| scc.indices[v] as usize == v));
+
+        graph.direct(n - 1, 0, ());
+        let scc = make_scc(&graph);
+        assert_eq!(scc.count, 1);
+    }
+
+    #[test]
+    fn test_star() {
+        let n = 1_000_000;
+        let mut graph = UnweightedGraph::new(n);
+        for v in 1..n {
+            graph.direct(0, v, ());
+        }
+        let scc = make_scc(&graph);
+        assert_eq!(scc.count, n);
+        assert_eq!(scc.indices[0], 0);
+    }
 }
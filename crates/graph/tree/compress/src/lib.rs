@@ -1,6 +1,7 @@
 //! Explanation
 
 use hld::Hld;
+use std::convert::TryFrom;
 
 extern crate hld;
 
@@ -15,6 +16,7 @@ pub struct CompressedTree<'a> {
 }
 
 impl<'a> CompressedTree<'a> {
+    #[must_use]
     pub fn new(hld: &'a Hld) -> Self {
         let n = hld.len();
         Self {
@@ -59,33 +61,108 @@ impl<'a> CompressedTree<'a> {
         // connect edges
         for w in vertices.windows(2) {
             let par = self.hld.lca(w[0], w[1]);
-            self.tree[par].push(w[1] as u32)
+            self.tree[par].push(u32::try_from(w[1]).unwrap());
         }
         self.used_vertices = vertices;
 
         true
     }
 
+    /// Calls `compute(self, v, children)` on every vertex `v` of the
+    /// compressed tree in post-order, where `children` lists the
+    /// children of `v` with their results. Returns the result of
+    /// the root, or `None` if nothing was compressed.
     pub fn dfs<V>(
         &mut self,
         compute: impl Fn(&mut Self, usize, Vec<(usize, V)>) -> V + Copy,
     ) -> Option<V> {
-        let Some(root) = self.root else {
-			return None;
-		};
-        Some(self.dfs_inner(root, compute))
+        let root = self.root?;
+        let mut calls = vec![(root, self.tree[root].clone().into_iter(), vec![])];
+        while let Some((_, children, _)) = calls.last_mut() {
+            if let Some(u) = children.next() {
+                let u = u as usize;
+                calls.push((u, self.tree[u].clone().into_iter(), vec![]));
+                continue;
+            }
+            let (v, _, sons) = calls.pop()?;
+            let val = compute(self, v, sons);
+            match calls.last_mut() {
+                Some((_, _, parent_sons)) => parent_sons.push((v, val)),
+                None => return Some(val),
+            }
+        }
+        None
     }
+}
 
-    fn dfs_inner<V>(
-        &mut self,
-        v: usize,
-        compute: impl Fn(&mut Self, usize, Vec<(usize, V)>) -> V + Copy,
-    ) -> V {
-        let mut sons = vec![];
-        for u in self.tree[v].clone() {
-            let val = self.dfs_inner(u as usize, compute);
-            sons.push((u as usize, val));
+#[cfg(test)]
+mod test {
+    extern crate edge_list;
+
+    use self::edge_list::UnweightedGraph;
+    use super::*;
+
+    /// Returns the number of target vertices in each subtree,
+    /// summed over all vertices.
+    fn count_targets(tree: &mut CompressedTree<'_>) -> Option<(usize, usize)> {
+        tree.dfs(|me, v, sons| {
+            let own = usize::from(me.is_target[v]);
+            sons.iter()
+                .fold((own, own), |(c, s), &(_, (sc, ss))| (c + sc, s + ss + sc))
+        })
+    }
+
+    #[test]
+    fn test_compress() {
+        //     0
+        //    / \
+        //   1   2
+        //  / \
+        // 3   4
+        let mut graph = UnweightedGraph::new(5);
+        for (a, b) in [(0, 1), (0, 2), (1, 3), (1, 4)] {
+            graph.bidirect(a, b, ());
+        }
+        let hld = Hld::new(&graph, 0);
+        let mut tree = CompressedTree::new(&hld);
+        assert!(!tree.compress(vec![]));
+        assert_eq!(count_targets(&mut tree), None);
+
+        assert!(tree.compress(vec![3, 4]));
+        let order = tree.dfs(|_, v, sons| {
+            let mut order = sons.into_iter().flat_map(|(_, o)| o).collect::<Vec<_>>();
+            order.push(v);
+            order
+        });
+        let order = order.unwrap();
+        assert_eq!(order.len(), 3);
+        assert_eq!(order[2], 1);
+        assert_eq!(count_targets(&mut tree), Some((2, 4)));
+    }
+
+    #[test]
+    fn test_line() {
+        let n = 1_000_000;
+        let mut graph = UnweightedGraph::new(n);
+        for v in 1..n {
+            graph.bidirect(v - 1, v, ());
+        }
+        let hld = Hld::new(&graph, 0);
+        let mut tree = CompressedTree::new(&hld);
+        assert!(tree.compress((0..n).collect()));
+        assert_eq!(count_targets(&mut tree), Some((n, n * (n + 1) / 2)));
+    }
+
+    #[test]
+    fn test_star() {
+        let n = 1_000_000;
+        let mut graph = UnweightedGraph::new(n);
+        for v in 1..n {
+            graph.bidirect(0, v, ());
         }
-        compute(self, v, sons)
+        let hld = Hld::new(&graph, 0);
+        let mut tree = CompressedTree::new(&hld);
+        assert!(tree.compress((1..n).collect()));
+        assert_eq!(count_targets(&mut tree), Some((n - 1, 2 * (n - 1))));
     }
 }
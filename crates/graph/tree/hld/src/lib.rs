@@ -37,56 +37,78 @@ impl Hld {
         };
         hld.dfs_size(tree, root);
         hld.top[root] = root as u32;
-        hld.dfs_hld(tree, root, 0);
+        hld.dfs_hld(tree, root);
         hld
     }
 
-    /// `v` = current vertex, `p` = parent
-    fn dfs_size<T>(&mut self, tree: &Graph<T>, v: usize) {
-        self.size[v] = 1;
-        for (u, _) in tree.neighbors(v) {
-            if Some(u as u32) == self.parent[v] {
-                continue;
+    /// Computes `parent`, `depth`, `size` and `heavy_child`.
+    /// The heavy child is the first child with the maximum size.
+    fn dfs_size<T>(&mut self, tree: &Graph<T>, root: usize) {
+        let mut order = vec![];
+        let mut stack = vec![root];
+        while let Some(v) = stack.pop() {
+            order.push(v);
+            for (u, _) in tree.neighbors(v) {
+                if Some(u as u32) == self.parent[v] {
+                    continue;
+                }
+                self.parent[u] = Some(v as u32);
+                self.depth[u] = self.depth[v] + 1;
+                stack.push(u);
             }
-            self.parent[u] = Some(v as u32);
-            self.depth[u] = self.depth[v] + 1;
-            self.dfs_size(tree, u);
-            self.size[v] += self.size[u];
-            if let Some(other_u) = self.heavy_child[v] {
-                if self.size[u] > self.size[other_u as usize] {
+        }
+        for &v in order.iter().rev() {
+            self.size[v] = 1;
+            for (u, _) in tree.neighbors(v) {
+                if Some(u as u32) == self.parent[v] {
+                    continue;
+                }
+                self.size[v] += self.size[u];
+                if let Some(other_u) = self.heavy_child[v] {
+                    if self.size[u] > self.size[other_u as usize] {
+                        self.heavy_child[v] = Some(u as u32);
+                    }
+                } else {
                     self.heavy_child[v] = Some(u as u32);
                 }
-            } else {
-                self.heavy_child[v] = Some(u as u32);
             }
         }
     }
 
-    fn dfs_hld<T>(&mut self, tree: &Graph<T>, v: usize, mut idx: u32) -> u32 {
-        self.dfs_index[v] = idx;
-        // dfs heavy child first
-        let Some(first_u) = self.heavy_child[v] else { return idx; };
-        self.top[first_u as usize] = self.top[v];
-        idx = self.dfs_hld(tree, first_u as usize, idx + 1);
-        // dfs rest
-        for (u, _) in tree.neighbors(v) {
-            if u as u32 == first_u || Some(u as u32) == self.parent[v] {
+    /// Computes `top` and `dfs_index`, visiting the heavy child
+    /// first and then the others in the order of the edges.
+    fn dfs_hld<T>(&mut self, tree: &Graph<T>, root: usize) {
+        let mut idx = 0;
+        let mut stack = vec![root];
+        while let Some(v) = stack.pop() {
+            self.dfs_index[v] = idx;
+            idx += 1;
+            let Some(first_u) = self.heavy_child[v] else {
                 continue;
-            }
-            self.top[u] = if Some(u as u32) == self.heavy_child[v] {
-                self.top[v]
-            } else {
-                u as u32
             };
-            idx = self.dfs_hld(tree, u, idx + 1);
+            let light = stack.len();
+            for (u, _) in tree.neighbors(v) {
+                if u as u32 == first_u || Some(u as u32) == self.parent[v] {
+                    continue;
+                }
+                self.top[u] = u as u32;
+                stack.push(u);
+            }
+            stack[light..].reverse();
+            self.top[first_u as usize] = self.top[v];
+            stack.push(first_u as usize);
         }
-        idx
     }
 
     pub fn len(&self) -> usize {
         self.parent.len()
     }
 
+    /// Returns the parent of `v`, or `None` if `v` is the root.
+    pub fn parent(&self, v: usize) -> Option<usize> {
+        self.parent[v].map(|p| p as usize)
+    }
+
     pub fn depth(&self, v: usize) -> u32 {
         self.depth[v]
     }
@@ -168,3 +190,123 @@ impl Hld {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use edge_list::UnweightedGraph;
+
+    /// Checks the decomposition of the tree where `parent[v]`
+    /// is the parent of `v`, rooted at `0`.
+    fn do_test_hld(parent: &[Option<usize>]) {
+        let n = parent.len();
+        let mut tree = UnweightedGraph::new(n);
+        for (v, p) in parent.iter().enumerate() {
+            if let Some(p) = *p {
+                tree.bidirect(p, v, ());
+            }
+        }
+        let hld = Hld::new(&tree, 0);
+        assert_eq!(hld.len(), n);
+
+        let mut seen = vec![false; n];
+        for v in 0..n {
+            assert!(!std::mem::replace(&mut seen[hld.index(v)], true));
+            let (l, r) = hld.subtree(v);
+            assert_eq!(l, hld.index(v));
+            if let Some(p) = parent[v] {
+                assert_eq!(hld.depth(v), hld.depth(p) + 1);
+                let (pl, pr) = hld.subtree(p);
+                assert!(pl < l && r <= pr);
+            }
+        }
+        let ancestors = |mut v: usize| {
+            let mut up = vec![v];
+            while let Some(p) = parent[v] {
+                up.push(p);
+                v = p;
+            }
+            up
+        };
+        for a in 0..n {
+            for b in 0..n {
+                let (c, a_path, b_path) = hld.path(a, b);
+                let b_up = ancestors(b);
+                let naive = ancestors(a).into_iter().find(|v| b_up.contains(v));
+                assert_eq!(Some(c), naive);
+                assert_eq!(hld.lca(a, b), c);
+                let len = a_path
+                    .iter()
+                    .chain(&b_path)
+                    .map(|(l, r)| r - l + 1)
+                    .sum::<usize>();
+                let d = hld.depth(a) + hld.depth(b) - 2 * hld.depth(c);
+                assert_eq!(len, d as usize + 1);
+            }
+        }
+    }
+
+    #[test]
+    fn test_hld() {
+        do_test_hld(&[None]);
+        do_test_hld(&[
+            None,
+            Some(0),
+            Some(0),
+            Some(1),
+            Some(1),
+            Some(2),
+            Some(4),
+            Some(4),
+            Some(3),
+            Some(8),
+        ]);
+        do_test_hld(&[
+            None,
+            Some(0),
+            Some(1),
+            Some(0),
+            Some(3),
+            Some(4),
+            Some(5),
+            Some(3),
+            Some(7),
+            Some(2),
+        ]);
+        // A binary tree, where heavy paths are short.
+        do_test_hld(
+            &(0..31)
+                .map(|v| (v > 0).then(|| (v - 1) / 2))
+                .collect::<Vec<_>>(),
+        );
+    }
+
+    #[test]
+    fn test_line() {
+        let n = 1_000_000;
+        let mut tree = UnweightedGraph::new(n);
+        for v in 1..n {
+            tree.bidirect(v - 1, v, ());
+        }
+        let hld = Hld::new(&tree, 0);
+        assert!((0..n).all(|v| hld.index(v) == v));
+        assert_eq!(hld.subtree(0), (0, n - 1));
+        assert_eq!(hld.path_from_top(n - 1), (0, n - 1));
+        assert_eq!(hld.lca(n - 1, 12345), 12345);
+    }
+
+    #[test]
+    fn test_star() {
+        let n = 1_000_000;
+        let mut tree = UnweightedGraph::new(n);
+        for v in 1..n {
+            tree.bidirect(0, v, ());
+        }
+        let hld = Hld::new(&tree, n - 1);
+        assert_eq!(hld.index(n - 1), 0);
+        assert_eq!(hld.index(0), 1);
+        assert_eq!(hld.subtree(0), (1, n - 1));
+        assert_eq!(hld.depth(1), 2);
+        assert_eq!(hld.lca(1, 2), 0);
+    }
+}
@@ -0,0 +1,457 @@
+//! Path and subtree queries on a tree, by pairing
+//! [`Hld`] with a [`LazySegtree`].
+//!
+//! # Usage
+//! Implement [`LazyMonoid`] as for `lazy_segtree`. The values are
+//! stored either on the vertices, or on the edges, where the edge
+//! between `v` and its parent is stored at `v`.
+//!
+//! `op` need not be commutative. Each node keeps the product
+//! in both directions, so [`HldSegtree::path_query`] returns
+//! the product from `a` to `b`, in this order.
+//!
+//! # Example
+//! ```
+//! # extern crate edge_list;
+//! # use edge_list::UnweightedGraph;
+//! # use hld_segtree::{Hld, HldSegtree, LazyMonoid};
+//! /// Range add, range sum.
+//! struct AddSum;
+//! impl LazyMonoid for AddSum {
+//!     type V = i64;
+//!     type L = i64;
+//!     const ID: i64 = 0;
+//!     fn op(x: &i64, y: &i64) -> i64 { x + y }
+//!     const NOT_LAZY: i64 = 0;
+//!     fn combine_lazy(cur: &mut i64, up: &i64) { *cur += up; }
+//!     fn unlazy(slot: &mut i64, lazy: i64, nl: usize, nr: usize) {
+//!         *slot += lazy * (nr - nl + 1) as i64;
+//!     }
+//! }
+//!
+//! //     0
+//! //    / \
+//! //   1   2
+//! //  / \
+//! // 3   4
+//! let mut tree = UnweightedGraph::new(5);
+//! for (a, b) in [(0, 1), (0, 2), (1, 3), (1, 4)] {
+//!     tree.bidirect(a, b, ());
+//! }
+//! let hld = Hld::new(&tree, 0);
+//!
+//! let mut vertices = HldSegtree::<AddSum>::new(hld.clone(), |v| v as i64);
+//! assert_eq!(vertices.path_query(3, 2), 3 + 1 + 0 + 2);
+//! vertices.path_update(4, 0, 10);
+//! assert_eq!(vertices.subtree_query(1), 11 + 3 + 14);
+//!
+//! // The edge above v has weight v.
+//! let mut edges = HldSegtree::<AddSum>::new_edges(hld, |v| v as i64);
+//! assert_eq!(edges.path_query(3, 2), 3 + 1 + 2);
+//! edges.subtree_update(1, 100);
+//! assert_eq!(edges.path_query(3, 4), 103 + 104);
+//! ```
+//!
+//! - [LC Vertex Set Path Composite](https://judge.yosupo.jp/problem/vertex_set_path_composite)
+//! ```ignore
+//! struct Affine;
+//! impl LazyMonoid for Affine {
+//!     type V = (Modint99, Modint99);
+//!     type L = ();
+//!     const ID: Self::V = (Modint99::new(1), Modint99::new(0));
+//!     fn op(x: &Self::V, y: &Self::V) -> Self::V {
+//!         (x.0 * y.0, x.1 * y.0 + y.1)
+//!     }
+//!     const NOT_LAZY: () = ();
+//!     fn combine_lazy(_: &mut (), _: &()) {}
+//!     fn unlazy(_: &mut Self::V, _: (), _: usize, _: usize) {}
+//! }
+//!
+//! let (n, q) = (oj.usize(), oj.usize());
+//! let init = (0..n).map(|_| (oj.read(), oj.read())).collect::<Vec<_>>();
+//! let mut tree = UnweightedGraph::new(n);
+//! for _ in 1..n {
+//!     tree.bidirect(oj.usize(), oj.usize(), ());
+//! }
+//! let mut seg = HldSegtree::<Affine>::new(Hld::new(&tree, 0), |v| init[v]);
+//! for _ in 0..q {
+//!     if oj.usize() == 0 {
+//!         seg.set(oj.usize(), (oj.read(), oj.read()));
+//!     } else {
+//!         let (a, b) = seg.path_query(oj.usize(), oj.usize());
+//!         oj.write(a * oj.read::<Modint99>() + b).ln();
+//!     }
+//! }
+//! ```
+
+extern crate hld;
+extern crate lazy_segtree;
+
+pub use hld::Hld;
+pub use lazy_segtree::{LazyMonoid, LazySegtree};
+use std::marker::PhantomData;
+
+/// `T` with the products in both directions, as `(forward, backward)`.
+struct BothWays<T>(PhantomData<T>);
+
+impl<T: LazyMonoid> LazyMonoid for BothWays<T> {
+    type V = (T::V, T::V);
+    type L = T::L;
+    const ID: Self::V = (T::ID, T::ID);
+    fn op(x: &Self::V, y: &Self::V) -> Self::V {
+        (T::op(&x.0, &y.0), T::op(&y.1, &x.1))
+    }
+    const NOT_LAZY: T::L = T::NOT_LAZY;
+    fn combine_lazy(cur: &mut T::L, up: &T::L) {
+        T::combine_lazy(cur, up);
+    }
+    fn unlazy(slot: &mut Self::V, lazy: T::L, nl: usize, nr: usize) {
+        T::unlazy(&mut slot.0, lazy.clone(), nl, nr);
+        T::unlazy(&mut slot.1, lazy, nl, nr);
+    }
+}
+
+/// [`Hld`] with a [`LazySegtree`] over [`LazyMonoid`] `T`.
+///
+/// In the method descriptions, let `n` be the number of vertices.
+pub struct HldSegtree<T: LazyMonoid> {
+    hld: Hld,
+    seg: LazySegtree<BothWays<T>>,
+    /// Whether the values are on the edges.
+    edges: bool,
+}
+
+impl<T: LazyMonoid> HldSegtree<T> {
+    fn build(hld: Hld, init: impl Fn(usize) -> T::V, edges: bool) -> Self {
+        let mut order = vec![0; hld.len()];
+        for v in 0..hld.len() {
+            order[hld.index(v)] = v;
+        }
+        let seg = LazySegtree::new(hld.len(), |i| {
+            let x = init(order[i]);
+            (x.clone(), x)
+        });
+        Self { hld, seg, edges }
+    }
+
+    /// Returns a tree where the value of vertex `v` is `init(v)`.
+    ///
+    /// 🕒 `O(n)`.
+    pub fn new(hld: Hld, init: impl Fn(usize) -> T::V) -> Self {
+        Self::build(hld, init, false)
+    }
+
+    /// Returns a tree where the value of the edge between
+    /// `v` and its parent is `init(v)`. `init` is never
+    /// called on the root.
+    ///
+    /// 🕒 `O(n)`.
+    pub fn new_edges(hld: Hld, init: impl Fn(usize) -> T::V) -> Self {
+        let root = (0..hld.len()).find(|&v| hld.parent(v).is_none());
+        Self::build(hld, |v| if Some(v) == root { T::ID } else { init(v) }, true)
+    }
+
+    /// Returns the underlying [`Hld`].
+    #[must_use]
+    pub const fn hld(&self) -> &Hld {
+        &self.hld
+    }
+
+    /// Returns the value of vertex `v`, or of the edge
+    /// between `v` and its parent.
+    ///
+    /// 🕒 `O(log n)`.
+    #[must_use]
+    pub fn get(&self, v: usize) -> T::V {
+        self.seg.get(self.hld.index(v)).0
+    }
+
+    /// Sets the value of vertex `v`, or of the edge
+    /// between `v` and its parent, to `x`.
+    ///
+    /// 🕒 `O(log n)`.
+    pub fn set(&mut self, v: usize, x: T::V) {
+        self.seg.set(self.hld.index(v), (x.clone(), x));
+    }
+
+    /// Returns the ETT ranges of the path from `a` to `b`,
+    /// each with whether it is traversed upwards, in order.
+    fn path_ranges(&self, a: usize, b: usize) -> Vec<(usize, usize, bool)> {
+        let (lca, a_path, b_path) = self.hld.path(a, b);
+        let skip = if self.edges {
+            Some(self.hld.index(lca))
+        } else {
+            None
+        };
+        let up = a_path.into_iter().map(|(l, r)| (l, r, true));
+        let down = b_path.into_iter().rev().map(|(l, r)| (l, r, false));
+        up.chain(down)
+            .map(|(l, r, up)| {
+                if Some(l) == skip {
+                    (l + 1, r, up)
+                } else {
+                    (l, r, up)
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the product of the values on the path from `a` to `b`,
+    /// in this order. In the edge mode, these are the edges on the path.
+    ///
+    /// 🕒 `O(log^2 n)`.
+    #[must_use]
+    pub fn path_query(&self, a: usize, b: usize) -> T::V {
+        self.path_ranges(a, b)
+            .into_iter()
+            .fold(T::ID, |acc, (l, r, up)| {
+                let (forward, backward) = self.seg.prod(l..=r);
+                T::op(&acc, if up { &backward } else { &forward })
+            })
+    }
+
+    /// Applies `lazy` to the values on the path from `a` to `b`.
+    ///
+    /// 🕒 `O(log^2 n)`.
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn path_update(&mut self, a: usize, b: usize, lazy: T::L) {
+        for (l, r, _) in self.path_ranges(a, b) {
+            self.seg.apply(l..=r, lazy.clone());
+        }
+    }
+
+    /// Returns the ETT range of the subtree of `v`,
+    /// without `v` itself in the edge mode.
+    fn subtree_range(&self, v: usize) -> (usize, usize) {
+        let (l, r) = self.hld.subtree(v);
+        (l + usize::from(self.edges), r)
+    }
+
+    /// Returns the product of the values in the subtree of `v`,
+    /// in the order of [`Hld::index`]. In the edge mode, these are
+    /// the edges below `v`.
+    ///
+    /// 🕒 `O(log n)`.
+    #[must_use]
+    pub fn subtree_query(&self, v: usize) -> T::V {
+        let (l, r) = self.subtree_range(v);
+        self.seg.prod(l..=r).0
+    }
+
+    /// Applies `lazy` to the values in the subtree of `v`.
+    ///
+    /// 🕒 `O(log n)`.
+    pub fn subtree_update(&mut self, v: usize, lazy: T::L) {
+        let (l, r) = self.subtree_range(v);
+        self.seg.apply(l..=r, lazy);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    extern crate edge_list;
+
+    use self::edge_list::UnweightedGraph;
+    use super::*;
+    use std::convert::TryFrom;
+
+    /// Concatenation, with adding to every element.
+    struct AddConcat;
+    impl LazyMonoid for AddConcat {
+        type V = Vec<i64>;
+        type L = i64;
+        const ID: Vec<i64> = vec![];
+        fn op(x: &Vec<i64>, y: &Vec<i64>) -> Vec<i64> {
+            [x.as_slice(), y].concat()
+        }
+        const NOT_LAZY: i64 = 0;
+        fn combine_lazy(cur: &mut i64, up: &i64) {
+            *cur += up;
+        }
+        fn unlazy(slot: &mut Vec<i64>, lazy: i64, _: usize, _: usize) {
+            for x in slot {
+                *x += lazy;
+            }
+        }
+    }
+
+    /// Returns the vertices on the path from `a` to `b`.
+    fn naive_path(parent: &[usize], depth: &[usize], mut a: usize, mut b: usize) -> Vec<usize> {
+        let mut front = vec![];
+        let mut back = vec![];
+        while a != b {
+            if depth[a] >= depth[b] {
+                front.push(a);
+                a = parent[a];
+            } else {
+                back.push(b);
+                b = parent[b];
+            }
+        }
+        front.push(a);
+        front.extend(back.into_iter().rev());
+        front
+    }
+
+    enum Query {
+        Path(usize, usize),
+        Subtree(usize),
+        PathUpdate(usize, usize, i64),
+        SubtreeUpdate(usize, i64),
+        /// Adds to the vertex and to the edge above it with `set`.
+        Set(usize, i64),
+    }
+
+    /// Runs `queries` on the tree where `parent[v]` is the parent
+    /// of `v`, and the root is its own parent.
+    fn do_test_hld_segtree(parent: &[usize], queries: Vec<Query>) {
+        let n = parent.len();
+        let root = (0..n).find(|&v| parent[v] == v).unwrap();
+        let mut depth = vec![0; n];
+        for (v, d) in depth.iter_mut().enumerate() {
+            let mut u = v;
+            while u != root {
+                u = parent[u];
+                *d += 1;
+            }
+        }
+        let mut tree = UnweightedGraph::new(n);
+        for v in (0..n).filter(|&v| v != root) {
+            tree.bidirect(parent[v], v, ());
+        }
+        let hld = Hld::new(&tree, root);
+        let mut vert = (0..n)
+            .map(|v| i64::try_from(v).unwrap() * 10)
+            .collect::<Vec<_>>();
+        let mut edge = vert.clone();
+        let mut vertices = HldSegtree::<AddConcat>::new(hld.clone(), |v| vec![vert[v]]);
+        let mut edges = HldSegtree::<AddConcat>::new_edges(hld, |v| vec![edge[v]]);
+        let in_subtree = |u: usize, v: usize| {
+            let path = naive_path(parent, &depth, u, v);
+            path.iter().all(|&w| depth[w] >= depth[v])
+        };
+
+        for (qi, query) in queries.into_iter().enumerate() {
+            match query {
+                Query::Path(a, b) => {
+                    let path = naive_path(parent, &depth, a, b);
+                    let lca = *path.iter().min_by_key(|&&v| depth[v]).unwrap();
+                    let want = path.iter().map(|&v| vert[v]).collect::<Vec<_>>();
+                    assert_eq!(vertices.path_query(a, b), want, "query {qi}");
+                    let want = path
+                        .iter()
+                        .filter(|&&v| v != lca)
+                        .map(|&v| edge[v])
+                        .collect::<Vec<_>>();
+                    assert_eq!(edges.path_query(a, b), want, "query {qi}");
+                }
+                Query::Subtree(a) => {
+                    let below = (0..n).filter(|&u| in_subtree(u, a)).collect::<Vec<_>>();
+                    let mut want = below.iter().map(|&v| vert[v]).collect::<Vec<_>>();
+                    let mut got = vertices.subtree_query(a);
+                    want.sort_unstable();
+                    got.sort_unstable();
+                    assert_eq!(got, want, "query {qi}");
+                    let mut want = below
+                        .iter()
+                        .filter(|&&v| v != a)
+                        .map(|&v| edge[v])
+                        .collect::<Vec<_>>();
+                    let mut got = edges.subtree_query(a);
+                    want.sort_unstable();
+                    got.sort_unstable();
+                    assert_eq!(got, want, "query {qi}");
+                }
+                Query::PathUpdate(a, b, lazy) => {
+                    let path = naive_path(parent, &depth, a, b);
+                    let lca = *path.iter().min_by_key(|&&v| depth[v]).unwrap();
+                    vertices.path_update(a, b, lazy);
+                    edges.path_update(a, b, lazy);
+                    for &v in &path {
+                        vert[v] += lazy;
+                        if v != lca {
+                            edge[v] += lazy;
+                        }
+                    }
+                }
+                Query::SubtreeUpdate(a, lazy) => {
+                    vertices.subtree_update(a, lazy);
+                    edges.subtree_update(a, lazy);
+                    for v in (0..n).filter(|&u| in_subtree(u, a)) {
+                        vert[v] += lazy;
+                        if v != a {
+                            edge[v] += lazy;
+                        }
+                    }
+                }
+                Query::Set(a, lazy) => {
+                    vert[a] += lazy;
+                    vertices.set(a, vec![vert[a]]);
+                    if a != root {
+                        edge[a] += lazy;
+                        edges.set(a, vec![edge[a]]);
+                    }
+                }
+            }
+            for v in 0..n {
+                assert_eq!(vertices.get(v), vec![vert[v]], "query {qi}");
+                let want = if v == root { vec![] } else { vec![edge[v]] };
+                assert_eq!(edges.get(v), want, "query {qi}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_hld_segtree() {
+        //         3
+        //       / | \
+        //      0  5  8
+        //     /|  |
+        //    1 2  4
+        //   /|    |
+        //  6 9    7
+        let parent = [3, 0, 0, 3, 5, 3, 1, 4, 3, 1];
+        do_test_hld_segtree(
+            &parent,
+            vec![
+                Query::Path(6, 7),
+                Query::Path(7, 6),
+                Query::Subtree(0),
+                Query::PathUpdate(9, 4, 3),
+                Query::Path(2, 7),
+                Query::Subtree(3),
+                Query::SubtreeUpdate(0, 2),
+                Query::Path(6, 9),
+                Query::Subtree(1),
+                Query::Set(3, 5),
+                Query::Set(6, 1),
+                Query::Path(8, 6),
+                Query::PathUpdate(8, 8, 4),
+                Query::Subtree(8),
+                Query::SubtreeUpdate(3, 1),
+                Query::Path(3, 3),
+                Query::Subtree(5),
+            ],
+        );
+        // Every path and subtree.
+        let n = parent.len();
+        let mut queries = vec![];
+        for a in 0..n {
+            for b in 0..n {
+                queries.push(Query::PathUpdate(
+                    a,
+                    b,
+                    i64::try_from(a * n + b).unwrap() % 5,
+                ));
+                queries.push(Query::Path(b, a));
+            }
+            queries.push(Query::SubtreeUpdate(a, 1));
+            queries.push(Query::Subtree(a));
+        }
+        do_test_hld_segtree(&parent, queries);
+        do_test_hld_segtree(
+            &[0],
+            vec![Query::Set(0, 1), Query::Path(0, 0), Query::Subtree(0)],
+        );
+    }
+}
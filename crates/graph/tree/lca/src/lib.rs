@@ -0,0 +1,447 @@
+//! Lowest common ancestor and path queries by binary lifting.
+//!
+//! Uses [`Graph`], which must be a tree given with edges
+//! in both directions.
+//!
+//! - [`Lca`]: LCA, ancestors, distances and k-th vertex on a path.
+//! - [`PathProd`]: additionally, the product of the edge weights
+//!   on a path over a [`Monoid`], e.g. the maximum edge.
+//!
+//! # Example
+//! ```
+//! # extern crate edge_list;
+//! # use lca::Lca;
+//! # use edge_list::UnweightedGraph;
+//! //     0
+//! //    / \
+//! //   1   2
+//! //  / \
+//! // 3   4
+//! let mut tree = UnweightedGraph::new(5);
+//! for (a, b) in [(0, 1), (0, 2), (1, 3), (1, 4)] {
+//!     tree.bidirect(a, b, ());
+//! }
+//! let lca = Lca::new(&tree, 0);
+//! assert_eq!(lca.lca(3, 4), 1);
+//! assert_eq!(lca.dist(3, 2), 3);
+//! assert_eq!(lca.kth_ancestor(3, 2), Some(0));
+//! assert_eq!(lca.kth_on_path(3, 2, 2), Some(0));
+//! assert!(lca.is_ancestor(1, 4));
+//! ```
+//!
+//! - [LC Jump on Tree](https://judge.yosupo.jp/problem/jump_on_tree)
+//! ```ignore
+//! let (n, q) = (oj.usize(), oj.usize());
+//! let mut tree = UnweightedGraph::new(n);
+//! for _ in 1..n {
+//!     tree.bidirect(oj.usize(), oj.usize(), ());
+//! }
+//! let lca = Lca::new(&tree, 0);
+//! for _ in 0..q {
+//!     match lca.kth_on_path(oj.usize(), oj.usize(), oj.usize()) {
+//!         Some(v) => oj.write(v).ln(),
+//!         None => oj.write(-1).ln(),
+//!     };
+//! }
+//! ```
+
+extern crate edge_list;
+extern crate segtree;
+
+use edge_list::Graph;
+pub use segtree::Monoid;
+use std::convert::TryFrom;
+
+/// Binary lifting table of a rooted tree.
+///
+/// In the method descriptions, let `n` be the number of vertices.
+#[derive(Clone, Debug)]
+pub struct Lca {
+    /// `up[k][v]` is the `2^k`-th ancestor of `v`,
+    /// or the root if there is none.
+    up: Vec<Vec<u32>>,
+    depth: Vec<u32>,
+    /// Preorder index of `v`.
+    tin: Vec<u32>,
+    /// The largest preorder index in the subtree of `v`.
+    tout: Vec<u32>,
+}
+
+impl Lca {
+    /// Builds the table for `tree` rooted at `root`.
+    ///
+    /// 🕒 `O(n log n)`.
+    ///
+    /// ⚠️ Panics if `tree` is not connected.
+    #[must_use]
+    pub fn new<T>(tree: &Graph<T>, root: usize) -> Self {
+        Self::build(tree, root, |_| {}).0
+    }
+
+    /// Calls `on_edge` on the edge to the parent of each non-root
+    /// vertex, and also returns those vertices in the same order.
+    fn build<'a, T>(
+        tree: &'a Graph<T>,
+        root: usize,
+        mut on_edge: impl FnMut(&'a T),
+    ) -> (Self, Vec<usize>) {
+        let n = tree.vertex_count();
+        let mut parent = vec![u32::try_from(root).unwrap(); n];
+        let mut depth = vec![0; n];
+        let mut tin = vec![0; n];
+        let mut tout = vec![0; n];
+        let mut order = Vec::with_capacity(n);
+        // Children are pushed right after their parent is popped,
+        // so each subtree gets contiguous preorder indices.
+        let mut stack = vec![root];
+        let mut visited = vec![false; n];
+        visited[root] = true;
+        let mut weighted = vec![];
+        while let Some(v) = stack.pop() {
+            tin[v] = u32::try_from(order.len()).unwrap();
+            order.push(v);
+            for edge in tree.edges_from(v) {
+                let u = edge.to as usize;
+                if !visited[u] {
+                    visited[u] = true;
+                    parent[u] = u32::try_from(v).unwrap();
+                    depth[u] = depth[v] + 1;
+                    weighted.push(u);
+                    on_edge(&edge.content);
+                    stack.push(u);
+                }
+            }
+        }
+        assert_eq!(order.len(), n, "the tree is not connected");
+        for &v in order.iter().rev() {
+            tout[v] = tout[v].max(tin[v]);
+            let p = parent[v] as usize;
+            tout[p] = tout[p].max(tout[v]);
+        }
+
+        let mut up = vec![parent];
+        while 1 << up.len() < n {
+            let prev = &up[up.len() - 1];
+            let row = prev.iter().map(|&p| prev[p as usize]).collect();
+            up.push(row);
+        }
+        let lca = Self {
+            up,
+            depth,
+            tin,
+            tout,
+        };
+        (lca, weighted)
+    }
+
+    /// Returns `n`.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.depth.len()
+    }
+
+    /// Returns whether `n == 0`.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.depth.is_empty()
+    }
+
+    /// Returns the depth of `v`. The root has depth 0.
+    #[must_use]
+    pub fn depth(&self, v: usize) -> usize {
+        self.depth[v] as usize
+    }
+
+    /// Returns the parent of `v`, or `None` if `v` is the root.
+    #[must_use]
+    pub fn parent(&self, v: usize) -> Option<usize> {
+        self.kth_ancestor(v, 1)
+    }
+
+    /// Returns whether `a` is an ancestor of `b`.
+    /// Every vertex is an ancestor of itself.
+    ///
+    /// 🕒 `O(1)`.
+    #[must_use]
+    pub fn is_ancestor(&self, a: usize, b: usize) -> bool {
+        self.tin[a] <= self.tin[b] && self.tout[b] <= self.tout[a]
+    }
+
+    /// Returns the ancestor of `v` that is `k` edges above,
+    /// or `None` if `k > depth(v)`.
+    ///
+    /// 🕒 `O(log n)`.
+    #[must_use]
+    pub fn kth_ancestor(&self, mut v: usize, k: usize) -> Option<usize> {
+        if k > self.depth(v) {
+            return None;
+        }
+        for (i, row) in self.up.iter().enumerate() {
+            if k >> i & 1 == 1 {
+                v = row[v] as usize;
+            }
+        }
+        Some(v)
+    }
+
+    /// Returns the LCA of `a` and `b`.
+    ///
+    /// 🕒 `O(log n)`.
+    #[must_use]
+    pub fn lca(&self, a: usize, b: usize) -> usize {
+        if self.is_ancestor(a, b) {
+            return a;
+        }
+        let mut a = a;
+        for row in self.up.iter().rev() {
+            let p = row[a] as usize;
+            if !self.is_ancestor(p, b) {
+                a = p;
+            }
+        }
+        self.up[0][a] as usize
+    }
+
+    /// Returns the number of edges on the path between `a` and `b`.
+    ///
+    /// 🕒 `O(log n)`.
+    #[must_use]
+    pub fn dist(&self, a: usize, b: usize) -> usize {
+        self.depth(a) + self.depth(b) - 2 * self.depth(self.lca(a, b))
+    }
+
+    /// Returns the `k`-th vertex on the path from `a` to `b`,
+    /// where the 0-th is `a`, or `None` if `k > dist(a, b)`.
+    ///
+    /// 🕒 `O(log n)`.
+    #[must_use]
+    pub fn kth_on_path(&self, a: usize, b: usize, k: usize) -> Option<usize> {
+        let c = self.lca(a, b);
+        let da = self.depth(a) - self.depth(c);
+        let db = self.depth(b) - self.depth(c);
+        if k <= da {
+            self.kth_ancestor(a, k)
+        } else if k <= da + db {
+            self.kth_ancestor(b, da + db - k)
+        } else {
+            None
+        }
+    }
+}
+
+/// [`Lca`] with the product of edge weights on paths,
+/// over [`Monoid`] `T`. The operation need not be commutative.
+///
+/// In the method descriptions, let `n` be the number of vertices.
+///
+/// # Example
+/// ```
+/// # extern crate edge_list;
+/// # use lca::{Monoid, PathProd};
+/// # use edge_list::Graph;
+/// struct Max;
+/// impl Monoid for Max {
+///     type V = u32;
+///     const ID: u32 = 0;
+///     fn op(x: &u32, y: &u32) -> u32 { *x.max(y) }
+/// }
+///
+/// let mut tree = Graph::new(4);
+/// tree.bidirect(0, 1, 5);
+/// tree.bidirect(1, 2, 3);
+/// tree.bidirect(1, 3, 7);
+/// let paths = PathProd::<Max>::new(&tree, 0);
+/// assert_eq!(paths.prod(2, 3), 7);
+/// assert_eq!(paths.prod(2, 0), 5);
+/// assert_eq!(paths.prod(2, 2), 0);
+/// assert_eq!(paths.lca.lca(2, 3), 1);
+/// ```
+#[derive(Clone, Debug)]
+pub struct PathProd<T: Monoid> {
+    /// The underlying LCA structure.
+    pub lca: Lca,
+    /// `up[k][v]` is the product of the `2^k` edges above `v`,
+    /// from bottom to top.
+    up: Vec<Vec<T::V>>,
+    /// Same as `up`, but from top to bottom.
+    down: Vec<Vec<T::V>>,
+}
+
+impl<T: Monoid> PathProd<T> {
+    /// Builds the tables for `tree` rooted at `root`.
+    /// The weight of each edge is its content.
+    ///
+    /// 🕒 `O(n log n)`.
+    ///
+    /// ⚠️ Panics if `tree` is not connected.
+    #[must_use]
+    pub fn new(tree: &Graph<T::V>, root: usize) -> Self {
+        let mut weights = vec![];
+        let (lca, children) = Lca::build(tree, root, |w| weights.push(w.clone()));
+        let mut base = vec![T::ID; tree.vertex_count()];
+        for (v, w) in children.into_iter().zip(weights) {
+            base[v] = w;
+        }
+        let mut up = vec![base.clone()];
+        let mut down = vec![base];
+        for k in 1..lca.up.len() {
+            let anc = &lca.up[k - 1];
+            let row = (0..anc.len())
+                .map(|v| T::op(&up[k - 1][v], &up[k - 1][anc[v] as usize]))
+                .collect();
+            up.push(row);
+            let row = (0..anc.len())
+                .map(|v| T::op(&down[k - 1][anc[v] as usize], &down[k - 1][v]))
+                .collect();
+            down.push(row);
+        }
+        Self { lca, up, down }
+    }
+
+    /// Returns the product of the edge weights on the path
+    /// from `a` to `b`, in this order.
+    ///
+    /// 🕒 `O(log n)`.
+    #[must_use]
+    pub fn prod(&self, a: usize, b: usize) -> T::V {
+        let c = self.lca.lca(a, b);
+        let mut left = T::ID;
+        let mut a = a;
+        let da = self.lca.depth(a) - self.lca.depth(c);
+        let mut right = T::ID;
+        let mut b = b;
+        let db = self.lca.depth(b) - self.lca.depth(c);
+        for (k, anc) in self.lca.up.iter().enumerate() {
+            if da >> k & 1 == 1 {
+                left = T::op(&left, &self.up[k][a]);
+                a = anc[a] as usize;
+            }
+            if db >> k & 1 == 1 {
+                right = T::op(&self.down[k][b], &right);
+                b = anc[b] as usize;
+            }
+        }
+        T::op(&left, &right)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use edge_list::UnweightedGraph;
+
+    /// Concatenation of edge labels, to check the order.
+    struct Concat;
+    impl Monoid for Concat {
+        type V = Vec<usize>;
+        const ID: Vec<usize> = vec![];
+        fn op(x: &Vec<usize>, y: &Vec<usize>) -> Vec<usize> {
+            [x.as_slice(), y].concat()
+        }
+    }
+
+    /// Returns the path from `a` to `b` using `parent`.
+    fn naive_path(parent: &[usize], depth: &[usize], mut a: usize, mut b: usize) -> Vec<usize> {
+        let mut front = vec![];
+        let mut back = vec![];
+        while a != b {
+            if depth[a] >= depth[b] {
+                front.push(a);
+                a = parent[a];
+            } else {
+                back.push(b);
+                b = parent[b];
+            }
+        }
+        front.push(a);
+        front.extend(back.into_iter().rev());
+        front
+    }
+
+    /// Checks every query on the tree where `parent[v]` is the
+    /// parent of `v`, and the root is its own parent.
+    fn do_test_lca(parent: &[usize]) {
+        let n = parent.len();
+        let root = (0..n).find(|&v| parent[v] == v).unwrap();
+        let mut depth = vec![0; n];
+        for (v, d) in depth.iter_mut().enumerate() {
+            let mut u = v;
+            while u != root {
+                u = parent[u];
+                *d += 1;
+            }
+        }
+        let mut tree = Graph::new(n);
+        for v in (0..n).filter(|&v| v != root) {
+            // The label of the edge above v is v.
+            tree.bidirect(v, parent[v], vec![v]);
+        }
+
+        let lca = Lca::new(&tree, root);
+        let paths = PathProd::<Concat>::new(&tree, root);
+        assert_eq!(lca.len(), n);
+        for a in 0..n {
+            assert_eq!(lca.depth(a), depth[a]);
+            assert_eq!(lca.parent(a), (a != root).then(|| parent[a]));
+            for b in 0..n {
+                let path = naive_path(parent, &depth, a, b);
+                let c = *path.iter().min_by_key(|&&v| depth[v]).unwrap();
+                assert_eq!(lca.lca(a, b), c);
+                assert_eq!(paths.lca.lca(a, b), c);
+                assert_eq!(lca.dist(a, b), path.len() - 1);
+                assert_eq!(lca.is_ancestor(a, b), c == a);
+                for k in 0..=path.len() {
+                    assert_eq!(lca.kth_on_path(a, b, k), path.get(k).copied());
+                }
+                let labels = path
+                    .windows(2)
+                    .map(|w| {
+                        if parent[w[0]] == w[1] && w[0] != root {
+                            w[0]
+                        } else {
+                            w[1]
+                        }
+                    })
+                    .collect::<Vec<_>>();
+                assert_eq!(paths.prod(a, b), labels);
+            }
+            let ancestors = naive_path(parent, &depth, a, root);
+            for k in 0..=ancestors.len() {
+                assert_eq!(lca.kth_ancestor(a, k), ancestors.get(k).copied());
+            }
+        }
+    }
+
+    #[test]
+    fn test_lca() {
+        do_test_lca(&[0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 6, 6, 10]);
+        // Root in the middle, parents after their children.
+        do_test_lca(&[4, 0, 1, 2, 4, 4, 5, 9, 6, 8, 9, 10, 10]);
+        // A long path, so that every lifting level is used.
+        do_test_lca(
+            &(0..40)
+                .map(|v: usize| v.saturating_sub(1))
+                .collect::<Vec<_>>(),
+        );
+        do_test_lca(&[3, 3, 3, 3, 3, 3]);
+        do_test_lca(&[0]);
+    }
+
+    #[test]
+    fn test_deep_line() {
+        let n = 1_000_000;
+        let mut tree = UnweightedGraph::new(n);
+        for v in 1..n {
+            tree.bidirect(v - 1, v, ());
+        }
+        let lca = Lca::new(&tree, 0);
+        assert_eq!(lca.lca(n - 1, 123), 123);
+        assert_eq!(lca.kth_on_path(n - 1, 0, 10), Some(n - 11));
+    }
+
+    #[test]
+    #[should_panic(expected = "the tree is not connected")]
+    fn test_disconnected() {
+        let _ = Lca::new(&UnweightedGraph::new(2), 0);
+    }
+}
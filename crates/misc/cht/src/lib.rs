@@ -0,0 +1,574 @@
+//! [Convex hull trick](https://cp-algorithms.com/geometry/convex_hull_trick.html)
+//! and [Li Chao tree](https://cp-algorithms.com/geometry/li_chao_tree.html),
+//! for DPs of the form `dp[i] = min_j (a_j * x_i + b_j)`.
+//!
+//! Every structure here holds lines `y = ax + b` and answers the
+//! **minimum** at an integer `x`. For the maximum, add `(-a, -b)`
+//! and negate the answer.
+//!
+//! - [`MonotoneCht`]: slopes are added in non-increasing order.
+//!   `O(log n)` queries, or amortized `O(1)` if `x` is non-decreasing.
+//! - [`LineContainer`]: lines in any order, `O(log n)` everything.
+//! - [`LiChaoTree`]: the `x`s are known in advance.
+//!   Also supports adding a line only on a range of `x`.
+//!
+//! All are generic over [`LineNum`], implemented for `i32`, `i64`,
+//! `i128`, and `isize`.
+//!
+//! # Overflow
+//! The hulls never multiply two coefficients together: they compare
+//! intersection points computed by floor division, so only
+//! `b1 - b2` and `a1 - a2` need to fit in `T`.
+//! Evaluating `ax + b` at a query point must of course fit in `T`,
+//! so use `i128` if it does not fit in `i64`.
+//!
+//! # Example
+//! ```
+//! # use cht::{LiChaoTree, LineContainer, MonotoneCht};
+//! let mut cht = MonotoneCht::new();
+//! cht.add(2, 0);
+//! cht.add(0, 3);
+//! cht.add(-1, 10);
+//! assert_eq!(cht.query(1), Some(2));
+//! assert_eq!(cht.query(5), Some(3));
+//!
+//! let mut lc = LineContainer::new();
+//! lc.add(0, 3);
+//! lc.add(-1, 10);
+//! lc.add(2, 0);
+//! assert_eq!(lc.query(1), Some(2));
+//! assert_eq!(lc.query(100), Some(-90));
+//!
+//! let mut lichao = LiChaoTree::new(vec![0i64, 1, 2, 3, 4]);
+//! lichao.add_segment(-1, 0, 2..=3);
+//! lichao.add_line(0, 1);
+//! assert_eq!(lichao.query(1), Some(1));
+//! assert_eq!(lichao.query(3), Some(-3));
+//! ```
+
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt::Debug;
+use std::ops::{Add, Bound, Div, Mul, Neg, RangeBounds, Rem, Sub};
+
+/// Numbers usable as line coefficients.
+pub trait LineNum:
+    Copy
+    + Debug
+    + Ord
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Rem<Output = Self>
+    + Neg<Output = Self>
+{
+    const ZERO: Self;
+    const ONE: Self;
+    const MIN: Self;
+}
+
+macro_rules! impl_line_num {
+	($($T:ty) *) => { $(
+		impl LineNum for $T {
+			const ZERO: Self = 0;
+			const ONE: Self = 1;
+			const MIN: Self = <$T>::MIN;
+		}
+	)* };
+}
+impl_line_num!(i32 i64 i128 isize);
+
+/// Returns `ceil(p / q)` for `q > 0`.
+fn div_ceil<T: LineNum>(p: T, q: T) -> T {
+    let d = p / q;
+    if p % q > T::ZERO {
+        d + T::ONE
+    } else {
+        d
+    }
+}
+
+/// Line `y = ax + b`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Line<T> {
+    pub a: T,
+    pub b: T,
+}
+
+impl<T: LineNum> Line<T> {
+    /// Returns `ax + b`.
+    #[must_use]
+    pub fn eval(&self, x: T) -> T {
+        self.a * x + self.b
+    }
+}
+
+/// Returns the smallest integer `x` where `l2` is at most `l1`,
+/// where `l1.a > l2.a`.
+fn cross<T: LineNum>(l1: &Line<T>, l2: &Line<T>) -> T {
+    div_ceil(l2.b - l1.b, l1.a - l2.a)
+}
+
+/// Whether `l2` is never strictly below both `l1` and `l3`,
+/// where `l1.a > l2.a > l3.a`.
+fn is_useless<T: LineNum>(l1: &Line<T>, l2: &Line<T>, l3: &Line<T>) -> bool {
+    cross(l1, l2) >= cross(l2, l3)
+}
+
+/// Convex hull trick with slopes added in non-increasing order.
+///
+/// In the method descriptions, let `n` be the number of lines.
+#[derive(Clone, Debug, Default)]
+pub struct MonotoneCht<T> {
+    /// Lines with decreasing slopes, all on the hull.
+    lines: VecDeque<Line<T>>,
+}
+
+impl<T: LineNum> MonotoneCht<T> {
+    /// Returns an empty hull.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            lines: VecDeque::new(),
+        }
+    }
+
+    /// Adds the line `y = ax + b`.
+    ///
+    /// 🕒 Amortized `O(1)`.
+    ///
+    /// ⚠️ Panics if `a` is greater than the slope of the last line.
+    pub fn add(&mut self, a: T, b: T) {
+        let line = Line { a, b };
+        if let Some(last) = self.lines.back() {
+            assert!(a <= last.a, "slope {:?} after {:?}", a, last.a);
+            if a == last.a {
+                if b >= last.b {
+                    return;
+                }
+                self.lines.pop_back();
+            }
+        }
+        while self.lines.len() >= 2 {
+            let n = self.lines.len();
+            if !is_useless(&self.lines[n - 2], &self.lines[n - 1], &line) {
+                break;
+            }
+            self.lines.pop_back();
+        }
+        self.lines.push_back(line);
+    }
+
+    /// Returns the minimum of `ax + b` over all lines,
+    /// or `None` if there is no line.
+    ///
+    /// 🕒 `O(log n)`.
+    #[must_use]
+    pub fn query(&self, x: T) -> Option<T> {
+        let mut lo = 0;
+        let mut hi = self.lines.len();
+        if hi == 0 {
+            return None;
+        }
+        // the answer is at the last i with cross(i-1, i) <= x
+        while hi - lo > 1 {
+            let mid = lo + (hi - lo) / 2;
+            if cross(&self.lines[mid - 1], &self.lines[mid]) <= x {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+        }
+        Some(self.lines[lo].eval(x))
+    }
+
+    /// Same as [`MonotoneCht::query`], but `x` must be
+    /// non-decreasing over the calls. Lines that are no longer
+    /// needed are removed, so do not call [`MonotoneCht::query`]
+    /// with a smaller `x` afterwards.
+    ///
+    /// 🕒 Amortized `O(1)`.
+    pub fn query_monotone(&mut self, x: T) -> Option<T> {
+        while self.lines.len() >= 2 && cross(&self.lines[0], &self.lines[1]) <= x {
+            self.lines.pop_front();
+        }
+        self.lines.front().map(|line| line.eval(x))
+    }
+}
+
+/// Convex hull trick with lines added in any order.
+///
+/// In the method descriptions, let `n` be the number of lines.
+#[derive(Clone, Debug, Default)]
+pub struct LineContainer<T> {
+    /// Slope to intercept and start, for the lines on the hull.
+    lines: BTreeMap<T, (T, T)>,
+    /// Start to slope, where the start of a line is
+    /// the smallest `x` where it is the minimum.
+    starts: BTreeMap<T, T>,
+}
+
+impl<T: LineNum> LineContainer<T> {
+    /// Returns an empty hull.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            lines: BTreeMap::new(),
+            starts: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the number of lines on the hull.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// Returns whether there is no line.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    /// Returns the line with the next larger slope than `a`.
+    fn larger(&self, a: T) -> Option<Line<T>> {
+        let (&a, &(b, _)) = self
+            .lines
+            .range((Bound::Excluded(a), Bound::Unbounded))
+            .next()?;
+        Some(Line { a, b })
+    }
+
+    /// Returns the line with the next smaller slope than `a`.
+    fn smaller(&self, a: T) -> Option<Line<T>> {
+        let (&a, &(b, _)) = self.lines.range(..a).next_back()?;
+        Some(Line { a, b })
+    }
+
+    fn remove(&mut self, a: T) {
+        let (_, start) = self.lines.remove(&a).unwrap();
+        self.starts.remove(&start);
+    }
+
+    /// Inserts `line`, whose neighbors are already correct.
+    fn insert(&mut self, line: Line<T>) {
+        let start = self.larger(line.a).map_or(T::MIN, |l| cross(&l, &line));
+        self.lines.insert(line.a, (line.b, start));
+        self.starts.insert(start, line.a);
+    }
+
+    /// Adds the line `y = ax + b`.
+    ///
+    /// 🕒 Amortized `O(log n)`.
+    pub fn add(&mut self, a: T, b: T) {
+        let line = Line { a, b };
+        if let Some(&(old, _)) = self.lines.get(&a) {
+            if old <= b {
+                return;
+            }
+            self.remove(a);
+        }
+        if let (Some(l1), Some(l3)) = (self.larger(a), self.smaller(a)) {
+            if is_useless(&l1, &line, &l3) {
+                return;
+            }
+        }
+        while let Some(l2) = self.larger(a) {
+            match self.larger(l2.a) {
+                Some(l1) if is_useless(&l1, &l2, &line) => self.remove(l2.a),
+                _ => break,
+            }
+        }
+        while let Some(l2) = self.smaller(a) {
+            match self.smaller(l2.a) {
+                Some(l3) if is_useless(&line, &l2, &l3) => self.remove(l2.a),
+                _ => break,
+            }
+        }
+        // the start of the next smaller line changes
+        let next = self.smaller(a);
+        if let Some(l3) = next {
+            self.remove(l3.a);
+        }
+        self.insert(line);
+        if let Some(l3) = next {
+            self.insert(l3);
+        }
+    }
+
+    /// Returns the minimum of `ax + b` over all lines,
+    /// or `None` if there is no line.
+    ///
+    /// 🕒 `O(log n)`.
+    #[must_use]
+    pub fn query(&self, x: T) -> Option<T> {
+        let (_, &a) = self.starts.range(..=x).next_back()?;
+        Some(
+            Line {
+                a,
+                b: self.lines[&a].0,
+            }
+            .eval(x),
+        )
+    }
+}
+
+/// Li Chao tree over a fixed set of `x`s.
+///
+/// In the method descriptions, let `n` be the number of `x`s.
+#[derive(Clone, Debug)]
+pub struct LiChaoTree<T> {
+    /// Sorted distinct `x`s, padded with the last one.
+    xs: Vec<T>,
+    n: usize,
+    line: Vec<Option<Line<T>>>,
+}
+
+impl<T: LineNum> LiChaoTree<T> {
+    /// Returns an empty tree that can be queried on `xs`.
+    ///
+    /// 🕒 `O(n log n)`.
+    #[must_use]
+    pub fn new(mut xs: Vec<T>) -> Self {
+        xs.sort_unstable();
+        xs.dedup();
+        let n = xs.len();
+        let size = n.next_power_of_two();
+        if let Some(&last) = xs.last() {
+            xs.resize(size, last);
+        }
+        Self {
+            xs,
+            n,
+            line: vec![None; size * 2],
+        }
+    }
+
+    /// Adds `line` to node `k`, which represents the `x`s
+    /// `xs[nl..nr]`.
+    fn insert(&mut self, mut k: usize, mut nl: usize, mut nr: usize, mut line: Line<T>) {
+        loop {
+            let Some(cur) = self.line[k] else {
+                self.line[k] = Some(line);
+                return;
+            };
+            let mid = nl + (nr - nl) / 2;
+            let mut cur = cur;
+            if line.eval(self.xs[mid]) < cur.eval(self.xs[mid]) {
+                std::mem::swap(&mut cur, &mut line);
+                self.line[k] = Some(cur);
+            }
+            if nr - nl == 1 {
+                return;
+            }
+            if line.eval(self.xs[nl]) < cur.eval(self.xs[nl]) {
+                (k, nr) = (k * 2, mid);
+            } else if line.eval(self.xs[nr - 1]) < cur.eval(self.xs[nr - 1]) {
+                (k, nl) = (k * 2 + 1, mid);
+            } else {
+                return;
+            }
+        }
+    }
+
+    /// Adds the line `y = ax + b`.
+    ///
+    /// 🕒 `O(log n)`.
+    pub fn add_line(&mut self, a: T, b: T) {
+        if self.n > 0 {
+            self.insert(1, 0, self.xs.len(), Line { a, b });
+        }
+    }
+
+    /// Adds the line `y = ax + b` only for the `x`s in `range`.
+    ///
+    /// 🕒 `O(log^2 n)`.
+    pub fn add_segment(&mut self, a: T, b: T, range: impl RangeBounds<T>) {
+        let line = Line { a, b };
+        let start = match range.start_bound() {
+            Bound::Included(x) => self.xs[..self.n].partition_point(|y| y < x),
+            Bound::Excluded(x) => self.xs[..self.n].partition_point(|y| y <= x),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(x) => self.xs[..self.n].partition_point(|y| y <= x),
+            Bound::Excluded(x) => self.xs[..self.n].partition_point(|y| y < x),
+            Bound::Unbounded => self.n,
+        };
+        let size = self.xs.len();
+        let (mut lx, mut rx) = (start + size, end + size);
+        let mut width = 1;
+        while lx < rx {
+            if lx & 1 == 1 {
+                let nl = lx * width - size;
+                self.insert(lx, nl, nl + width, line);
+                lx += 1;
+            }
+            if rx & 1 == 1 {
+                rx -= 1;
+                let nl = rx * width - size;
+                self.insert(rx, nl, nl + width, line);
+            }
+            lx >>= 1;
+            rx >>= 1;
+            width <<= 1;
+        }
+    }
+
+    /// Returns the minimum of `ax + b` over all lines covering `x`,
+    /// or `None` if there is no such line.
+    ///
+    /// 🕒 `O(log n)`.
+    ///
+    /// ⚠️ Panics if `x` is not one of the `x`s given in
+    /// [`LiChaoTree::new`].
+    #[must_use]
+    pub fn query(&self, x: T) -> Option<T> {
+        let i = self.xs[..self.n]
+            .binary_search(&x)
+            .unwrap_or_else(|_| panic!("{:?} is not a query point", x));
+        let mut k = i + self.xs.len();
+        let mut ans = None;
+        while k > 0 {
+            if let Some(line) = self.line[k] {
+                let y = line.eval(x);
+                ans = Some(ans.map_or(y, |v: T| v.min(y)));
+            }
+            k >>= 1;
+        }
+        ans
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn brute(lines: &[(i128, i128)], x: i128) -> Option<i128> {
+        lines.iter().map(|(a, b)| a * x + b).min()
+    }
+
+    /// Adds `lines` one by one, and the `i`-th of `segs` (a line on
+    /// `l..r`) to the Li Chao tree right after the `i`-th line,
+    /// checking the minimum at every `x` in `xs` each time.
+    fn do_test_cht(lines: &[(i128, i128)], segs: &[(i128, i128, i128, i128)], xs: &[i128]) {
+        let mut lc = LineContainer::new();
+        let mut lichao = LiChaoTree::new(xs.to_vec());
+        for (i, &(a, b)) in lines.iter().enumerate() {
+            lc.add(a, b);
+            lichao.add_line(a, b);
+            if let Some(&(a, b, l, r)) = segs.get(i) {
+                lichao.add_segment(a, b, l..r);
+            }
+            for &x in xs {
+                let seg_ans = segs[..segs.len().min(i + 1)]
+                    .iter()
+                    .filter(|s| s.2 <= x && x < s.3)
+                    .map(|s| s.0 * x + s.1)
+                    .min();
+                let ans = brute(&lines[..=i], x);
+                assert_eq!(lc.query(x), ans, "line {i}, x = {x}");
+                assert_eq!(
+                    lichao.query(x),
+                    ans.into_iter().chain(seg_ans).min(),
+                    "line {i}, x = {x}"
+                );
+            }
+        }
+
+        let mut sorted = lines.to_vec();
+        sorted.sort_by(|x, y| y.cmp(x));
+        let mut cht = MonotoneCht::new();
+        for &(a, b) in &sorted {
+            cht.add(a, b);
+        }
+        for &x in xs {
+            assert_eq!(cht.query(x), brute(lines, x), "x = {x}");
+        }
+        let mut xs = xs.to_vec();
+        xs.sort_unstable();
+        for &x in &xs {
+            assert_eq!(cht.query_monotone(x), brute(lines, x), "x = {x}");
+        }
+    }
+
+    #[test]
+    fn test_cht_small() {
+        let xs = (-10..=10).collect::<Vec<_>>();
+        do_test_cht(
+            &[
+                (2, 3),
+                (-1, 0),
+                (2, -5),
+                (0, 4),
+                (-1, 7),
+                (5, 30),
+                (1, -1),
+                (-3, -20),
+                (0, -2),
+            ],
+            &[
+                (-4, 1, -3, 4),
+                (6, 2, 0, 11),
+                (1, -30, -10, -9),
+                (-2, -9, 5, 5),
+                (0, -3, 2, 3),
+            ],
+            &xs,
+        );
+        do_test_cht(&[(0, 0)], &[], &[0]);
+        do_test_cht(&[(1, 0), (1, 0), (1, 0)], &[(1, 0, -5, 5)], &[-5, 0, 5]);
+    }
+
+    /// Adds every line with small coefficients, in a scrambled order,
+    /// so that lines are both kept and removed from the hulls.
+    #[test]
+    fn test_cht_all_lines() {
+        let lines = (0..49)
+            .map(|i| (i * 17 % 49 / 7 - 3, i * 17 % 7 - 3))
+            .collect::<Vec<_>>();
+        let segs = (0..49)
+            .map(|i| (i % 5 - 2, i % 9 - 8, i % 11 - 5, i % 7 - 1))
+            .collect::<Vec<_>>();
+        let xs = (-6..=6).collect::<Vec<_>>();
+        do_test_cht(&lines, &segs, &xs);
+    }
+
+    #[test]
+    fn test_cht_large() {
+        let a = 1_000_000_000_000_000_000;
+        let b = 1 << 120;
+        do_test_cht(
+            &[
+                (a, b),
+                (-a, b),
+                (0, b + 1),
+                (a - 1, -b),
+                (-a + 1, -b),
+                (7, -b - 1),
+                (a, -b),
+            ],
+            &[(-a, -b, -a, 0), (a, b, 1, a), (3, -b, 0, 2)],
+            &[-a, -a + 1, -1, 0, 1, 2, a - 1, a],
+        );
+    }
+
+    #[test]
+    fn test_cht_i64() {
+        let mut cht = MonotoneCht::<i64>::new();
+        let mut lc = LineContainer::<i64>::new();
+        for (a, b) in [
+            (1_000_000_000, i64::MAX / 2),
+            (0, 0),
+            (-1_000_000_000, i64::MIN / 2),
+        ] {
+            cht.add(a, b);
+            lc.add(a, b);
+        }
+        let x = 1_000_000_000;
+        assert_eq!(cht.query(0), Some(i64::MIN / 2));
+        assert_eq!(lc.query(0), Some(i64::MIN / 2));
+        assert_eq!(cht.query(-x), Some(i64::MIN / 2 + x * x));
+        assert_eq!(lc.query(-x), Some(i64::MIN / 2 + x * x));
+    }
+}
@@ -0,0 +1,582 @@
+//! [Mo's algorithm](https://cp-algorithms.com/data_structures/sqrt_decomposition.html#mos-algorithm)
+//! for offline range queries.
+//!
+//! # Usage
+//! Implement [`MoState`] for the state of a range `l..r`, which
+//! is initially `0..0`. The driver moves the range one element at
+//! a time and calls the hooks, then records [`MoState::answer`].
+//! Queries are half-open `(l, r)` meaning `l..r`, and the answers
+//! are returned in the original order.
+//!
+//! - [`mo`] sorts the queries into blocks of `n / sqrt(q)`.
+//! - [`mo_hilbert`] sorts the queries along a Hilbert curve,
+//!   which is usually faster.
+//! - [`mo_with_updates`] also handles point updates between
+//!   queries; implement [`MoUpdateState`] too.
+//! - [`mo_tree`] answers queries on the vertices of tree paths,
+//!   using `hld::Hld`.
+//!
+//! Each takes `O(n sqrt q)` hook calls, except [`mo_with_updates`]
+//! which takes `O(n^(5/3))` for `n`, `q`, and updates of similar size.
+//!
+//! # Example
+//! ```
+//! # use mo::{mo, MoState};
+//! /// Number of distinct values.
+//! struct Distinct { arr: Vec<usize>, cnt: Vec<usize>, distinct: usize }
+//! impl Distinct {
+//!     fn add(&mut self, i: usize) {
+//!         self.cnt[self.arr[i]] += 1;
+//!         if self.cnt[self.arr[i]] == 1 { self.distinct += 1; }
+//!     }
+//!     fn remove(&mut self, i: usize) {
+//!         self.cnt[self.arr[i]] -= 1;
+//!         if self.cnt[self.arr[i]] == 0 { self.distinct -= 1; }
+//!     }
+//! }
+//! impl MoState for Distinct {
+//!     type Answer = usize;
+//!     fn add_left(&mut self, i: usize) { self.add(i); }
+//!     fn add_right(&mut self, i: usize) { self.add(i); }
+//!     fn remove_left(&mut self, i: usize) { self.remove(i); }
+//!     fn remove_right(&mut self, i: usize) { self.remove(i); }
+//!     fn answer(&self) -> usize { self.distinct }
+//! }
+//!
+//! let arr = vec![1, 2, 1, 3, 2];
+//! let mut state = Distinct { arr, cnt: vec![0; 4], distinct: 0 };
+//! let ans = mo(5, &[(0, 3), (2, 5), (1, 1)], &mut state);
+//! assert_eq!(ans, vec![2, 3, 0]);
+//! ```
+
+extern crate hld;
+
+use hld::Hld;
+
+/// State of Mo's algorithm over a range `l..r`.
+pub trait MoState {
+    type Answer;
+    /// Extends `l+1..r` to `l..r`, where `i = l`.
+    fn add_left(&mut self, i: usize);
+    /// Extends `l..r-1` to `l..r`, where `i = r-1`.
+    fn add_right(&mut self, i: usize);
+    /// Shrinks `l..r` to `l+1..r`, where `i = l`.
+    fn remove_left(&mut self, i: usize);
+    /// Shrinks `l..r` to `l..r-1`, where `i = r-1`.
+    fn remove_right(&mut self, i: usize);
+    /// Returns the answer for the current range.
+    fn answer(&self) -> Self::Answer;
+}
+
+/// State of Mo's algorithm with updates.
+pub trait MoUpdateState: MoState {
+    /// Applies the `t`-th update when the range is `l..r`.
+    /// The same `t` is passed again to undo it, so a swap
+    /// between the array and the update works well.
+    fn toggle_update(&mut self, t: usize, l: usize, r: usize);
+}
+
+/// Moves the range `l..r` to `ql..qr`.
+fn move_to<S: MoState>(state: &mut S, (l, r): &mut (usize, usize), (ql, qr): (usize, usize)) {
+    while *l > ql {
+        *l -= 1;
+        state.add_left(*l);
+    }
+    while *r < qr {
+        state.add_right(*r);
+        *r += 1;
+    }
+    while *l < ql {
+        state.remove_left(*l);
+        *l += 1;
+    }
+    while *r > qr {
+        *r -= 1;
+        state.remove_right(*r);
+    }
+}
+
+/// Runs the queries in `order`.
+fn run<S: MoState>(queries: &[(usize, usize)], order: &[usize], state: &mut S) -> Vec<S::Answer> {
+    let mut ans = (0..queries.len()).map(|_| None).collect::<Vec<_>>();
+    let mut cur = (0, 0);
+    for &i in order {
+        move_to(state, &mut cur, queries[i]);
+        ans[i] = Some(state.answer());
+    }
+    ans.into_iter().map(Option::unwrap).collect()
+}
+
+fn check_queries(n: usize, queries: &[(usize, usize)]) {
+    for &(l, r) in queries {
+        assert!(l <= r && r <= n, "bad range {}..{} on {}", l, r, n);
+    }
+}
+
+/// Returns the block size `n / sqrt(q)` for `n` elements and `q` queries.
+fn block_size(n: usize, q: usize) -> usize {
+    let mut sqrt = 1;
+    while sqrt * sqrt < q {
+        sqrt += 1;
+    }
+    (n / sqrt).max(1)
+}
+
+/// Answers the queries on `0..n`, sorted into blocks.
+///
+/// 🕒 `O(n sqrt q)` hook calls.
+///
+/// ⚠️ Panics if a query is not within `0..n`.
+pub fn mo<S: MoState>(n: usize, queries: &[(usize, usize)], state: &mut S) -> Vec<S::Answer> {
+    check_queries(n, queries);
+    let b = block_size(n, queries.len());
+    let mut order = (0..queries.len()).collect::<Vec<_>>();
+    order.sort_by_key(|&i| {
+        let (l, r) = queries[i];
+        let block = l / b;
+        (block, if block & 1 == 0 { r } else { n - r })
+    });
+    run(queries, &order, state)
+}
+
+/// Returns the position of `(x, y)` on the Hilbert curve
+/// of the square of size `2^log`.
+fn hilbert(mut x: usize, mut y: usize, log: u32) -> u64 {
+    let size = 1usize << log;
+    let mut d = 0u64;
+    let mut s = size >> 1;
+    while s > 0 {
+        let rx = usize::from(x & s > 0);
+        let ry = usize::from(y & s > 0);
+        d += (s * s * ((3 * rx) ^ ry)) as u64;
+        if ry == 0 {
+            if rx == 1 {
+                x = size - 1 - x;
+                y = size - 1 - y;
+            }
+            std::mem::swap(&mut x, &mut y);
+        }
+        s >>= 1;
+    }
+    d
+}
+
+/// Answers the queries on `0..n`, sorted along a Hilbert curve.
+///
+/// 🕒 `O(n sqrt q)` hook calls.
+///
+/// ⚠️ Panics if a query is not within `0..n`.
+pub fn mo_hilbert<S: MoState>(
+    n: usize,
+    queries: &[(usize, usize)],
+    state: &mut S,
+) -> Vec<S::Answer> {
+    check_queries(n, queries);
+    let log = (n + 1).next_power_of_two().trailing_zeros();
+    let mut order = (0..queries.len()).collect::<Vec<_>>();
+    order.sort_by_cached_key(|&i| hilbert(queries[i].0, queries[i].1, log));
+    run(queries, &order, state)
+}
+
+/// Answers the queries `(l, r, t)` on `0..n`, where `t` is the
+/// number of updates applied before the query.
+///
+/// 🕒 `O(n^(5/3))` hook calls if `n`, `q`, and `updates` are similar.
+///
+/// ⚠️ Panics if a query is not within `0..n`, or `t > updates`.
+pub fn mo_with_updates<S: MoUpdateState>(
+    n: usize,
+    updates: usize,
+    queries: &[(usize, usize, usize)],
+    state: &mut S,
+) -> Vec<S::Answer> {
+    for &(l, r, t) in queries {
+        assert!(l <= r && r <= n, "bad range {}..{} on {}", l, r, n);
+        assert!(t <= updates, "time {} out of {}", t, updates);
+    }
+    let mut width = 1;
+    while width * width * width < n * n {
+        width += 1;
+    }
+    let mut order = (0..queries.len()).collect::<Vec<_>>();
+    order.sort_by_key(|&i| {
+        let (l, r, t) = queries[i];
+        (l / width, r / width, t)
+    });
+    let mut ans = (0..queries.len()).map(|_| None).collect::<Vec<_>>();
+    let mut cur = (0, 0);
+    let mut time = 0;
+    for i in order {
+        let (l, r, t) = queries[i];
+        move_to(state, &mut cur, (l, r));
+        while time < t {
+            state.toggle_update(time, l, r);
+            time += 1;
+        }
+        while time > t {
+            time -= 1;
+            state.toggle_update(time, l, r);
+        }
+        ans[i] = Some(state.answer());
+    }
+    ans.into_iter().map(Option::unwrap).collect()
+}
+
+/// Adds the vertices appearing once in the range.
+struct Toggle<'a, S> {
+    state: &'a mut S,
+    tour: Vec<usize>,
+    inside: Vec<bool>,
+}
+impl<S: MoState> Toggle<'_, S> {
+    fn toggle(&mut self, i: usize) {
+        let v = self.tour[i];
+        if self.inside[v] {
+            self.state.remove_right(v);
+        } else {
+            self.state.add_right(v);
+        }
+        self.inside[v] ^= true;
+    }
+}
+impl<S: MoState> MoState for Toggle<'_, S> {
+    type Answer = ();
+    fn add_left(&mut self, i: usize) {
+        self.toggle(i);
+    }
+    fn add_right(&mut self, i: usize) {
+        self.toggle(i);
+    }
+    fn remove_left(&mut self, i: usize) {
+        self.toggle(i);
+    }
+    fn remove_right(&mut self, i: usize) {
+        self.toggle(i);
+    }
+    fn answer(&self) {}
+}
+
+/// Answers the queries `(u, v)` on the vertices of the path
+/// between `u` and `v` in the tree of `hld`.
+///
+/// The hooks receive vertices instead of indices, and only
+/// `add_right` and `remove_right` are called. The vertices
+/// are added in no particular order.
+///
+/// 🕒 `O(n sqrt q)` hook calls.
+///
+/// ⚠️ Panics if a vertex is out of range.
+pub fn mo_tree<S: MoState>(hld: &Hld, queries: &[(usize, usize)], state: &mut S) -> Vec<S::Answer> {
+    let n = hld.len();
+    // Euler tour where each vertex appears when entering and leaving
+    let mut tour = vec![0; 2 * n];
+    let mut enter = vec![0; n];
+    let mut leave = vec![0; n];
+    for v in 0..n {
+        let (l, r) = hld.subtree(v);
+        // l - depth vertices are left before entering v
+        enter[v] = 2 * l - hld.depth(v) as usize;
+        leave[v] = enter[v] + 2 * (r - l + 1) - 1;
+        tour[enter[v]] = v;
+        tour[leave[v]] = v;
+    }
+    let mut ranges = Vec::with_capacity(queries.len());
+    let mut extra = Vec::with_capacity(queries.len());
+    for &(u, v) in queries {
+        let (u, v) = if enter[u] <= enter[v] { (u, v) } else { (v, u) };
+        let w = hld.lca(u, v);
+        if w == u {
+            ranges.push((enter[u], enter[v] + 1));
+            extra.push(None);
+        } else {
+            ranges.push((leave[u], enter[v] + 1));
+            extra.push(Some(w));
+        }
+    }
+
+    let b = block_size(2 * n, queries.len());
+    let mut order = (0..queries.len()).collect::<Vec<_>>();
+    order.sort_by_key(|&i| {
+        let (l, r) = ranges[i];
+        let block = l / b;
+        (block, if block & 1 == 0 { r } else { 2 * n - r })
+    });
+    let mut toggle = Toggle {
+        state,
+        tour,
+        inside: vec![false; n],
+    };
+    let mut ans = (0..queries.len()).map(|_| None).collect::<Vec<_>>();
+    let mut cur = (0, 0);
+    for i in order {
+        move_to(&mut toggle, &mut cur, ranges[i]);
+        if let Some(w) = extra[i] {
+            toggle.state.add_right(w);
+            ans[i] = Some(toggle.state.answer());
+            toggle.state.remove_right(w);
+        } else {
+            ans[i] = Some(toggle.state.answer());
+        }
+    }
+    ans.into_iter().map(Option::unwrap).collect()
+}
+
+#[cfg(test)]
+mod test {
+    extern crate edge_list;
+
+    use super::*;
+    use std::collections::BTreeSet;
+
+    /// Number of distinct values, checking that the hooks
+    /// are called on the right side.
+    struct Distinct {
+        arr: Vec<usize>,
+        updates: Vec<(usize, usize)>,
+        range: (usize, usize),
+        cnt: Vec<usize>,
+        count: usize,
+    }
+
+    impl Distinct {
+        fn new(arr: Vec<usize>, updates: Vec<(usize, usize)>) -> Self {
+            Self {
+                arr,
+                updates,
+                range: (0, 0),
+                cnt: vec![0; 10],
+                count: 0,
+            }
+        }
+        fn add(&mut self, x: usize) {
+            self.cnt[x] += 1;
+            if self.cnt[x] == 1 {
+                self.count += 1;
+            }
+        }
+        fn remove(&mut self, x: usize) {
+            self.cnt[x] -= 1;
+            if self.cnt[x] == 0 {
+                self.count -= 1;
+            }
+        }
+    }
+
+    impl MoState for Distinct {
+        type Answer = usize;
+        fn add_left(&mut self, i: usize) {
+            self.range.0 -= 1;
+            assert_eq!(self.range.0, i);
+            self.add(self.arr[i]);
+        }
+        fn add_right(&mut self, i: usize) {
+            assert_eq!(self.range.1, i);
+            self.range.1 += 1;
+            self.add(self.arr[i]);
+        }
+        fn remove_left(&mut self, i: usize) {
+            assert_eq!(self.range.0, i);
+            self.range.0 += 1;
+            self.remove(self.arr[i]);
+        }
+        fn remove_right(&mut self, i: usize) {
+            self.range.1 -= 1;
+            assert_eq!(self.range.1, i);
+            self.remove(self.arr[i]);
+        }
+        fn answer(&self) -> usize {
+            self.count
+        }
+    }
+
+    impl MoUpdateState for Distinct {
+        fn toggle_update(&mut self, t: usize, l: usize, r: usize) {
+            assert_eq!(self.range, (l, r));
+            let (pos, val) = &mut self.updates[t];
+            std::mem::swap(&mut self.arr[*pos], val);
+            let (pos, old) = self.updates[t];
+            if l <= pos && pos < r {
+                self.remove(old);
+                self.add(self.arr[pos]);
+            }
+        }
+    }
+
+    fn distinct(arr: &[usize]) -> usize {
+        arr.iter().collect::<BTreeSet<_>>().len()
+    }
+
+    fn do_test_mo(arr: &[usize], queries: &[(usize, usize)]) {
+        let expected = queries
+            .iter()
+            .map(|&(l, r)| distinct(&arr[l..r]))
+            .collect::<Vec<_>>();
+        let mut state = Distinct::new(arr.to_vec(), vec![]);
+        assert_eq!(mo(arr.len(), queries, &mut state), expected);
+        let mut state = Distinct::new(arr.to_vec(), vec![]);
+        assert_eq!(mo_hilbert(arr.len(), queries, &mut state), expected);
+    }
+
+    #[test]
+    fn test_mo() {
+        do_test_mo(
+            &[1, 3, 1, 4, 1, 5, 9, 2, 6, 5, 3, 5],
+            &[
+                (0, 12),
+                (3, 7),
+                (0, 0),
+                (5, 12),
+                (1, 2),
+                (2, 11),
+                (6, 6),
+                (0, 5),
+                (11, 12),
+                (4, 9),
+            ],
+        );
+        do_test_mo(&[], &[(0, 0), (0, 0)]);
+        // Every range, longest first, so that both ends move both ways.
+        let arr = [2, 7, 1, 8, 2, 8, 1, 8, 2, 8, 4, 5, 9, 0, 4, 5];
+        let n = arr.len();
+        let mut queries = (0..=n)
+            .flat_map(|l| (l..=n).map(move |r| (l, r)))
+            .collect::<Vec<_>>();
+        queries.sort_by_key(|&(l, r)| (std::cmp::Reverse(r - l), l));
+        do_test_mo(&arr, &queries);
+    }
+
+    fn do_test_mo_with_updates(
+        arr: &[usize],
+        updates: &[(usize, usize)],
+        queries: &[(usize, usize, usize)],
+    ) {
+        let expected = queries
+            .iter()
+            .map(|&(l, r, t)| {
+                let mut cur = arr.to_vec();
+                for &(i, x) in &updates[..t] {
+                    cur[i] = x;
+                }
+                distinct(&cur[l..r])
+            })
+            .collect::<Vec<_>>();
+        let mut state = Distinct::new(arr.to_vec(), updates.to_vec());
+        let ans = mo_with_updates(arr.len(), updates.len(), queries, &mut state);
+        assert_eq!(ans, expected);
+    }
+
+    #[test]
+    fn test_mo_with_updates() {
+        do_test_mo_with_updates(
+            &[1, 2, 1, 3, 2, 1, 4],
+            &[(0, 3), (2, 3), (6, 1), (3, 1), (0, 1)],
+            &[
+                (0, 7, 0),
+                (0, 7, 5),
+                (0, 3, 2),
+                (2, 5, 1),
+                (5, 7, 3),
+                (0, 4, 4),
+                (3, 3, 2),
+                (1, 7, 5),
+            ],
+        );
+        do_test_mo_with_updates(&[0], &[], &[(0, 1, 0)]);
+        // Every range at every time.
+        let arr = [3, 0, 3, 1, 4, 1, 0, 2];
+        let updates = [(1, 3), (4, 0), (1, 4), (7, 3), (0, 2), (5, 5)];
+        let n = arr.len();
+        let queries = (0..=n)
+            .flat_map(|l| (l..=n).flat_map(move |r| (0..=6).map(move |t| (l, r, t))))
+            .collect::<Vec<_>>();
+        do_test_mo_with_updates(&arr, &updates, &queries);
+    }
+
+    /// Number of distinct values on a set of vertices.
+    struct TreeDistinct {
+        val: Vec<usize>,
+        cnt: Vec<usize>,
+        count: usize,
+    }
+
+    impl MoState for TreeDistinct {
+        type Answer = usize;
+        fn add_left(&mut self, _: usize) {
+            unreachable!();
+        }
+        fn add_right(&mut self, v: usize) {
+            self.cnt[self.val[v]] += 1;
+            if self.cnt[self.val[v]] == 1 {
+                self.count += 1;
+            }
+        }
+        fn remove_left(&mut self, _: usize) {
+            unreachable!();
+        }
+        fn remove_right(&mut self, v: usize) {
+            self.cnt[self.val[v]] -= 1;
+            if self.cnt[self.val[v]] == 0 {
+                self.count -= 1;
+            }
+        }
+        fn answer(&self) -> usize {
+            self.count
+        }
+    }
+
+    #[test]
+    fn test_mo_tree() {
+        for parent in [
+            vec![
+                None,
+                Some(0),
+                Some(0),
+                Some(1),
+                Some(1),
+                Some(2),
+                Some(4),
+                Some(4),
+                Some(2),
+            ],
+            vec![None, Some(0), Some(1), Some(2), Some(3), Some(4), Some(5)],
+            vec![None, Some(0), Some(0), Some(0), Some(0), Some(0)],
+            vec![None],
+        ] {
+            let n = parent.len();
+            let mut tree = edge_list::UnweightedGraph::new(n);
+            for (v, p) in parent.iter().enumerate() {
+                if let Some(p) = *p {
+                    tree.bidirect(p, v, ());
+                }
+            }
+            let val = (0..n).map(|v| v * v % 5).collect::<Vec<_>>();
+            let path = |mut u: usize, mut v: usize| {
+                let mut up = vec![u];
+                while let Some(p) = parent[u] {
+                    up.push(p);
+                    u = p;
+                }
+                let mut vals = BTreeSet::new();
+                while !up.contains(&v) {
+                    vals.insert(val[v]);
+                    v = parent[v].unwrap();
+                }
+                for &w in &up[..=up.iter().position(|&w| w == v).unwrap()] {
+                    vals.insert(val[w]);
+                }
+                vals.len()
+            };
+            let queries = (0..n)
+                .flat_map(|u| (0..n).map(move |v| (u, v)))
+                .collect::<Vec<_>>();
+            let expected = queries.iter().map(|&(u, v)| path(u, v)).collect::<Vec<_>>();
+            let hld = Hld::new(&tree, 0);
+            let mut state = TreeDistinct {
+                val,
+                cnt: vec![0; 10],
+                count: 0,
+            };
+            assert_eq!(mo_tree(&hld, &queries, &mut state), expected);
+        }
+    }
+}
@@ -1,15 +1,47 @@
 //! 2-SAT.
+//!
+//! # Example
+//! ```
+//! # use two_sat::{Literal, TwoSat};
+//! let mut cnf = TwoSat::new(4);
+//! cnf.xor((0, true), (1, true));
+//! cnf.equal((1, true), (2, false));
+//! cnf.at_most_one(&[Literal::pos(0), Literal::pos(2), Literal::pos(3)]);
+//! cnf.set((3, true));
+//! assert_eq!(cnf.solution(), Some(vec![false, true, false, true]));
+//! ```
+//!
 //! # Examples
 //! - [LC 2 Sat](https://judge.yosupo.jp/problem/two_sat)
+//! ```ignore
+//! oj.read::<String>(); // p
+//! oj.read::<String>(); // cnf
+//! let (n, m) = (oj.usize(), oj.usize());
+//! let mut cnf = TwoSat::new(n);
+//! for _ in 0..m {
+//!     let x = Literal::from_dimacs(oj.i64());
+//!     let y = Literal::from_dimacs(oj.i64());
+//!     oj.i64(); // 0
+//!     cnf.clause(x, y);
+//! }
+//! let Some(sol) = cnf.solution() else {
+//!     oj.write("s UNSATISFIABLE").ln();
+//!     return;
+//! };
+//! oj.write("s SATISFIABLE").ln();
+//! oj.write("v").sp();
+//! for (i, x) in sol.into_iter().enumerate() {
+//!     oj.write(if x { i as i64 + 1 } else { -(i as i64) - 1 }).sp();
+//! }
+//! oj.write(0).ln();
+//! ```
 //! - [BOJ 11281 2-SAT - 4](https://www.acmicpc.net/problem/11281)
 //! ```ignore
 //! let mut cnf = TwoSat::new(oj.usize());
 //! for _ in 0..oj.usize() {
-//!     let x = oj.i32();
-//!     let (x, sign_x) = (x.abs() as usize - 1, x > 0);
-//!     let y = oj.i32();
-//!     let (y, sign_y) = (y.abs() as usize - 1, y > 0);
-//!     cnf.clause(x, sign_x, y, sign_y);
+//!     let x = Literal::from_dimacs(oj.i64());
+//!     let y = Literal::from_dimacs(oj.i64());
+//!     cnf.clause(x, y);
 //! }
 //! let Some(sol) = cnf.solution() else {
 //!     oj.write(0).ln();
@@ -24,85 +56,238 @@
 extern crate edge_list;
 extern crate scc;
 
+use std::convert::TryFrom;
 use std::fmt::Debug;
+use std::ops::Not;
+use std::str::FromStr;
 
 use edge_list::UnweightedGraph;
-use scc::make_scc;
+use scc::{make_scc, Scc};
+
+/// A literal, i.e. variable `var` if `sign` is true,
+/// otherwise its negation `~var`.
+///
+/// The methods of [`TwoSat`] take anything convertible into
+/// a literal, so `(var, sign)` pairs can be passed directly.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Literal {
+    /// The variable.
+    pub var: usize,
+    /// Whether the variable is not negated.
+    pub sign: bool,
+}
+
+impl Literal {
+    /// Returns the literal `var`.
+    #[must_use]
+    pub const fn pos(var: usize) -> Self {
+        Self { var, sign: true }
+    }
+
+    /// Returns the literal `~var`.
+    #[must_use]
+    pub const fn neg(var: usize) -> Self {
+        Self { var, sign: false }
+    }
+
+    /// Returns the literal of a signed DIMACS integer,
+    /// where `k` is variable `k-1`, and `-k` is its negation.
+    /// ```
+    /// # use two_sat::Literal;
+    /// assert_eq!(Literal::from_dimacs(3), Literal::pos(2));
+    /// assert_eq!(Literal::from_dimacs(-1), Literal::neg(0));
+    /// ```
+    ///
+    /// ⚠️ Panics if `x == 0`.
+    #[must_use]
+    pub fn from_dimacs(x: i64) -> Self {
+        assert_ne!(x, 0, "0 is not a literal");
+        let var = usize::try_from(x.unsigned_abs() - 1).unwrap();
+        Self { var, sign: x > 0 }
+    }
+
+    /// Returns the vertex of the implication graph.
+    const fn node(self) -> usize {
+        self.var * 2 + self.sign as usize
+    }
+}
+
+impl From<(usize, bool)> for Literal {
+    fn from((var, sign): (usize, bool)) -> Self {
+        Self { var, sign }
+    }
+}
+
+impl Not for Literal {
+    type Output = Self;
+    fn not(self) -> Self {
+        Self {
+            var: self.var,
+            sign: !self.sign,
+        }
+    }
+}
 
 /// 2-SAT interface.
+///
+/// In the method descriptions, `X` and `Y` are literals.
 #[derive(Clone, Debug)]
 pub struct TwoSat {
-    graph: UnweightedGraph,
+    var_count: usize,
+    /// The number of helper variables added by `at_most_one`.
+    /// As a literal, the `k`-th one is variable `usize::MAX - k`.
+    helper_count: usize,
+    /// The edges of the implication graph, where the vertices
+    /// of the helper variables are counted down from `u32::MAX`,
+    /// since more variables may be added after them.
+    implications: Vec<(u32, u32)>,
 }
 
 impl TwoSat {
     /// Initializes a 2-SAT interface with `var_count` variables.
     #[inline]
-    pub fn new(var_count: usize) -> Self {
+    #[must_use]
+    pub const fn new(var_count: usize) -> Self {
         Self {
-            graph: UnweightedGraph::new(var_count * 2),
+            var_count,
+            helper_count: 0,
+            implications: vec![],
         }
     }
 
-    /// Returns the number of variables.
+    /// Returns the number of variables,
+    /// not counting the helpers of [`TwoSat::at_most_one`].
     /// ```
     /// # use two_sat::TwoSat;
     /// let cnf = TwoSat::new(10);
     /// assert_eq!(cnf.num_vars(), 10);
     /// ```
     #[inline]
-    pub fn num_vars(&self) -> usize {
-        self.graph.vertex_count() >> 1
+    #[must_use]
+    pub const fn num_vars(&self) -> usize {
+        self.var_count
+    }
+
+    /// Adds a new variable, and returns it.
+    #[inline]
+    #[allow(clippy::missing_const_for_fn)] // `&mut` in `const fn` needs Rust 1.83
+    pub fn new_var(&mut self) -> usize {
+        self.var_count += 1;
+        self.var_count - 1
     }
 
+    /// Returns the vertex of `x` as stored in `implications`.
+    fn vertex(&self, x: Literal) -> u32 {
+        if x.var < self.var_count {
+            u32::try_from(x.node()).unwrap()
+        } else {
+            let k = usize::MAX - x.var;
+            debug_assert!(k < self.helper_count, "variable {} out of range", x.var);
+            u32::MAX - u32::try_from(k * 2 + usize::from(!x.sign)).unwrap()
+        }
+    }
+
+    #[inline]
+    fn imply(&mut self, x: Literal, y: Literal) {
+        let (x, y) = (self.vertex(x), self.vertex(y));
+        self.implications.push((x, y));
+    }
+
+    /// Adds a clause `X \/ Y`.
     #[inline]
-    fn var(v: usize, sign: bool) -> usize {
-        v * 2 + (sign as usize)
+    pub fn clause(&mut self, x: impl Into<Literal>, y: impl Into<Literal>) {
+        let (x, y) = (x.into(), y.into());
+        self.imply(!x, y);
+        self.imply(!y, x);
     }
 
-    /// Adds a clause `X \/ Y`,
-    /// where `X` is `x` if `sign_x` is true,
-    /// otherwise `~x`; and similarly for `Y`.
+    /// Adds `X => Y`, i.e. `~X \/ Y`.
     #[inline]
-    pub fn clause(&mut self, x: usize, sign_x: bool, y: usize, sign_y: bool) {
-        self.graph
-            .direct(Self::var(x, !sign_x), Self::var(y, sign_y), ());
-        self.graph
-            .direct(Self::var(y, !sign_y), Self::var(x, sign_x), ());
+    pub fn if_then(&mut self, x: impl Into<Literal>, y: impl Into<Literal>) {
+        self.clause(!x.into(), y);
     }
 
-    /// Adds `X => Y`, i.e. `~X \/ Y`,
-    /// where `X` is `x` if `sign_x` is true,
-    /// otherwise `~x`; and similarly for `Y`.
+    /// Adds `~(X /\ Y)`, i.e. `~X \/ ~Y`.
     #[inline]
-    pub fn if_then(&mut self, x: usize, sign_x: bool, y: usize, sign_y: bool) {
-        self.clause(x, !sign_x, y, sign_y)
+    pub fn not_both(&mut self, x: impl Into<Literal>, y: impl Into<Literal>) {
+        self.clause(!x.into(), !y.into());
     }
 
-    /// Adds `~(X /\ Y)`, i.e. `~X \/ ~Y`,
-    /// where `X` is `x` if `sign_x` is true,
-    /// otherwise `~x`; and similarly for `Y`.
+    /// Adds a unit clause `X`.
     #[inline]
-    pub fn not_both(&mut self, x: usize, sign_x: bool, y: usize, sign_y: bool) {
-        self.clause(x, !sign_x, y, !sign_y)
+    pub fn set(&mut self, x: impl Into<Literal>) {
+        let x = x.into();
+        self.imply(!x, x);
+    }
+
+    /// Adds `X <=> Y`.
+    #[inline]
+    pub fn equal(&mut self, x: impl Into<Literal>, y: impl Into<Literal>) {
+        let (x, y) = (x.into(), y.into());
+        self.if_then(x, y);
+        self.if_then(y, x);
+    }
+
+    /// Adds `X xor Y`, i.e. `X <=> ~Y`.
+    #[inline]
+    pub fn xor(&mut self, x: impl Into<Literal>, y: impl Into<Literal>) {
+        self.equal(x, !y.into());
+    }
+
+    /// Adds that at most one of `lits` is true.
+    ///
+    /// 🕒 `O(k)` clauses, where `k = lits.len()`. This uses
+    /// `k-1` helper variables, where the `i`-th one is whether any
+    /// of `lits[..=i]` is true. They are not counted by
+    /// [`TwoSat::num_vars`] or returned by [`TwoSat::solution`].
+    pub fn at_most_one(&mut self, lits: &[Literal]) {
+        let Some((&first, rest)) = lits.split_first() else {
+            return;
+        };
+        let mut prefix = first;
+        for &x in rest {
+            let next = Literal::pos(usize::MAX - self.helper_count);
+            self.helper_count += 1;
+            self.not_both(prefix, x);
+            self.if_then(prefix, next);
+            self.if_then(x, next);
+            prefix = next;
+        }
+    }
+
+    /// Returns the SCCs of the implication graph, where the helper
+    /// variables are numbered after the others.
+    fn scc(&self) -> Scc {
+        let n = self.var_count * 2;
+        let mut graph = UnweightedGraph::new(n + self.helper_count * 2);
+        let index = |x: u32| {
+            let x = x as usize;
+            if x < n {
+                x
+            } else {
+                n + ((u32::MAX as usize - x) ^ 1)
+            }
+        };
+        for &(x, y) in &self.implications {
+            graph.direct(index(x), index(y), ());
+        }
+        make_scc(&graph)
     }
 
     /// Returns whether the 2-CNF is satisfiable.
     /// ```
     /// # use two_sat::TwoSat;
     /// let mut cnf = TwoSat::new(2);
-    /// cnf.clause(0, true, 1, true);
-    /// cnf.clause(0, true, 1, false);
+    /// cnf.clause((0, true), (1, true));
+    /// cnf.clause((0, true), (1, false));
     /// assert!(cnf.is_satisfiable());
-    /// cnf.clause(0, false, 1, true);
-    /// cnf.clause(0, false, 1, false);
+    /// cnf.clause((0, false), (1, true));
+    /// cnf.clause((0, false), (1, false));
     /// assert!(!cnf.is_satisfiable());
     /// ```
+    #[must_use]
     pub fn is_satisfiable(&self) -> bool {
-        make_scc(&self.graph)
-            .indices
-            .chunks(2)
-            .all(|c| c[0] != c[1])
+        self.scc().indices.chunks(2).all(|c| c[0] != c[1])
     }
 
     /// If the 2-CNF is satisfiable, returns one of the solutions.
@@ -110,21 +295,22 @@ impl TwoSat {
     /// ```
     /// # use two_sat::TwoSat;
     /// let mut cnf = TwoSat::new(2);
-    /// cnf.clause(0, true, 1, true);
-    /// cnf.clause(0, true, 1, false);
+    /// cnf.clause((0, true), (1, true));
+    /// cnf.clause((0, true), (1, false));
     /// let sol = cnf.solution().unwrap(); // [true, false] or [true, true]
     /// assert!(sol[0]);
-    /// cnf.clause(0, false, 1, true);
-    /// cnf.clause(0, false, 1, false);
+    /// cnf.clause((0, false), (1, true));
+    /// cnf.clause((0, false), (1, false));
     /// assert!(cnf.solution().is_none());
     /// ```
+    #[must_use]
     pub fn solution(&self) -> Option<Vec<bool>> {
-        let scc = make_scc(&self.graph);
+        let scc = self.scc();
         if scc.indices.chunks(2).any(|c| c[0] == c[1]) {
             return None;
         }
 
-        let mut sol = vec![None; self.num_vars()];
+        let mut sol = vec![None; self.var_count + self.helper_count];
         // in reverse topological order,
         // if someone's not set, set it to the literal's sign
         for component in scc.components() {
@@ -136,6 +322,203 @@ impl TwoSat {
                 sol[id] = Some(lit & 1 == 0);
             }
         }
+        sol.truncate(self.var_count);
         Some(sol.into_iter().map(|b| b.unwrap()).collect())
     }
 }
+
+/// Parses a 2-CNF in the DIMACS format, i.e. a line `p cnf N M`,
+/// then `M` clauses of one or two signed integers, each ending with `0`.
+/// Lines starting with `c` are comments.
+/// ```
+/// # use two_sat::TwoSat;
+/// let cnf = "p cnf 2 3\n1 2 0\n-1 0\nc comment\n2 -1 0\n".parse::<TwoSat>().unwrap();
+/// assert_eq!(cnf.solution(), Some(vec![false, true]));
+/// assert!("p cnf 3 1\n1 2 3 0\n".parse::<TwoSat>().is_err());
+/// ```
+impl FromStr for TwoSat {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut tokens = s
+            .lines()
+            .filter(|line| !line.starts_with('c'))
+            .flat_map(str::split_whitespace);
+        if (tokens.next(), tokens.next()) != (Some("p"), Some("cnf")) {
+            return Err(());
+        }
+        let mut number = || tokens.next().ok_or(())?.parse::<i64>().map_err(|_| ());
+        let var_count = usize::try_from(number()?).map_err(|_| ())?;
+        let clause_count = number()?;
+        let mut cnf = Self::new(var_count);
+        for _ in 0..clause_count {
+            let mut lits = vec![];
+            loop {
+                match number()? {
+                    0 => break,
+                    x if x.unsigned_abs() > var_count as u64 || lits.len() == 2 => return Err(()),
+                    x => lits.push(Literal::from_dimacs(x)),
+                }
+            }
+            match lits[..] {
+                [x] => cnf.set(x),
+                [x, y] => cnf.clause(x, y),
+                _ => return Err(()),
+            }
+        }
+        Ok(cnf)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    type Lit = (usize, bool);
+
+    /// A constraint, as a predicate on an assignment.
+    type Constraint = Box<dyn Fn(&[bool]) -> bool>;
+
+    enum Query {
+        Clause(Lit, Lit),
+        IfThen(Lit, Lit),
+        NotBoth(Lit, Lit),
+        Set(Lit),
+        Equal(Lit, Lit),
+        Xor(Lit, Lit),
+        AtMostOne(Vec<Lit>),
+    }
+
+    /// Adds the constraints one by one, checking against
+    /// all assignments after each one.
+    fn do_test_two_sat(n: usize, queries: Vec<Query>) {
+        let mut cnf = TwoSat::new(n);
+        let value = |a: &[bool], (var, sign): Lit| a[var] == sign;
+        let mut constraints: Vec<Constraint> = vec![];
+        for (qi, query) in queries.into_iter().enumerate() {
+            match query {
+                Query::Clause(x, y) => {
+                    cnf.clause(x, y);
+                    constraints.push(Box::new(move |a| value(a, x) || value(a, y)));
+                }
+                Query::IfThen(x, y) => {
+                    cnf.if_then(x, y);
+                    constraints.push(Box::new(move |a| !value(a, x) || value(a, y)));
+                }
+                Query::NotBoth(x, y) => {
+                    cnf.not_both(x, y);
+                    constraints.push(Box::new(move |a| !(value(a, x) && value(a, y))));
+                }
+                Query::Set(x) => {
+                    cnf.set(x);
+                    constraints.push(Box::new(move |a| value(a, x)));
+                }
+                Query::Equal(x, y) => {
+                    cnf.equal(x, y);
+                    constraints.push(Box::new(move |a| value(a, x) == value(a, y)));
+                }
+                Query::Xor(x, y) => {
+                    cnf.xor(x, y);
+                    constraints.push(Box::new(move |a| value(a, x) != value(a, y)));
+                }
+                Query::AtMostOne(lits) => {
+                    cnf.at_most_one(&lits.iter().map(|&l| Literal::from(l)).collect::<Vec<_>>());
+                    constraints.push(Box::new(move |a| {
+                        lits.iter().filter(|&&l| value(a, l)).count() <= 1
+                    }));
+                }
+            }
+
+            let satisfiable = (0..1 << n).any(|mask: usize| {
+                let a = (0..n).map(|i| mask >> i & 1 == 1).collect::<Vec<_>>();
+                constraints.iter().all(|c| c(&a))
+            });
+            assert_eq!(cnf.is_satisfiable(), satisfiable, "query {qi}");
+            let sol = cnf.solution();
+            assert_eq!(sol.is_some(), satisfiable, "query {qi}");
+            if let Some(sol) = sol {
+                assert_eq!(sol.len(), n);
+                assert!(constraints.iter().all(|c| c(&sol)), "query {}", qi);
+            }
+            assert_eq!(cnf.num_vars(), n);
+        }
+    }
+
+    #[test]
+    fn test_two_sat() {
+        do_test_two_sat(
+            5,
+            vec![
+                Query::Clause((0, true), (1, false)),
+                Query::IfThen((1, false), (2, true)),
+                Query::AtMostOne(vec![(2, true), (3, true), (4, false)]),
+                Query::Xor((3, true), (4, true)),
+                Query::NotBoth((0, true), (2, true)),
+                Query::Equal((1, true), (4, false)),
+                Query::AtMostOne(vec![]),
+                Query::AtMostOne(vec![(0, false)]),
+                Query::Set((2, false)),
+                Query::Clause((3, true), (3, true)),
+                Query::IfThen((0, false), (0, true)),
+                // Now 4 must be both true and false.
+                Query::Equal((4, true), (1, true)),
+            ],
+        );
+        do_test_two_sat(
+            4,
+            vec![
+                Query::AtMostOne(vec![(0, true), (1, true), (2, true), (3, true)]),
+                Query::AtMostOne(vec![(0, false), (1, false), (2, false), (3, false)]),
+                Query::Clause((0, true), (1, true)),
+                Query::Clause((2, true), (3, true)),
+            ],
+        );
+        do_test_two_sat(
+            3,
+            vec![
+                Query::AtMostOne(vec![(0, true), (0, true)]),
+                Query::Xor((0, true), (1, true)),
+                Query::Xor((1, true), (2, true)),
+                Query::Xor((2, true), (0, true)),
+            ],
+        );
+        do_test_two_sat(1, vec![Query::Set((0, true)), Query::Set((0, false))]);
+    }
+
+    #[test]
+    fn test_new_var_after_helpers() {
+        let mut cnf = TwoSat::new(2);
+        cnf.at_most_one(&[Literal::pos(0), Literal::pos(1)]);
+        let x = cnf.new_var();
+        assert_eq!((x, cnf.num_vars()), (2, 3));
+        cnf.set((1, true));
+        cnf.equal((1, true), (x, false));
+        assert_eq!(cnf.solution(), Some(vec![false, true, false]));
+        cnf.set((x, true));
+        assert_eq!(cnf.solution(), None);
+    }
+
+    #[test]
+    fn test_dimacs() {
+        assert_eq!(Literal::from_dimacs(1), Literal::pos(0));
+        assert_eq!(!Literal::from_dimacs(-5), Literal::pos(4));
+        let cnf = "c header\np cnf 2 3\n1 -2 0\n2 0\n-1 0\n"
+            .parse::<TwoSat>()
+            .unwrap();
+        assert_eq!(cnf.num_vars(), 2);
+        assert_eq!(cnf.solution(), None);
+        let cnf = "p cnf 3 2\n1 -2 0 3 0".parse::<TwoSat>().unwrap();
+        let sol = cnf.solution().unwrap();
+        assert!((sol[0] || !sol[1]) && sol[2]);
+        for bad in [
+            "",
+            "p cnf 2",
+            "p cnf 2 1\n1 3 0",
+            "p cnf 2 1\n0",
+            "p cnf 2 1\n1 x 0",
+            "q cnf 1 0",
+        ] {
+            assert!(bad.parse::<TwoSat>().is_err(), "{}", bad);
+        }
+    }
+}